@@ -1,2 +1,11 @@
 #!/usr/bin/env bash
+set -euo pipefail
+
+# The integration tests deploy real ICRC ledgers; fetch the same version that the factory types mirror.
+LEDGER_WASM_URL="https://download.dfinity.systems/ic/e446c64d99a97e38166be23ff2bfade997d15ff7/canisters/ic-icrc1-ledger.wasm.gz"
+"$(dirname "$0")/download-immutable.sh" "$LEDGER_WASM_URL" target/ic-icrc1-ledger.wasm.gz
+
+# ICP payments are tested against a stand-in CMC.
+cargo build --locked --target wasm32-unknown-unknown --release -p cmc-stub
+
 RUST_BACKTRACE=1 RUSTFLAGS="-D warnings" cargo test --all-features
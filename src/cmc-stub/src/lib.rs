@@ -0,0 +1,202 @@
+//! A stand-in for the cycles minting canister (CMC), for the integration tests of the factory.
+//!
+//! It implements just enough of the CMC interface for ICP payments: a fixed ICP/XDR conversion
+//! rate, and `notify_top_up`, which reads the transfer from the ICRC ledger standing in for the ICP
+//! ledger and deposits the matching cycles into the canister to top up. Tests can make
+//! `notify_top_up` fail with `set_notify_rejected`, as the CMC does while it cannot convert.
+use std::{
+    cell::{Cell, RefCell},
+    collections::BTreeMap,
+};
+
+use candid::{CandidType, Nat, Principal};
+use ic_cdk::{
+    api::{
+        management_canister::main::{deposit_cycles, CanisterIdRecord},
+        time,
+    },
+    id, init, query, update,
+};
+use icrc_ledger_types::{
+    icrc1::{
+        account::{Account, Subaccount},
+        transfer::Memo,
+    },
+    icrc3::transactions::{GetTransactionsRequest, GetTransactionsResponse, Transfer},
+};
+use serde::Deserialize;
+use serde_bytes::ByteBuf;
+
+/// Memo the CMC expects on transfers that top up a canister, `TPUP` as a little-endian `u64`.
+const MEMO_TOP_UP_CANISTER: u64 = 0x5055_5054;
+
+#[derive(CandidType, Deserialize, Clone, Copy)]
+struct InitArgs {
+    /// Ledger standing in for the ICP ledger.
+    ledger: Principal,
+    xdr_permyriad_per_icp: u64,
+}
+
+#[derive(CandidType, Deserialize)]
+struct IcpXdrConversionRate {
+    xdr_permyriad_per_icp: u64,
+    timestamp_seconds: u64,
+}
+
+#[derive(CandidType, Deserialize)]
+struct IcpXdrConversionRateResponse {
+    data: IcpXdrConversionRate,
+    hash_tree: ByteBuf,
+    certificate: ByteBuf,
+}
+
+#[derive(CandidType, Deserialize)]
+struct NotifyTopUpArg {
+    block_index: u64,
+    canister_id: Principal,
+}
+
+#[derive(CandidType, Deserialize)]
+enum NotifyError {
+    InvalidTransaction(String),
+    Other {
+        error_code: u64,
+        error_message: String,
+    },
+}
+
+#[derive(CandidType, Deserialize)]
+enum NotifyTopUpResult {
+    Ok(Nat),
+    Err(NotifyError),
+}
+
+thread_local! {
+    static CONFIG: RefCell<Option<InitArgs>> = const { RefCell::new(None) };
+
+    /// Cycles minted for every block already converted, so that repeated notifications are
+    /// answered without minting twice, as the CMC does.
+    static NOTIFIED: RefCell<BTreeMap<u64, u128>> = const { RefCell::new(BTreeMap::new()) };
+
+    /// Whether `notify_top_up` currently fails without looking at the transfer.
+    static NOTIFY_REJECTED: Cell<bool> = const { Cell::new(false) };
+}
+
+fn config() -> InitArgs {
+    CONFIG.with(|config| config.borrow().expect("the stub is initialized"))
+}
+
+#[init]
+fn init(args: InitArgs) {
+    CONFIG.with(|config| *config.borrow_mut() = Some(args));
+}
+
+#[query]
+fn get_icp_xdr_conversion_rate() -> IcpXdrConversionRateResponse {
+    IcpXdrConversionRateResponse {
+        data: IcpXdrConversionRate {
+            xdr_permyriad_per_icp: config().xdr_permyriad_per_icp,
+            timestamp_seconds: time() / 1_000_000_000,
+        },
+        hash_tree: ByteBuf::new(),
+        certificate: ByteBuf::new(),
+    }
+}
+
+#[update]
+fn set_notify_rejected(rejected: bool) {
+    NOTIFY_REJECTED.with(|cell| cell.set(rejected));
+}
+
+#[update]
+async fn notify_top_up(arg: NotifyTopUpArg) -> NotifyTopUpResult {
+    if NOTIFY_REJECTED.with(Cell::get) {
+        return NotifyTopUpResult::Err(other("The CMC is unavailable"));
+    }
+
+    match top_up(arg).await {
+        Ok(cycles) => NotifyTopUpResult::Ok(Nat::from(cycles)),
+        Err(err) => NotifyTopUpResult::Err(err),
+    }
+}
+
+async fn top_up(arg: NotifyTopUpArg) -> Result<u128, NotifyError> {
+    let NotifyTopUpArg {
+        block_index,
+        canister_id,
+    } = arg;
+
+    if let Some(cycles) = NOTIFIED.with(|notified| notified.borrow().get(&block_index).copied()) {
+        return Ok(cycles);
+    }
+
+    let config = config();
+    let transfer = get_transfer(config.ledger, block_index).await?;
+
+    let expected_to = Account {
+        owner: id(),
+        subaccount: Some(principal_to_subaccount(canister_id)),
+    };
+    if transfer.to != expected_to {
+        return Err(NotifyError::InvalidTransaction(
+            "Destination account does not match the canister".to_string(),
+        ));
+    }
+
+    if transfer.memo != Some(Memo::from(MEMO_TOP_UP_CANISTER.to_le_bytes().to_vec())) {
+        return Err(NotifyError::InvalidTransaction(
+            "Transfer memo is not a top-up memo".to_string(),
+        ));
+    }
+
+    let e8s = u128::try_from(transfer.amount.0).map_err(|_| other("Amount is too large"))?;
+    let cycles = e8s * u128::from(config.xdr_permyriad_per_icp);
+
+    deposit_cycles(CanisterIdRecord { canister_id }, cycles)
+        .await
+        .map_err(|(_, msg)| other(&msg))?;
+
+    NOTIFIED.with(|notified| notified.borrow_mut().insert(block_index, cycles));
+
+    Ok(cycles)
+}
+
+/// Reads the transfer recorded in `block_index` of `ledger`.
+async fn get_transfer(ledger: Principal, block_index: u64) -> Result<Transfer, NotifyError> {
+    let request = GetTransactionsRequest {
+        start: Nat::from(block_index),
+        length: Nat::from(1u8),
+    };
+
+    let (response,) = ic_cdk::call::<(GetTransactionsRequest,), (GetTransactionsResponse,)>(
+        ledger,
+        "get_transactions",
+        (request,),
+    )
+    .await
+    .map_err(|(_, msg)| other(&msg))?;
+
+    response
+        .transactions
+        .into_iter()
+        .next()
+        .and_then(|transaction| transaction.transfer)
+        .ok_or_else(|| NotifyError::InvalidTransaction("Block is not a transfer".to_string()))
+}
+
+fn other(message: &str) -> NotifyError {
+    NotifyError::Other {
+        error_code: 0,
+        error_message: message.to_string(),
+    }
+}
+
+/// The subaccount that top-ups of `principal` are sent to: the length of the principal followed
+/// by its bytes.
+fn principal_to_subaccount(principal: Principal) -> Subaccount {
+    let bytes = principal.as_slice();
+    let mut subaccount = [0; 32];
+    subaccount[0] = u8::try_from(bytes.len()).expect("principals are at most 29 bytes long");
+    subaccount[1..=bytes.len()].copy_from_slice(bytes);
+    subaccount
+}
@@ -0,0 +1,113 @@
+use candid::Principal;
+use ic_cdk::api::time;
+
+use crate::{
+    events::record_event,
+    state::{mutate_state, read_config, read_state, update_config, State},
+    types::{
+        access_list::{AccessList, AccessListEntry, CreationAccess},
+        args::access_list::SetAccessListArgs,
+        event::EventKind,
+        memory::AccessListMap,
+        results::create_canister::CreateCanisterError,
+        stored_principal::StoredPrincipal,
+    },
+};
+
+/// Largest number of principals returned by a single call of [`list_access_list`].
+pub const MAX_ACCESS_LIST_ENTRIES_PER_PAGE: u64 = 100;
+
+/// Returns who may create canisters.
+#[must_use]
+pub fn creation_access() -> CreationAccess {
+    read_config(|config| config.creation_access.unwrap_or_default())
+}
+
+/// Sets who may create canisters, and records the new configuration.
+pub fn set_creation_access(access: CreationAccess) {
+    update_config(|config| config.creation_access = Some(access));
+}
+
+/// Refuses the canister creations of `caller` if it is denylisted, or if creations are limited to
+/// the allowlist and it is not on it.
+///
+/// # Errors
+/// - `Denylisted` if the caller is on the creation denylist.
+/// - `NotAllowlisted` if creations are allowlist-only and the caller is not on the allowlist.
+pub fn check_creation_access(caller: Principal) -> Result<(), CreateCanisterError> {
+    let principal = StoredPrincipal(caller);
+    let (denylisted, allowlisted) = read_state(|s| {
+        (
+            s.creation_denylist.contains_key(&principal),
+            s.creation_allowlist.contains_key(&principal),
+        )
+    });
+
+    if denylisted {
+        return Err(CreateCanisterError::Denylisted);
+    }
+
+    match creation_access() {
+        CreationAccess::AllowlistOnly if !allowlisted => Err(CreateCanisterError::NotAllowlisted),
+        _ => Ok(()),
+    }
+}
+
+/// Adds principals to a creation access list, or removes them from it; principals already listed
+/// keep the time they were first listed at.
+pub fn set_access_list(list: AccessList, args: SetAccessListArgs) {
+    let now = time();
+
+    mutate_state(|s| {
+        let map = access_list_mut(s, list);
+        for principal in &args.principals {
+            let principal = StoredPrincipal(*principal);
+            if args.listed {
+                if !map.contains_key(&principal) {
+                    map.insert(principal, now);
+                }
+            } else {
+                map.remove(&principal);
+            }
+        }
+    });
+
+    record_event(EventKind::CreationAccessListUpdated {
+        list,
+        principals: args.principals,
+        listed: args.listed,
+    });
+}
+
+/// Returns at most [`MAX_ACCESS_LIST_ENTRIES_PER_PAGE`] principals on a creation access list,
+/// sorted by principal.
+#[must_use]
+pub fn list_access_list(list: AccessList, offset: u64, limit: u64) -> Vec<AccessListEntry> {
+    let limit = limit.min(MAX_ACCESS_LIST_ENTRIES_PER_PAGE);
+
+    read_state(|s| {
+        let map = match list {
+            AccessList::Allowlist => &s.creation_allowlist,
+            AccessList::Denylist => &s.creation_denylist,
+        };
+
+        map.keys()
+            .skip(usize::try_from(offset).unwrap_or(usize::MAX))
+            .take(usize::try_from(limit).unwrap_or(usize::MAX))
+            .filter_map(|principal| {
+                let listed_at = map.get(&principal)?;
+                Some(AccessListEntry {
+                    principal: principal.0,
+                    listed_at,
+                })
+            })
+            .collect()
+    })
+}
+
+fn access_list_mut(s: &mut State, list: AccessList) -> &mut AccessListMap {
+    match list {
+        AccessList::Allowlist => &mut s.creation_allowlist,
+        AccessList::Denylist => &mut s.creation_denylist,
+    }
+}
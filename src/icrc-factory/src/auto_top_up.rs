@@ -0,0 +1,293 @@
+use std::{
+    cell::{Cell, RefCell},
+    ops::Bound,
+    time::Duration,
+};
+
+use candid::{Nat, Principal};
+use ic_cdk::caller;
+use ic_cdk_timers::set_timer_interval;
+
+use crate::{
+    events::record_event,
+    ledger::list_archives,
+    methods::SignerMethods,
+    mgmt::{deposit_cycles_to, get_canister_status},
+    prepaid::{credit, debit, prepaid_balance},
+    state::{mutate_state, read_state},
+    top_up::MIN_TOP_UP_CYCLES,
+    types::{
+        args::auto_top_up::SetAutoTopUpArgs,
+        auto_top_up::AutoTopUp,
+        candid::Candid,
+        event::EventKind,
+        prepaid::PrepaidReason,
+        results::create_canister::{CreateCanisterError, SetCanisterResult},
+        stored_principal::StoredPrincipal,
+        user_canister::UserCanisterKind,
+    },
+    user_canister::{find_user_canister, get_managed_user_canister, get_owned_user_canister},
+};
+
+/// How often a batch of subscriptions is checked.
+const AUTO_TOP_UP_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// Number of subscriptions checked per timer tick, to bound the number of outstanding calls.
+const AUTO_TOP_UP_BATCH_SIZE: usize = 20;
+
+thread_local! {
+    /// Last subscription checked; the next batch starts right after it.
+    static CURSOR: RefCell<Option<StoredPrincipal>> = const { RefCell::new(None) };
+    static RUNNING: Cell<bool> = const { Cell::new(false) };
+}
+
+pub fn set_auto_top_up(args: SetAutoTopUpArgs) -> SetCanisterResult {
+    let owner = caller();
+    let SetAutoTopUpArgs {
+        canister_id,
+        threshold,
+        refill,
+    } = args;
+
+    if refill < MIN_TOP_UP_CYCLES {
+        return SetCanisterResult::Err(CreateCanisterError::InvalidTopUpAmount);
+    }
+
+    // The factory polls `canister_status`, so it must still control the canister.
+    if let Err(err) = read_state(|s| get_managed_user_canister(s, owner, canister_id)) {
+        return SetCanisterResult::Err(err);
+    }
+
+    mutate_state(|s| {
+        s.auto_top_ups.insert(
+            StoredPrincipal(canister_id),
+            Candid(AutoTopUp {
+                canister_id,
+                owner,
+                threshold,
+                refill,
+                balance_exhausted: false,
+            }),
+        );
+    });
+
+    record_event(EventKind::AutoTopUpSet {
+        canister_id,
+        threshold,
+        refill,
+    });
+
+    SetCanisterResult::Ok()
+}
+
+pub fn remove_auto_top_up(canister_id: Principal) -> SetCanisterResult {
+    if let Err(err) = read_state(|s| get_owned_user_canister(s, caller(), canister_id)) {
+        return SetCanisterResult::Err(err);
+    }
+
+    if drop_auto_top_up(canister_id).is_none() {
+        return SetCanisterResult::Err(CreateCanisterError::NoAutoTopUp);
+    }
+
+    record_event(EventKind::AutoTopUpRemoved { canister_id });
+
+    SetCanisterResult::Ok()
+}
+
+/// Removes the subscription of a canister, e.g. once the factory can no longer poll it.
+pub fn drop_auto_top_up(canister_id: Principal) -> Option<AutoTopUp> {
+    mutate_state(|s| s.auto_top_ups.remove(&StoredPrincipal(canister_id)))
+        .map(|Candid(subscription)| subscription)
+}
+
+#[must_use]
+pub fn list_auto_top_ups(owner: Principal) -> Vec<AutoTopUp> {
+    read_state(|s| {
+        s.auto_top_ups
+            .values()
+            .map(|Candid(subscription)| subscription)
+            .filter(|subscription| subscription.owner == owner)
+            .collect()
+    })
+}
+
+/// Starts polling subscriptions. Timers do not survive upgrades, so this runs on every install and
+/// upgrade.
+pub fn start_auto_top_up_timer() {
+    set_timer_interval(AUTO_TOP_UP_INTERVAL, || ic_cdk::spawn(check_next_batch()));
+}
+
+/// Resets the running flag even if a check traps midway.
+struct RunningGuard;
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        RUNNING.with(|running| running.set(false));
+    }
+}
+
+async fn check_next_batch() {
+    if RUNNING.with(|running| running.replace(true)) {
+        return;
+    }
+    let _guard = RunningGuard;
+
+    for subscription in next_batch() {
+        check_subscription(subscription).await;
+    }
+}
+
+/// Returns the subscriptions following the cursor, wrapping around once all have been checked.
+fn next_batch() -> Vec<AutoTopUp> {
+    let start = CURSOR.with(|cursor| cursor.borrow().map_or(Bound::Unbounded, Bound::Excluded));
+
+    let batch: Vec<AutoTopUp> = read_state(|s| {
+        s.auto_top_ups
+            .values_range((start, Bound::Unbounded))
+            .take(AUTO_TOP_UP_BATCH_SIZE)
+            .map(|Candid(subscription)| subscription)
+            .collect()
+    });
+
+    let next_cursor = if batch.len() < AUTO_TOP_UP_BATCH_SIZE {
+        None
+    } else {
+        batch.last().map(|s| StoredPrincipal(s.canister_id))
+    };
+    CURSOR.with(|cursor| *cursor.borrow_mut() = next_cursor);
+
+    batch
+}
+
+async fn check_subscription(subscription: AutoTopUp) {
+    let canister_id = subscription.canister_id;
+
+    let Some((_, user_canister)) = read_state(|s| find_user_canister(s, canister_id)) else {
+        drop_auto_top_up(canister_id);
+        return;
+    };
+
+    let mut targets = vec![canister_id];
+    if user_canister.kind == UserCanisterKind::IcrcLedger {
+        match list_archives(canister_id).await {
+            Ok(archives) => targets.extend(archives),
+            Err(reason) => record_event(EventKind::AutoTopUpFailed {
+                canister_id,
+                reason,
+            }),
+        }
+    }
+
+    for target in targets {
+        if !top_up_if_needed(&subscription, target).await {
+            return;
+        }
+    }
+}
+
+/// Tops up `target` from the subscription owner's prepaid balance if it is below the threshold.
+///
+/// Returns `false` once the prepaid balance is exhausted, so that no further target is tried.
+async fn top_up_if_needed(subscription: &AutoTopUp, target: Principal) -> bool {
+    let cycles = match get_canister_status(target).await {
+        Ok(status) => status.cycles,
+        Err(reason) => {
+            record_event(EventKind::AutoTopUpFailed {
+                canister_id: target,
+                reason,
+            });
+            return true;
+        }
+    };
+
+    if cycles >= Nat::from(subscription.threshold) {
+        return true;
+    }
+
+    let owner = subscription.owner;
+    let required = subscription
+        .refill
+        .saturating_add(SignerMethods::TopUpCanister.fee_for(owner, None));
+
+    if debit(
+        owner,
+        required,
+        PrepaidReason::AutoTopUp {
+            canister_id: target,
+        },
+    )
+    .is_err()
+    {
+        notify_balance_exhausted(subscription.canister_id, target, owner, required);
+        return false;
+    }
+
+    if let Err(reason) = deposit_cycles_to(target, subscription.refill.into()).await {
+        credit(
+            owner,
+            required,
+            PrepaidReason::AutoTopUpReverted {
+                canister_id: target,
+            },
+        );
+        record_event(EventKind::AutoTopUpFailed {
+            canister_id: target,
+            reason,
+        });
+        return true;
+    }
+
+    set_balance_exhausted(subscription.canister_id, false);
+
+    record_event(EventKind::AutoToppedUp {
+        owner,
+        canister_id: target,
+        cycles: subscription.refill,
+    });
+
+    true
+}
+
+/// Records a `PrepaidBalanceExhausted` event, once per exhaustion.
+fn notify_balance_exhausted(
+    canister_id: Principal,
+    target: Principal,
+    owner: Principal,
+    required: u64,
+) {
+    let already_notified = read_state(|s| {
+        s.auto_top_ups
+            .get(&StoredPrincipal(canister_id))
+            .is_some_and(|Candid(subscription)| subscription.balance_exhausted)
+    });
+
+    if already_notified {
+        return;
+    }
+
+    set_balance_exhausted(canister_id, true);
+
+    record_event(EventKind::PrepaidBalanceExhausted {
+        owner,
+        canister_id: target,
+        balance: prepaid_balance(owner),
+        required,
+    });
+}
+
+fn set_balance_exhausted(canister_id: Principal, balance_exhausted: bool) {
+    mutate_state(|s| {
+        let key = StoredPrincipal(canister_id);
+        if let Some(Candid(subscription)) = s.auto_top_ups.get(&key) {
+            if subscription.balance_exhausted != balance_exhausted {
+                s.auto_top_ups.insert(
+                    key,
+                    Candid(AutoTopUp {
+                        balance_exhausted,
+                        ..subscription
+                    }),
+                );
+            }
+        }
+    });
+}
@@ -1,13 +1,64 @@
-use candid::Encode;
+use candid::{Encode, Principal};
+use ic_cdk::caller;
 
 use crate::{
-    get_stored_ledger_wasm, types::args::create_canister::UpgradeLedgerCanisterArgs, upgrade_wasm,
+    get_stored_ledger_wasm,
+    logging::LogContext,
+    pause::{ensure_not_paused, PausableOperation},
+    rate_limit::{check_rate_limit, release_rate_limit},
+    state::read_state,
+    types::{args::create_canister::UpgradeLedgerCanisterArgs, user_canister::UserCanisterKind},
+    upgrade_wasm,
+    user_canister::get_managed_user_canister,
     CreateCanisterError, SetCanisterResult,
 };
 
+/// Admits a ledger upgrade requested by `caller`, counting it against the rate limits.
+///
+/// Returns the time the upgrade is counted at, as [`check_rate_limit`] does.
+///
+/// # Errors
+/// - `Paused` if upgrades are paused.
+/// - `RateLimited` if the caller, or all principals together, made too many calls lately.
+fn check_upgrade_allowed(caller: Principal) -> Result<u64, CreateCanisterError> {
+    ensure_not_paused(PausableOperation::Upgrades)?;
+    check_rate_limit(caller)
+}
+
+/// Upgrades a ledger of the caller, once admitted by [`check_upgrade_allowed`]. An upgrade that
+/// fails does not count against the rate limits.
 pub async fn upgrade_ledger_canister(args: UpgradeLedgerCanisterArgs) -> SetCanisterResult {
+    let caller = caller();
+
+    // Only ledgers take the ledger WASM; an index of the caller must not be reinstalled with it.
+    match read_state(|s| get_managed_user_canister(s, caller, args.ledger_id)) {
+        Ok(canister) if canister.kind != UserCanisterKind::IcrcLedger => {
+            return SetCanisterResult::Err(CreateCanisterError::NotALedger);
+        }
+        Ok(_) => {}
+        Err(err) => return SetCanisterResult::Err(err),
+    }
+
+    let counted_at = match check_upgrade_allowed(caller) {
+        Ok(counted_at) => counted_at,
+        Err(err) => return SetCanisterResult::Err(err),
+    };
+
+    let result = upgrade_managed_ledger(args).await;
+
+    if result != SetCanisterResult::Ok() {
+        release_rate_limit(caller, counted_at);
+    }
+
+    result
+}
+
+async fn upgrade_managed_ledger(args: UpgradeLedgerCanisterArgs) -> SetCanisterResult {
+    let log = LogContext::start(Some(args.ledger_id), "Upgrading ICRC ledger");
+
     let ledger_wasm = get_stored_ledger_wasm();
     if ledger_wasm.is_empty() {
+        log.error("No ledger WASM stored");
         return SetCanisterResult::Err(CreateCanisterError::NoWasmStored);
     }
 
@@ -16,15 +67,22 @@ pub async fn upgrade_ledger_canister(args: UpgradeLedgerCanisterArgs) -> SetCani
     let arg = match Encode!(&upgrade_arg) {
         Ok(arg) => arg,
         Err(e) => {
+            log.error(&format!("Failed to encode upgrade args: {e}"));
             return SetCanisterResult::Err(CreateCanisterError::InitArgsEncodingFailed(format!(
                 "Failed to encode upgrade args: {e}",
-            )))
+            )));
         }
     };
 
+    log.debug(&format!(
+        "Upgrading to the ledger WASM of {} bytes",
+        ledger_wasm.len()
+    ));
     if let Err(err) = upgrade_wasm(args.ledger_id, ledger_wasm, arg).await {
+        log.error(&format!("WASM upgrade failed: {err}"));
         return SetCanisterResult::Err(CreateCanisterError::WasmInstallationFailed(err));
     }
+    log.info("Ledger upgraded");
 
     SetCanisterResult::Ok()
 }
@@ -0,0 +1,292 @@
+use std::cell::{Cell, RefCell};
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use candid::{CandidType, Principal};
+use ic_cdk::api::{data_certificate, set_certified_data};
+use ic_certification::{
+    empty, fork, labeled, leaf, merge_hash_trees, pruned, AsHashTree, Hash, HashTree, RbTree,
+};
+use serde::Serialize;
+use serde_bytes::ByteBuf;
+use sha2::{Digest, Sha256};
+
+use crate::types::{config::Config, user_canister::UserCanister};
+
+const CONFIG_LABEL: &str = "config";
+const HTTP_ASSETS_LABEL: &str = "http_assets";
+const REGISTRY_LABEL: &str = "registry";
+
+/// Registry entries, by owner and canister ID, as SHA-256 hashes of their Candid encoding.
+type Registry = RbTree<Principal, RbTree<Principal, Hash>>;
+
+thread_local! {
+    /// SHA-256 hash of the Candid encoding of the configuration, once set.
+    static CONFIG: Cell<Option<Hash>> = const { Cell::new(None) };
+
+    /// SHA-256 hashes of the certified HTTP response bodies, by path.
+    static HTTP_ASSETS: RefCell<RbTree<String, Hash>> = const { RefCell::new(RbTree::new()) };
+
+    static REGISTRY: RefCell<Registry> = const { RefCell::new(RbTree::new()) };
+
+    /// Index and hash of the last ICRC-3 block, or `None` while the block log is empty.
+    static TIP: Cell<Option<(u64, Hash)>> = const { Cell::new(None) };
+}
+
+/// Certifies the configuration.
+pub fn certify_config(config: &Config) {
+    CONFIG.with(|hash| hash.set(Some(candid_hash(config))));
+    certify();
+}
+
+/// Certifies the registry entries of `owner`, replacing the entries certified so far.
+pub fn certify_user_canisters(owner: Principal, canisters: &[UserCanister]) {
+    REGISTRY.with(|registry| {
+        let mut registry = registry.borrow_mut();
+        if canisters.is_empty() {
+            registry.delete(owner.as_slice());
+        } else {
+            registry.insert(owner, owner_tree(canisters));
+        }
+    });
+    certify();
+}
+
+/// Certifies the whole registry, e.g. after an upgrade, when the certified tree is empty.
+pub fn certify_registry(registry: impl IntoIterator<Item = (Principal, Vec<UserCanister>)>) {
+    REGISTRY.with(|certified| {
+        *certified.borrow_mut() = registry
+            .into_iter()
+            .map(|(owner, canisters)| (owner, owner_tree(&canisters)))
+            .collect();
+    });
+    certify();
+}
+
+/// Certifies the last block of the ICRC-3 block log.
+pub fn certify_tip(index: u64, hash: Hash) {
+    TIP.with(|tip| tip.set(Some((index, hash))));
+    certify();
+}
+
+/// Certifies the bodies of HTTP responses, replacing every response certified so far.
+pub fn certify_http_responses<'a>(responses: impl IntoIterator<Item = (&'a str, &'a [u8])>) {
+    HTTP_ASSETS.with(|assets| {
+        *assets.borrow_mut() = responses
+            .into_iter()
+            .map(|(path, body)| (path.to_string(), Sha256::digest(body).into()))
+            .collect();
+    });
+    certify();
+}
+
+/// The certified tree with every part but the ICRC-3 tip pruned, or `None` while the block log is
+/// empty.
+#[must_use]
+pub fn tip_witness() -> Option<HashTree> {
+    TIP.with(Cell::get)?;
+
+    Some(root(
+        pruned(config_tree().digest()),
+        pruned(http_assets_hash()),
+        pruned(registry_hash()),
+        tip_tree(),
+    ))
+}
+
+/// The certified tree with every part but the configuration pruned.
+#[must_use]
+pub fn config_witness() -> HashTree {
+    root(
+        config_tree(),
+        pruned(http_assets_hash()),
+        pruned(registry_hash()),
+        pruned(tip_tree().digest()),
+    )
+}
+
+/// The certified tree revealing every registry entry of `owner`, or proving that it has none.
+#[must_use]
+pub fn owner_witness(owner: Principal) -> HashTree {
+    let witness = REGISTRY.with(|registry| registry.borrow().witness(owner.as_slice()));
+
+    registry_witness(witness)
+}
+
+/// The certified tree revealing the registry entries of the given canisters, by owner.
+#[must_use]
+pub fn canisters_witness(canisters: &[(Principal, Principal)]) -> HashTree {
+    let witness = REGISTRY.with(|registry| {
+        let registry = registry.borrow();
+        canisters.iter().fold(
+            pruned(registry.root_hash()),
+            |witness, (owner, canister_id)| {
+                let entry = registry.nested_witness(owner.as_slice(), |entries| {
+                    entries.witness(canister_id.as_slice())
+                });
+                merge_hash_trees(witness, entry)
+            },
+        )
+    });
+
+    registry_witness(witness)
+}
+
+/// The certificate of the certified data and the CBOR-encoded `witness`, as returned by certified
+/// queries.
+///
+/// # Panics
+/// - If called outside a query, where the IC provides no certificate.
+#[must_use]
+pub fn certificate_with_witness(witness: &HashTree) -> (ByteBuf, ByteBuf) {
+    let certificate = data_certificate()
+        .unwrap_or_else(|| ic_cdk::trap("certificates are only available in queries"));
+
+    (ByteBuf::from(certificate), ByteBuf::from(cbor(witness)))
+}
+
+/// The `IC-Certificate` header of the HTTP response served at `path`, as verified by the HTTP
+/// gateway, or `None` if the response is not certified.
+///
+/// Only available in queries, where the IC provides the certificate of the certified data.
+#[must_use]
+pub fn http_certificate_header(path: &str) -> Option<(String, String)> {
+    let certificate = data_certificate()?;
+
+    let witness = HTTP_ASSETS.with(|assets| {
+        let assets = assets.borrow();
+        assets.get(path.as_bytes())?;
+        Some(assets.witness(path.as_bytes()))
+    })?;
+    let tree = root(
+        pruned(config_tree().digest()),
+        witness,
+        pruned(registry_hash()),
+        pruned(tip_tree().digest()),
+    );
+
+    Some((
+        "IC-Certificate".to_string(),
+        format!(
+            "certificate=:{}:, tree=:{}:",
+            BASE64.encode(certificate),
+            BASE64.encode(cbor(&tree))
+        ),
+    ))
+}
+
+/// Self-describing CBOR encoding of `value`, as expected by certificate verifiers.
+#[must_use]
+pub fn cbor<T: Serialize>(value: &T) -> Vec<u8> {
+    let mut serializer = serde_cbor::Serializer::new(Vec::new());
+    serializer
+        .self_describe()
+        .expect("writing to a vector should always succeed");
+    value
+        .serialize(&mut serializer)
+        .expect("encoding should always succeed");
+    serializer.into_inner()
+}
+
+fn certify() {
+    let root = root(
+        config_tree(),
+        pruned(http_assets_hash()),
+        pruned(registry_hash()),
+        tip_tree(),
+    );
+    set_certified_data(&root.digest());
+}
+
+/// The certified tree, whose root hash is the certified data of the factory:
+///
+/// ```text
+/// fork(
+///     fork(
+///         labeled("config", <SHA-256 of the Candid-encoded configuration>),
+///         labeled("http_assets", <path> -> SHA-256 of the response body),
+///     ),
+///     fork(
+///         fork(
+///             labeled("last_block_hash", <hash of the last ICRC-3 block>),
+///             labeled("last_block_index", <LEB128 index of the last ICRC-3 block>),
+///         ),
+///         labeled("registry", <owner> -> <canister ID> -> SHA-256 of the Candid-encoded entry),
+///     ),
+/// )
+/// ```
+///
+/// Labels appear in sorted order, as lookups in a hash tree require. The tree is kept in heap
+/// memory and rebuilt on upgrade. Certificates come with a witness of the part they vouch for,
+/// every other part being pruned.
+fn root(config: HashTree, http_assets: HashTree, registry: HashTree, tip: HashTree) -> HashTree {
+    fork(
+        fork(
+            labeled(CONFIG_LABEL, config),
+            labeled(HTTP_ASSETS_LABEL, http_assets),
+        ),
+        fork(tip, labeled(REGISTRY_LABEL, registry)),
+    )
+}
+
+/// The certified tree revealing `witness` of the registry, every other part being pruned.
+fn registry_witness(witness: HashTree) -> HashTree {
+    root(
+        pruned(config_tree().digest()),
+        pruned(http_assets_hash()),
+        witness,
+        pruned(tip_tree().digest()),
+    )
+}
+
+fn config_tree() -> HashTree {
+    match CONFIG.with(Cell::get) {
+        Some(hash) => leaf(hash.to_vec()),
+        None => empty(),
+    }
+}
+
+fn http_assets_hash() -> Hash {
+    HTTP_ASSETS.with(|assets| assets.borrow().root_hash())
+}
+
+fn registry_hash() -> Hash {
+    REGISTRY.with(|registry| registry.borrow().root_hash())
+}
+
+fn owner_tree(canisters: &[UserCanister]) -> RbTree<Principal, Hash> {
+    canisters
+        .iter()
+        .map(|canister| (canister.canister_id, candid_hash(canister)))
+        .collect()
+}
+
+/// SHA-256 hash of the Candid encoding of `value`, which clients compute again to check a response
+/// against the certified tree.
+fn candid_hash<T: CandidType>(value: &T) -> Hash {
+    Sha256::digest(candid::encode_one(value).expect("encoding should always succeed")).into()
+}
+
+/// The tree certifying the last block index and hash, as defined by ICRC-3.
+fn tip_tree() -> HashTree {
+    match TIP.with(Cell::get) {
+        Some((index, hash)) => fork(
+            labeled("last_block_hash", leaf(hash.to_vec())),
+            labeled("last_block_index", leaf(leb128(index))),
+        ),
+        None => empty(),
+    }
+}
+
+/// Unsigned LEB128 encoding of `value`.
+fn leb128(mut value: u64) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    loop {
+        let byte = u8::try_from(value & 0x7f).expect("masked to seven bits");
+        value >>= 7;
+        if value == 0 {
+            bytes.push(byte);
+            return bytes;
+        }
+        bytes.push(byte | 0x80);
+    }
+}
@@ -0,0 +1,123 @@
+use std::fmt;
+
+use candid::{CandidType, Nat, Principal};
+use serde::Deserialize;
+use serde_bytes::ByteBuf;
+
+/// Memo the CMC expects on transfers that top up a canister, `TPUP` as a little-endian `u64`.
+pub const MEMO_TOP_UP_CANISTER: u64 = 0x5055_5054;
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+struct IcpXdrConversionRate {
+    xdr_permyriad_per_icp: u64,
+    timestamp_seconds: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+struct IcpXdrConversionRateResponse {
+    data: IcpXdrConversionRate,
+    hash_tree: ByteBuf,
+    certificate: ByteBuf,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+struct NotifyTopUpArg {
+    block_index: u64,
+    canister_id: Principal,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+enum NotifyError {
+    Refunded {
+        reason: String,
+        block_index: Option<u64>,
+    },
+    InvalidTransaction(String),
+    TransactionTooOld(u64),
+    Processing,
+    Other {
+        error_code: u64,
+        error_message: String,
+    },
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+enum NotifyTopUpResult {
+    Ok(Nat),
+    Err(NotifyError),
+}
+
+/// Why the CMC did not convert a top-up transfer.
+#[derive(Clone, Debug)]
+pub enum NotifyTopUpError {
+    /// The CMC will never convert the transfer, e.g. because it refunded the ICP to the sender.
+    Final(String),
+    /// The conversion may succeed if notified again, e.g. because the CMC could not be reached.
+    Retryable(String),
+}
+
+impl fmt::Display for NotifyTopUpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NotifyTopUpError::Final(message) | NotifyTopUpError::Retryable(message) => {
+                f.write_str(message)
+            }
+        }
+    }
+}
+
+/// Reads the ICP/XDR conversion rate of the CMC, in ten-thousandths of an XDR per ICP.
+///
+/// One XDR converts to one trillion cycles, so the rate is also the number of cycles one e8s of
+/// ICP converts to.
+pub async fn icp_xdr_rate(cmc: Principal) -> Result<u64, String> {
+    let (response,) =
+        ic_cdk::call::<(), (IcpXdrConversionRateResponse,)>(cmc, "get_icp_xdr_conversion_rate", ())
+            .await
+            .map_err(|(code, msg)| format!("Failed to read the ICP/XDR rate: {code:?} - {msg}"))?;
+
+    match response.data.xdr_permyriad_per_icp {
+        0 => Err("The CMC reported an ICP/XDR rate of zero".to_string()),
+        rate => Ok(rate),
+    }
+}
+
+/// Asks the CMC to convert the ICP sent in `block_index` into cycles for `canister_id`.
+///
+/// Returns the cycles deposited into the canister. The CMC converts every block once, so notifying
+/// it again for a converted block returns the same cycles without depositing them twice.
+pub async fn notify_top_up(
+    cmc: Principal,
+    block_index: u64,
+    canister_id: Principal,
+) -> Result<u128, NotifyTopUpError> {
+    let args = NotifyTopUpArg {
+        block_index,
+        canister_id,
+    };
+
+    let (result,) =
+        ic_cdk::call::<(NotifyTopUpArg,), (NotifyTopUpResult,)>(cmc, "notify_top_up", (args,))
+            .await
+            .map_err(|(code, msg)| {
+                NotifyTopUpError::Retryable(format!("Failed to notify the CMC: {code:?} - {msg}"))
+            })?;
+
+    match result {
+        NotifyTopUpResult::Ok(cycles) => u128::try_from(cycles.0).map_err(|_| {
+            NotifyTopUpError::Final("The CMC minted more cycles than fit in a u128".to_string())
+        }),
+        NotifyTopUpResult::Err(err) => {
+            let message = format!("The CMC refused to convert block {block_index}: {err:?}");
+
+            Err(match err {
+                NotifyError::Processing | NotifyError::Other { .. } => {
+                    NotifyTopUpError::Retryable(message)
+                }
+                NotifyError::Refunded { .. }
+                | NotifyError::InvalidTransaction(_)
+                | NotifyError::TransactionTooOld(_) => NotifyTopUpError::Final(message),
+            })
+        }
+    }
+}
@@ -0,0 +1,376 @@
+use candid::{Encode, Principal};
+use ic_cdk::{caller, id};
+
+use crate::{
+    auto_top_up::drop_auto_top_up,
+    events::record_event,
+    ledger::{list_archives, LedgerArgs},
+    mgmt::{get_canister_info, get_canister_status, set_controllers, upgrade_wasm},
+    state::{mutate_state, read_config, read_state},
+    types::{
+        args::{
+            controllers::{AddControllerArgs, RemoveControllerArgs},
+            ownership::MakeLedgerImmutableArgs,
+        },
+        event::EventKind,
+        ledger_suite::ledger::upgrade_args::{ChangeArchiveOptions, UpgradeArgs},
+        results::create_canister::{CreateCanisterError, SetCanisterResult},
+        stored_principal::StoredPrincipal,
+        user_canister::{UserCanister, UserCanisterKind},
+    },
+    user_canister::{get_managed_user_canister, get_owned_user_canister, upsert_user_canister},
+    wasm::{ledger_wasm::get_stored_ledger_wasm, utils::module_hash},
+};
+
+pub async fn renounce_factory_control(canister_id: Principal) -> SetCanisterResult {
+    let owner = caller();
+
+    let user_canister = match read_state(|s| get_managed_user_canister(s, owner, canister_id)) {
+        Ok(user_canister) => user_canister,
+        Err(err) => return SetCanisterResult::Err(err),
+    };
+
+    let archive_controller = if user_canister.kind == UserCanisterKind::IcrcLedger {
+        if let Err(err) = release_ledger_archives(owner, &user_canister, owner).await {
+            return SetCanisterResult::Err(err);
+        }
+        Some(owner)
+    } else {
+        None
+    };
+
+    let controllers = match swap_controller(canister_id, id(), owner).await {
+        Ok(controllers) => controllers,
+        Err(err) => return SetCanisterResult::Err(err),
+    };
+
+    mutate_state(|state| {
+        upsert_user_canister(
+            StoredPrincipal(owner),
+            state,
+            UserCanister {
+                pending_owner: None,
+                sovereign: Some(true),
+                controllers: Some(controllers),
+                archive_controller,
+                ..user_canister
+            },
+        );
+    });
+
+    drop_auto_top_up(canister_id);
+
+    record_event(EventKind::FactoryControlRenounced { canister_id });
+
+    SetCanisterResult::Ok()
+}
+
+pub async fn make_ledger_immutable(args: MakeLedgerImmutableArgs) -> SetCanisterResult {
+    let owner = caller();
+    let ledger_id = args.ledger_id;
+
+    if args.confirm_ledger_id != ledger_id || !args.acknowledge_irreversible {
+        return SetCanisterResult::Err(CreateCanisterError::ImmutabilityNotConfirmed);
+    }
+
+    let user_canister = match read_state(|s| get_managed_user_canister(s, owner, ledger_id)) {
+        Ok(user_canister) => user_canister,
+        Err(err) => return SetCanisterResult::Err(err),
+    };
+
+    if user_canister.kind != UserCanisterKind::IcrcLedger {
+        return SetCanisterResult::Err(CreateCanisterError::NotALedger);
+    }
+
+    // Read first: the hash recorded is that of the code the owner chose to freeze, which the
+    // archive hand-over below keeps.
+    let module_hash = match running_module_hash(ledger_id).await {
+        Ok(module_hash) => module_hash,
+        Err(err) => return SetCanisterResult::Err(err),
+    };
+
+    let blackhole = read_config(|config| config.blackhole_canister);
+
+    // Archives must stay upgradable by someone able to keep them alive; without a blackhole
+    // canister, the ledger itself (already one of their controllers) is the only one left.
+    let archive_controller = blackhole.unwrap_or(ledger_id);
+    if let Err(err) = release_ledger_archives(owner, &user_canister, archive_controller).await {
+        return SetCanisterResult::Err(err);
+    }
+
+    let controllers: Vec<Principal> = blackhole.into_iter().collect();
+
+    // The hand-over is recorded in the registry, so calling again only retries this last step.
+    if let Err(err) = set_controllers(ledger_id, controllers.clone()).await {
+        return SetCanisterResult::Err(CreateCanisterError::UpdateSettingsFailed(err));
+    }
+
+    mutate_state(|state| {
+        upsert_user_canister(
+            StoredPrincipal(owner),
+            state,
+            UserCanister {
+                pending_owner: None,
+                sovereign: Some(true),
+                final_module_hash: Some(module_hash.clone()),
+                controllers: Some(controllers),
+                archive_controller: Some(archive_controller),
+                ..user_canister
+            },
+        );
+    });
+
+    drop_auto_top_up(ledger_id);
+
+    record_event(EventKind::LedgerMadeImmutable {
+        canister_id: ledger_id,
+        module_hash,
+    });
+
+    SetCanisterResult::Ok()
+}
+
+pub async fn add_controller(args: AddControllerArgs) -> SetCanisterResult {
+    let owner = caller();
+    let AddControllerArgs {
+        canister_id,
+        controller,
+    } = args;
+
+    if controller == Principal::anonymous() || controller == id() {
+        return SetCanisterResult::Err(CreateCanisterError::InvalidController);
+    }
+
+    let user_canister = match read_state(|s| get_managed_user_canister(s, owner, canister_id)) {
+        Ok(user_canister) => user_canister,
+        Err(err) => return SetCanisterResult::Err(err),
+    };
+
+    let controllers = match update_controllers(canister_id, |mut controllers| {
+        if !controllers.contains(&controller) {
+            controllers.push(controller);
+        }
+        Ok(controllers)
+    })
+    .await
+    {
+        Ok(controllers) => controllers,
+        Err(err) => return SetCanisterResult::Err(err),
+    };
+
+    mutate_state(|state| {
+        upsert_user_canister(
+            StoredPrincipal(owner),
+            state,
+            UserCanister {
+                controllers: Some(owner_side_controllers(controllers)),
+                ..user_canister
+            },
+        );
+    });
+
+    record_event(EventKind::ControllerAdded {
+        canister_id,
+        controller,
+    });
+
+    SetCanisterResult::Ok()
+}
+
+pub async fn remove_controller(args: RemoveControllerArgs) -> SetCanisterResult {
+    let owner = caller();
+    let RemoveControllerArgs {
+        canister_id,
+        controller,
+    } = args;
+
+    // The factory leaves through `renounce_factory_control`, which also takes care of archives.
+    if controller == id() {
+        return SetCanisterResult::Err(CreateCanisterError::InvalidController);
+    }
+
+    let user_canister = match read_state(|s| get_managed_user_canister(s, owner, canister_id)) {
+        Ok(user_canister) => user_canister,
+        Err(err) => return SetCanisterResult::Err(err),
+    };
+
+    let controllers = match update_controllers(canister_id, |controllers| {
+        if !controllers.contains(&controller) {
+            return Err(CreateCanisterError::InvalidController);
+        }
+
+        let controllers: Vec<Principal> = controllers
+            .into_iter()
+            .filter(|c| *c != controller)
+            .collect();
+
+        if owner_side_controllers(controllers.clone()).is_empty() {
+            return Err(CreateCanisterError::LastController);
+        }
+
+        Ok(controllers)
+    })
+    .await
+    {
+        Ok(controllers) => controllers,
+        Err(err) => return SetCanisterResult::Err(err),
+    };
+
+    mutate_state(|state| {
+        upsert_user_canister(
+            StoredPrincipal(owner),
+            state,
+            UserCanister {
+                controllers: Some(owner_side_controllers(controllers)),
+                ..user_canister
+            },
+        );
+    });
+
+    record_event(EventKind::ControllerRemoved {
+        canister_id,
+        controller,
+    });
+
+    SetCanisterResult::Ok()
+}
+
+/// Hands a ledger's archives over to `controller`, and records it in the ledger's registry entry.
+///
+/// Existing archives get the factory swapped for `controller` in their controllers, and the ledger
+/// is upgraded so that archives it spawns from now on are controlled by `controller` too. The
+/// upgrade keeps the code the ledger runs: it is refused unless that code is the stored ledger
+/// WASM.
+///
+/// Archives the factory no longer controls, e.g. handed over by an earlier call interrupted before
+/// the factory let go of the ledger, are left as they are, so that the call can be retried.
+///
+/// # Errors
+/// - `ModuleHashMismatch` if the ledger does not run the stored ledger WASM.
+/// - Any error of listing the archives, swapping their controllers or upgrading the ledger.
+async fn release_ledger_archives(
+    owner: Principal,
+    ledger: &UserCanister,
+    controller: Principal,
+) -> Result<(), CreateCanisterError> {
+    let ledger_id = ledger.canister_id;
+
+    if ledger.archive_controller == Some(controller) {
+        return Ok(());
+    }
+
+    // Read once, so that the ledger is upgraded with the very module checked against it.
+    let ledger_wasm = get_stored_ledger_wasm();
+    if running_module_hash(ledger_id).await? != module_hash(&ledger_wasm) {
+        return Err(CreateCanisterError::ModuleHashMismatch);
+    }
+
+    let archives = list_archives(ledger_id)
+        .await
+        .map_err(CreateCanisterError::ArchiveListingFailed)?;
+
+    for archive_id in archives {
+        let archive_controllers = get_canister_info(archive_id)
+            .await
+            .map_err(CreateCanisterError::CanisterStatusFailed)?
+            .controllers;
+
+        if archive_controllers.contains(&id()) {
+            swap_controller(archive_id, id(), controller).await?;
+        }
+    }
+
+    let upgrade_arg = LedgerArgs::Upgrade(Some(UpgradeArgs {
+        change_archive_options: Some(ChangeArchiveOptions {
+            controller_id: Some(controller),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }));
+    let arg = Encode!(&upgrade_arg).map_err(|e| {
+        CreateCanisterError::InitArgsEncodingFailed(format!("Failed to encode upgrade args: {e}"))
+    })?;
+
+    upgrade_wasm(ledger_id, ledger_wasm, arg)
+        .await
+        .map_err(CreateCanisterError::WasmInstallationFailed)?;
+
+    mutate_state(|state| {
+        if let Ok(entry) = get_owned_user_canister(state, owner, ledger_id) {
+            upsert_user_canister(
+                StoredPrincipal(owner),
+                state,
+                UserCanister {
+                    archive_controller: Some(controller),
+                    ..entry
+                },
+            );
+        }
+    });
+
+    Ok(())
+}
+
+/// Returns the hash of the module a canister controlled by the factory runs.
+async fn running_module_hash(canister_id: Principal) -> Result<Vec<u8>, CreateCanisterError> {
+    get_canister_status(canister_id)
+        .await
+        .map(|status| status.module_hash.unwrap_or_default())
+        .map_err(CreateCanisterError::CanisterStatusFailed)
+}
+
+/// Replaces `old` with `new` in the live controller list of a canister.
+///
+/// Returns the controllers other than the factory once the swap is done.
+pub async fn swap_controller(
+    canister_id: Principal,
+    old: Principal,
+    new: Principal,
+) -> Result<Vec<Principal>, CreateCanisterError> {
+    update_controllers(canister_id, |controllers| {
+        Ok(replace_controller(controllers, old, new))
+    })
+    .await
+    .map(owner_side_controllers)
+}
+
+/// Applies `update` to the live controller list of a canister and returns the resulting list.
+///
+/// The live list is read from the management canister rather than from the registry, so that
+/// controllers added outside of the factory are preserved.
+async fn update_controllers(
+    canister_id: Principal,
+    update: impl FnOnce(Vec<Principal>) -> Result<Vec<Principal>, CreateCanisterError>,
+) -> Result<Vec<Principal>, CreateCanisterError> {
+    let controllers = get_canister_status(canister_id)
+        .await
+        .map_err(CreateCanisterError::CanisterStatusFailed)?
+        .settings
+        .controllers;
+
+    let controllers = update(controllers)?;
+
+    set_controllers(canister_id, controllers.clone())
+        .await
+        .map_err(CreateCanisterError::UpdateSettingsFailed)?;
+
+    Ok(controllers)
+}
+
+/// Drops the factory from a controller list.
+fn owner_side_controllers(controllers: Vec<Principal>) -> Vec<Principal> {
+    controllers.into_iter().filter(|c| *c != id()).collect()
+}
+
+/// Swaps `old` for `new` in a controller list, keeping every other controller untouched.
+fn replace_controller(
+    controllers: Vec<Principal>,
+    old: Principal,
+    new: Principal,
+) -> Vec<Principal> {
+    let mut controllers: Vec<Principal> = controllers.into_iter().filter(|c| *c != old).collect();
+    if !controllers.contains(&new) {
+        controllers.push(new);
+    }
+    controllers
+}
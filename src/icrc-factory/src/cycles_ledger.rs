@@ -0,0 +1,30 @@
+use candid::{CandidType, Nat, Principal};
+use icrc_ledger_types::icrc1::account::Account;
+use serde::Deserialize;
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+struct DepositArgs {
+    to: Account,
+    memo: Option<Vec<u8>>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+struct DepositResult {
+    balance: Nat,
+    block_index: Nat,
+}
+
+/// Credits `to` on the cycles ledger with `cycles` taken from the factory's own balance.
+pub async fn deposit(cycles_ledger: Principal, to: Account, cycles: u128) -> Result<(), String> {
+    let args = DepositArgs { to, memo: None };
+
+    ic_cdk::api::call::call_with_payment128::<(DepositArgs,), (DepositResult,)>(
+        cycles_ledger,
+        "deposit",
+        (args,),
+        cycles,
+    )
+    .await
+    .map(|_| ())
+    .map_err(|(code, msg)| format!("Failed to deposit to the cycles ledger: {code:?} - {msg}"))
+}
@@ -0,0 +1,407 @@
+use candid::Principal;
+use ic_cdk::{api::time, caller};
+
+use crate::{
+    access_list::check_creation_access,
+    events::record_event,
+    methods::SignerMethods,
+    pause::{ensure_not_paused, PausableOperation},
+    payment::{charge, check_max_fee, creation_fee, payment_method},
+    pricing::{get_pricing, updated_pricing},
+    rate_limit::{check_rate_limit, release_rate_limit},
+    state::{mutate_state, read_state, State},
+    types::{
+        args::{
+            create_canister::CanisterSettingsArgs,
+            discounts::{SetCustomPricingArgs, SetFreeQuotaArgs, SetPromoCodeArgs},
+        },
+        candid::Candid,
+        discounts::{CustomPricing, Discount, PromoCode, PromoCodeRedemption},
+        event::{EventKind, EventPayment},
+        payment::FactoryPaymentType,
+        pricing::Pricing,
+        results::create_canister::{CreateCanisterError, SetCanisterResult},
+        stored_principal::StoredPrincipal,
+    },
+};
+
+/// Longest promo code accepted, in bytes.
+pub const MAX_PROMO_CODE_LENGTH: usize = 32;
+
+/// Largest discount accepted, which makes creations free.
+pub const MAX_DISCOUNT_BPS: u16 = 10_000;
+
+/// What a canister creation costs its caller once their discounts are applied.
+#[derive(Clone, Debug)]
+pub struct CreationCharge {
+    pub method: SignerMethods,
+    /// Fee, in cycles, before discounts.
+    pub fee: u64,
+    /// Fee, in cycles, the caller pays.
+    pub charged: u64,
+    pub discount: Option<Discount>,
+}
+
+impl CreationCharge {
+    /// The payment of the creation, as recorded in the event log.
+    #[must_use]
+    pub fn event_payment(&self, payment: Option<&FactoryPaymentType>) -> EventPayment {
+        EventPayment {
+            method: payment_method(payment),
+            cycles: self.charged,
+            discount: self.discount.clone(),
+        }
+    }
+}
+
+/// Returns the pricing `principal` is charged from: its custom pricing if it is allowlisted, the
+/// factory's pricing otherwise.
+#[must_use]
+pub fn pricing_for(principal: Principal) -> Pricing {
+    read_state(|s| s.custom_pricing.get(&StoredPrincipal(principal)))
+        .map_or_else(get_pricing, |Candid(pricing)| pricing)
+}
+
+/// Allowlists a principal with custom pricing, or removes it from the allowlist.
+///
+/// Omitted fields keep the value the principal is currently charged.
+pub fn set_custom_pricing(args: SetCustomPricingArgs) -> SetCanisterResult {
+    let principal = args.principal;
+
+    let Some(pricing) = args.pricing else {
+        mutate_state(|s| s.custom_pricing.remove(&StoredPrincipal(principal)));
+        record_event(EventKind::CustomPricingSet {
+            principal,
+            pricing: None,
+        });
+        return SetCanisterResult::Ok();
+    };
+
+    let pricing = match updated_pricing(&pricing_for(principal), pricing) {
+        Ok(pricing) => pricing,
+        Err(err) => return SetCanisterResult::Err(err),
+    };
+
+    mutate_state(|s| {
+        s.custom_pricing
+            .insert(StoredPrincipal(principal), Candid(pricing.clone()));
+    });
+
+    record_event(EventKind::CustomPricingSet {
+        principal,
+        pricing: Some(pricing),
+    });
+
+    SetCanisterResult::Ok()
+}
+
+/// Returns the allowlisted principals and their pricing.
+#[must_use]
+pub fn list_custom_pricing() -> Vec<CustomPricing> {
+    read_state(|s| {
+        s.custom_pricing
+            .keys()
+            .filter_map(|StoredPrincipal(principal)| {
+                let Candid(pricing) = s.custom_pricing.get(&StoredPrincipal(principal))?;
+                Some(CustomPricing { principal, pricing })
+            })
+            .collect()
+    })
+}
+
+/// Returns the number of free canister creations `principal` has left.
+#[must_use]
+pub fn free_quota(principal: Principal) -> u32 {
+    read_state(|s| s.free_quotas.get(&StoredPrincipal(principal)).unwrap_or(0))
+}
+
+/// Sets the number of free canister creations a principal has left.
+pub fn set_free_quota(args: SetFreeQuotaArgs) -> SetCanisterResult {
+    mutate_state(|s| set_quota(s, args.principal, args.creations));
+
+    record_event(EventKind::FreeQuotaSet {
+        principal: args.principal,
+        creations: args.creations,
+    });
+
+    SetCanisterResult::Ok()
+}
+
+/// Creates or updates a promo code; an updated code keeps its past redemptions.
+pub fn set_promo_code(args: SetPromoCodeArgs) -> SetCanisterResult {
+    if args.code.is_empty() || args.code.len() > MAX_PROMO_CODE_LENGTH {
+        return SetCanisterResult::Err(CreateCanisterError::InvalidPromoCode(format!(
+            "codes must be 1 to {MAX_PROMO_CODE_LENGTH} bytes long"
+        )));
+    }
+
+    if args.discount_bps > MAX_DISCOUNT_BPS {
+        return SetCanisterResult::Err(CreateCanisterError::InvalidPromoCode(format!(
+            "discount_bps must be at most {MAX_DISCOUNT_BPS}"
+        )));
+    }
+
+    let promo_code = mutate_state(|s| {
+        let uses = s
+            .promo_codes
+            .get(&args.code)
+            .map_or(0, |Candid(code)| code.uses);
+
+        let promo_code = PromoCode {
+            code: args.code.clone(),
+            discount_bps: args.discount_bps,
+            expires_at: args.expires_at,
+            max_uses: args.max_uses,
+            uses,
+        };
+
+        s.promo_codes.insert(args.code, Candid(promo_code.clone()));
+
+        promo_code
+    });
+
+    record_event(EventKind::PromoCodeSet { promo_code });
+
+    SetCanisterResult::Ok()
+}
+
+/// Removes a promo code; its past redemptions stay in the audit trail.
+pub fn remove_promo_code(code: String) -> SetCanisterResult {
+    if mutate_state(|s| s.promo_codes.remove(&code)).is_none() {
+        return SetCanisterResult::Err(unknown_promo_code(&code));
+    }
+
+    record_event(EventKind::PromoCodeRemoved { code });
+
+    SetCanisterResult::Ok()
+}
+
+/// Returns every promo code, with its number of redemptions.
+#[must_use]
+pub fn list_promo_codes() -> Vec<PromoCode> {
+    read_state(|s| s.promo_codes.values().map(|Candid(code)| code).collect())
+}
+
+/// Returns promo code redemptions, oldest first.
+#[must_use]
+pub fn list_promo_code_redemptions(offset: u64, limit: u64) -> Vec<PromoCodeRedemption> {
+    read_state(|s| {
+        (offset..s.promo_code_redemptions.len())
+            .take(usize::try_from(limit).unwrap_or(usize::MAX))
+            .filter_map(|idx| s.promo_code_redemptions.get(idx))
+            .map(|Candid(redemption)| redemption)
+            .collect()
+    })
+}
+
+/// Computes what a canister creation costs `caller`, without consuming any discount.
+///
+/// A free creation left in the caller's quota makes the creation free, and takes precedence over
+/// `promo_code`. Otherwise the promo code, if any, is taken off the caller's fee.
+///
+/// # Errors
+/// - `InvalidCanisterSettings` if `settings` are out of bounds.
+/// - `InvalidPromoCode` if `promo_code` is unknown, expired or fully redeemed.
+pub fn creation_charge(
+    caller: Principal,
+    method: SignerMethods,
+    settings: Option<&CanisterSettingsArgs>,
+    promo_code: Option<&str>,
+) -> Result<CreationCharge, CreateCanisterError> {
+    let fee = creation_fee(caller, method, settings)?;
+
+    let (charged, discount) = if free_quota(caller) > 0 {
+        (0, Some(Discount::FreeQuota))
+    } else if let Some(code) = promo_code {
+        let promo = redeemable_promo_code(code)?;
+        let discount = u128::from(fee) * u128::from(promo.discount_bps) / 10_000;
+        let discounted = fee.saturating_sub(u64::try_from(discount).unwrap_or(u64::MAX));
+
+        (discounted, Some(Discount::PromoCode { code: promo.code }))
+    } else {
+        (fee, None)
+    };
+
+    Ok(CreationCharge {
+        method,
+        fee,
+        charged,
+        discount,
+    })
+}
+
+/// Takes the fee of a canister creation from the caller, and consumes the discount it was granted.
+///
+/// The discount is consumed before the payment is awaited, so that concurrent calls cannot redeem
+/// it twice, and is handed back if the payment fails. Free creations charge nothing. Promo code
+/// redemptions are recorded once paid for.
+///
+/// # Errors
+/// - Any error of [`charge`]; the discount is then handed back.
+pub async fn charge_creation(
+    payment: Option<FactoryPaymentType>,
+    creation: &CreationCharge,
+) -> Result<(), CreateCanisterError> {
+    let caller = caller();
+
+    if let Some(discount) = &creation.discount {
+        consume(caller, discount);
+    }
+
+    if creation.charged > 0 {
+        if let Err(err) = charge(payment, creation.charged).await {
+            if let Some(discount) = &creation.discount {
+                hand_back(caller, discount);
+            }
+            return Err(err);
+        }
+    }
+
+    if let Some(Discount::PromoCode { code }) = &creation.discount {
+        let redemption = PromoCodeRedemption {
+            timestamp: time(),
+            code: code.clone(),
+            redeemer: caller,
+            method: creation.method,
+            fee: creation.fee,
+            charged: creation.charged,
+        };
+
+        mutate_state(|s| {
+            s.promo_code_redemptions
+                .append(&Candid(redemption))
+                .expect("failed to append promo code redemption");
+        });
+    }
+
+    Ok(())
+}
+
+/// Prices a canister creation for the caller, checks the price against `max_fee`, and charges it.
+///
+/// Returns the payment of the creation, as recorded in the event log. A creation that is not paid
+/// for does not count against the rate limits.
+///
+/// # Errors
+/// - `Paused` if creations are paused.
+/// - `Denylisted` or `NotAllowlisted` if the caller may not create canisters.
+/// - `RateLimited` if the caller, or all principals together, created too many canisters lately.
+/// - Any error of [`creation_charge`], [`check_max_fee`] or [`charge_creation`].
+pub async fn pay_for_creation(
+    method: SignerMethods,
+    settings: Option<&CanisterSettingsArgs>,
+    payment: Option<FactoryPaymentType>,
+    max_fee: Option<u64>,
+    promo_code: Option<&str>,
+) -> Result<EventPayment, CreateCanisterError> {
+    let caller = caller();
+
+    ensure_not_paused(PausableOperation::Creation)?;
+    check_creation_access(caller)?;
+    let counted_at = check_rate_limit(caller)?;
+
+    let result = charge_for_creation(caller, method, settings, payment, max_fee, promo_code).await;
+
+    if result.is_err() {
+        release_rate_limit(caller, counted_at);
+    }
+
+    result
+}
+
+/// Prices a creation for `caller`, checks the price against `max_fee`, and charges it.
+async fn charge_for_creation(
+    caller: Principal,
+    method: SignerMethods,
+    settings: Option<&CanisterSettingsArgs>,
+    payment: Option<FactoryPaymentType>,
+    max_fee: Option<u64>,
+    promo_code: Option<&str>,
+) -> Result<EventPayment, CreateCanisterError> {
+    let creation = creation_charge(caller, method, settings, promo_code)?;
+
+    check_max_fee(creation.charged, max_fee)?;
+
+    let event_payment = creation.event_payment(payment.as_ref());
+
+    charge_creation(payment, &creation).await?;
+
+    Ok(event_payment)
+}
+
+/// Consumes a discount of `caller`.
+fn consume(caller: Principal, discount: &Discount) {
+    mutate_state(|s| match discount {
+        Discount::FreeQuota => {
+            let left = s.free_quotas.get(&StoredPrincipal(caller)).unwrap_or(0);
+            set_quota(s, caller, left.saturating_sub(1));
+        }
+        Discount::PromoCode { code } => {
+            if let Some(Candid(mut promo)) = s.promo_codes.get(code) {
+                promo.uses = promo.uses.saturating_add(1);
+                s.promo_codes.insert(code.clone(), Candid(promo));
+            }
+        }
+    });
+}
+
+/// Hands back a discount consumed by a creation whose payment failed.
+fn hand_back(caller: Principal, discount: &Discount) {
+    mutate_state(|s| match discount {
+        Discount::FreeQuota => {
+            let left = s.free_quotas.get(&StoredPrincipal(caller)).unwrap_or(0);
+            set_quota(s, caller, left.saturating_add(1));
+        }
+        Discount::PromoCode { code } => {
+            // A code removed while the payment was awaited has nothing to hand back.
+            if let Some(Candid(mut promo)) = s.promo_codes.get(code) {
+                promo.uses = promo.uses.saturating_sub(1);
+                s.promo_codes.insert(code.clone(), Candid(promo));
+            }
+        }
+    });
+}
+
+fn set_quota(state: &mut State, principal: Principal, creations: u32) {
+    if creations == 0 {
+        state.free_quotas.remove(&StoredPrincipal(principal));
+    } else {
+        state
+            .free_quotas
+            .insert(StoredPrincipal(principal), creations);
+    }
+}
+
+/// Looks up a promo code that can still be redeemed.
+///
+/// # Errors
+/// - `InvalidPromoCode` if the code is unknown, expired or fully redeemed.
+fn redeemable_promo_code(code: &str) -> Result<PromoCode, CreateCanisterError> {
+    let Some(Candid(promo)) = read_state(|s| s.promo_codes.get(&code.to_string())) else {
+        return Err(unknown_promo_code(code));
+    };
+
+    if promo
+        .expires_at
+        .is_some_and(|expires_at| time() > expires_at)
+    {
+        return Err(CreateCanisterError::InvalidPromoCode(format!(
+            "promo code {code} has expired"
+        )));
+    }
+
+    if promo
+        .max_uses
+        .is_some_and(|max_uses| promo.uses >= max_uses)
+    {
+        return Err(CreateCanisterError::InvalidPromoCode(format!(
+            "promo code {code} has been fully redeemed"
+        )));
+    }
+
+    Ok(promo)
+}
+
+fn unknown_promo_code(code: &str) -> CreateCanisterError {
+    CreateCanisterError::InvalidPromoCode(format!("unknown promo code {code}"))
+}
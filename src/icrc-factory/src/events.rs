@@ -0,0 +1,91 @@
+use ic_cdk::{api::time, caller};
+
+use crate::{
+    icrc3::sync_blocks,
+    metrics::record_payment,
+    state::{mutate_state, read_state},
+    types::{
+        candid::Candid,
+        event::{Event, EventFilter, EventKind, EventOutcome, EventPayment},
+        results::create_canister::CreateCanisterError,
+    },
+};
+
+/// Largest number of events returned by a single call of [`list_events`].
+pub const MAX_EVENTS_PER_PAGE: u64 = 100;
+
+/// Appends an audit event attributed to the current caller, and its ICRC-3 block.
+///
+/// # Panics
+/// - If the event log cannot grow, which only happens when stable memory is exhausted.
+pub fn record_event(kind: EventKind) {
+    append(kind, None, None);
+}
+
+/// Appends the audit event of a paid action attributed to the current caller, with its payment and
+/// its outcome: `error` is the error the action failed with after its payment was taken. The
+/// payment is added to the totals reported in the metrics.
+///
+/// # Panics
+/// - If the event log cannot grow, which only happens when stable memory is exhausted.
+pub fn record_paid_event(
+    kind: EventKind,
+    payment: EventPayment,
+    error: Option<&CreateCanisterError>,
+) {
+    let outcome = match error {
+        None => EventOutcome::Succeeded,
+        Some(err) => EventOutcome::Failed {
+            reason: format!("{err:?}"),
+        },
+    };
+
+    record_payment(&payment);
+
+    append(kind, Some(payment), Some(outcome));
+}
+
+fn append(kind: EventKind, payment: Option<EventPayment>, outcome: Option<EventOutcome>) {
+    let event = Event {
+        timestamp: time(),
+        caller: caller(),
+        kind,
+        payment,
+        outcome,
+    };
+
+    mutate_state(|state| {
+        state
+            .events
+            .append(&Candid(event))
+            .expect("failed to append event to the event log");
+
+        sync_blocks(state);
+    });
+}
+
+/// Returns at most [`MAX_EVENTS_PER_PAGE`] events matching `filter`, oldest first; `offset` counts
+/// matching events only.
+#[must_use]
+pub fn list_events(filter: &EventFilter, offset: u64, limit: u64) -> Vec<Event> {
+    let limit = usize::try_from(limit.min(MAX_EVENTS_PER_PAGE)).unwrap_or(usize::MAX);
+
+    read_state(|s| {
+        // Without criteria, the page is read by index instead of scanning the log up to it.
+        if *filter == EventFilter::default() {
+            return (offset..s.events.len())
+                .take(limit)
+                .filter_map(|idx| s.events.get(idx))
+                .map(|Candid(event)| event)
+                .collect();
+        }
+
+        s.events
+            .iter()
+            .map(|Candid(event)| event)
+            .filter(|event| filter.matches(event))
+            .skip(usize::try_from(offset).unwrap_or(usize::MAX))
+            .take(limit)
+            .collect()
+    })
+}
@@ -1,20 +1,26 @@
-use candid::Encode;
-use ic_cdk::{api::management_canister::main::CanisterSettings, caller, id};
+use candid::{Encode, Principal};
+use ic_cdk::{caller, id};
 use icrc_ledger_types::icrc1::account::Account;
 
 use crate::{
+    events::record_paid_event,
     index::create_default_index_init_args,
     ledger::create_default_ledger_init_args,
+    logging::LogContext,
     methods::SignerMethods,
     mgmt::{create_canister_with_ic_mgmt, install_wasm},
-    state::mutate_state,
+    settings::canister_settings,
+    state::{mutate_state, read_state},
+    tokens::{set_token_metadata, update_token_metadata},
     types::{
         args::create_canister::{CreateIcrcIndexArgs, CreateIcrcLedgerArgs},
+        event::{EventKind, EventPayment},
         results::create_canister::{CreateCanisterError, CreateCanisterResult},
         stored_principal::StoredPrincipal,
+        token::TokenMetadata,
         user_canister::{UserCanister, UserCanisterKind},
     },
-    user_canister::upsert_user_canister,
+    user_canister::{get_owned_user_canister, upsert_user_canister},
     wasm::{index_wasm::get_stored_index_wasm, ledger_wasm::get_stored_ledger_wasm},
 };
 
@@ -22,42 +28,92 @@ use crate::{
 /// installation cost.
 pub const MIN_CYCLES_FOR_CANISTER_CREATION: u64 = 500_000_000_000;
 
-pub async fn create_icrc_ledger(args: CreateIcrcLedgerArgs) -> CreateCanisterResult {
-    let cycles = SignerMethods::CreateIcrcLedger.fee();
+/// Creates a ledger for the caller, who has already paid for it, and records the creation with its
+/// payment and outcome.
+pub async fn create_icrc_ledger(
+    args: CreateIcrcLedgerArgs,
+    payment: EventPayment,
+) -> CreateCanisterResult {
+    let symbol = args.symbol.clone();
+    let name = args.name.clone();
+
+    let result = install_icrc_ledger(args).await;
+
+    let (canister_id, error) = created(&result);
+    record_paid_event(
+        EventKind::IcrcLedgerCreated {
+            canister_id,
+            symbol,
+            name,
+        },
+        payment,
+        error,
+    );
+
+    result
+}
+
+/// Creates an index for the caller, who has already paid for it, and records the creation with its
+/// payment and outcome.
+pub async fn create_icrc_index(
+    args: CreateIcrcIndexArgs,
+    payment: EventPayment,
+) -> CreateCanisterResult {
+    let ledger_id = args.ledger_id;
+
+    let result = install_icrc_index(args).await;
+
+    let (canister_id, error) = created(&result);
+    record_paid_event(
+        EventKind::IcrcIndexCreated {
+            canister_id,
+            ledger_id,
+        },
+        payment,
+        error,
+    );
+
+    result
+}
+
+fn created(result: &CreateCanisterResult) -> (Option<Principal>, Option<&CreateCanisterError>) {
+    match result {
+        CreateCanisterResult::Ok(canister_id) => (Some(*canister_id), None),
+        CreateCanisterResult::Err(err) => (None, Some(err)),
+    }
+}
+
+async fn install_icrc_ledger(args: CreateIcrcLedgerArgs) -> CreateCanisterResult {
+    let cycles = SignerMethods::CreateIcrcLedger.provisioned_cycles(args.settings.as_ref());
 
     let caller = caller();
 
+    let log = LogContext::start(None, &format!("Creating ICRC ledger for {caller}"));
+
     let ledger_wasm = get_stored_ledger_wasm();
     if ledger_wasm.is_empty() {
+        log.error("No ledger WASM stored");
         return CreateCanisterResult::Err(CreateCanisterError::NoWasmStored);
     }
 
-    let settings = CanisterSettings {
-        controllers: Some(vec![id(), caller]),
-        compute_allocation: None,
-        memory_allocation: None,
-        freezing_threshold: None,
-        reserved_cycles_limit: None,
-        log_visibility: None,
-        wasm_memory_limit: None,
-    };
+    let settings = canister_settings(vec![id(), caller], args.settings);
 
     let canister_id = match create_canister_with_ic_mgmt(Some(settings), cycles.into()).await {
         Ok(id) => id,
         Err(err) => {
-            return CreateCanisterResult::Err(CreateCanisterError::CanisterCreationFailed(err))
+            log.error(&format!("Canister creation failed: {err}"));
+            return CreateCanisterResult::Err(CreateCanisterError::CanisterCreationFailed(err));
         }
     };
 
+    let log = log.with_canister(canister_id);
+    log.info(&format!("Canister created with {cycles} cycles"));
+
     mutate_state(|state| {
         upsert_user_canister(
             StoredPrincipal(caller),
-            &mut state.user_canister,
-            UserCanister {
-                canister_id,
-                kind: UserCanisterKind::IcrcLedger,
-                installed: false,
-            },
+            state,
+            UserCanister::new(canister_id, UserCanisterKind::IcrcLedger, caller, false),
         );
     });
 
@@ -72,72 +128,86 @@ pub async fn create_icrc_ledger(args: CreateIcrcLedgerArgs) -> CreateCanisterRes
         subaccount: None,
     });
 
-    let init_args =
-        create_default_ledger_init_args(symbol, name, transfer_fee, decimals, minting_account);
+    let metadata = TokenMetadata {
+        symbol: symbol.clone(),
+        name: name.clone(),
+        decimals,
+        logo: args.logo.clone(),
+        index_id: None,
+    };
+
+    let init_args = create_default_ledger_init_args(
+        symbol,
+        name,
+        transfer_fee,
+        decimals,
+        minting_account,
+        args.logo,
+    );
     let arg = match Encode!(&init_args) {
         Ok(arg) => arg,
         Err(e) => {
-            return CreateCanisterResult::Err(CreateCanisterError::InitArgsEncodingFailed(format!(
-                "Failed to encode init args: {e}",
-            )))
+            log.error(&format!("Failed to encode init args: {e}"));
+            return CreateCanisterResult::Err(CreateCanisterError::InitArgsEncodingFailed(
+                format!("Failed to encode init args: {e}",),
+            ));
         }
     };
 
+    log.debug(&format!(
+        "Installing ledger WASM of {} bytes",
+        ledger_wasm.len()
+    ));
     if let Err(err) = install_wasm(canister_id, ledger_wasm, arg).await {
+        log.error(&format!("WASM installation failed: {err}"));
         return CreateCanisterResult::Err(CreateCanisterError::WasmInstallationFailed(err));
     }
+    log.info("Ledger installed");
 
     mutate_state(|state| {
         upsert_user_canister(
             StoredPrincipal(caller),
-            &mut state.user_canister,
-            UserCanister {
-                canister_id,
-                kind: UserCanisterKind::IcrcLedger,
-                installed: true,
-            },
+            state,
+            UserCanister::new(canister_id, UserCanisterKind::IcrcLedger, caller, true),
         );
     });
 
+    set_token_metadata(canister_id, metadata);
+
     CreateCanisterResult::Ok(canister_id)
 }
 
-pub async fn create_icrc_index(args: CreateIcrcIndexArgs) -> CreateCanisterResult {
-    let cycles = SignerMethods::CreateIcrcIndex.fee();
+async fn install_icrc_index(args: CreateIcrcIndexArgs) -> CreateCanisterResult {
+    let cycles = SignerMethods::CreateIcrcIndex.provisioned_cycles(args.settings.as_ref());
 
     let caller = caller();
 
+    let log = LogContext::start(None, &format!("Creating ICRC index for {caller}"));
+
     let index_wasm = get_stored_index_wasm();
     if index_wasm.is_empty() {
+        log.error("No index WASM stored");
         return CreateCanisterResult::Err(CreateCanisterError::NoWasmStored);
     }
 
-    let settings = CanisterSettings {
-        controllers: Some(vec![id(), caller]),
-        compute_allocation: None,
-        memory_allocation: None,
-        freezing_threshold: None,
-        reserved_cycles_limit: None,
-        log_visibility: None,
-        wasm_memory_limit: None,
-    };
+    let settings = canister_settings(vec![id(), caller], args.settings);
 
     let canister_id = match create_canister_with_ic_mgmt(Some(settings), cycles.into()).await {
         Ok(id) => id,
         Err(err) => {
-            return CreateCanisterResult::Err(CreateCanisterError::CanisterCreationFailed(err))
+            log.error(&format!("Canister creation failed: {err}"));
+            return CreateCanisterResult::Err(CreateCanisterError::CanisterCreationFailed(err));
         }
     };
 
+    let log = log.with_canister(canister_id);
+    log.info(&format!("Canister created with {cycles} cycles"));
+
     mutate_state(|state| {
         upsert_user_canister(
             StoredPrincipal(caller),
-            &mut state.user_canister,
-            UserCanister {
-                canister_id,
-                kind: UserCanisterKind::IcrcIndex,
-                installed: false,
-            },
+            state,
+            UserCanister::new(canister_id, UserCanisterKind::IcrcIndex, caller, false),
         );
     });
 
@@ -145,27 +215,37 @@ pub async fn create_icrc_index(args: CreateIcrcIndexArgs) -> CreateCanisterResul
     let arg = match Encode!(&init_args) {
         Ok(arg) => arg,
         Err(e) => {
-            return CreateCanisterResult::Err(CreateCanisterError::InitArgsEncodingFailed(format!(
-                "Failed to encode init args: {e}"
-            )))
+            log.error(&format!("Failed to encode init args: {e}"));
+            return CreateCanisterResult::Err(CreateCanisterError::InitArgsEncodingFailed(
+                format!("Failed to encode init args: {e}"),
+            ));
         }
     };
 
+    log.debug(&format!(
+        "Installing index WASM of {} bytes",
+        index_wasm.len()
+    ));
     if let Err(err) = install_wasm(canister_id, index_wasm, arg).await {
+        log.error(&format!("WASM installation failed: {err}"));
         return CreateCanisterResult::Err(CreateCanisterError::WasmInstallationFailed(err));
     }
+    log.info("Index installed");
 
     mutate_state(|state| {
         upsert_user_canister(
             StoredPrincipal(caller),
-            &mut state.user_canister,
-            UserCanister {
-                canister_id,
-                kind: UserCanisterKind::IcrcIndex,
-                installed: true,
-            },
+            state,
+            UserCanister::new(canister_id, UserCanisterKind::IcrcIndex, caller, true),
         );
     });
 
+    // Only the owner of the ledger can list an index for it in the token list.
+    if read_state(|s| get_owned_user_canister(s, caller, args.ledger_id)).is_ok() {
+        update_token_metadata(args.ledger_id, |metadata| {
+            metadata.index_id = Some(canister_id);
+        });
+    }
+
     CreateCanisterResult::Ok(canister_id)
 }
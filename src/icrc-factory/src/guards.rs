@@ -1,6 +1,12 @@
 use candid::Principal;
 use ic_cdk::{api::is_controller, caller};
 
+use crate::{
+    pause::{ensure_not_paused, PausableOperation},
+    roles::has_role,
+    types::role::Role,
+};
+
 pub fn caller_is_not_anonymous() -> Result<(), String> {
     if caller() == Principal::anonymous() {
         Err("Update call error. RejectionCode: CanisterReject, Error: Anonymous caller not authorized.".to_string())
@@ -17,3 +23,38 @@ pub fn caller_is_controller() -> Result<(), String> {
         Err("Caller is not a controller.".to_string())
     }
 }
+
+pub fn caller_is_wasm_manager() -> Result<(), String> {
+    caller_has_role(Role::WasmManager)
+}
+
+pub fn caller_is_pricing_manager() -> Result<(), String> {
+    caller_has_role(Role::PricingManager)
+}
+
+pub fn caller_is_operator() -> Result<(), String> {
+    caller_has_role(Role::Operator)
+}
+
+pub fn caller_is_auditor() -> Result<(), String> {
+    caller_has_role(Role::Auditor)
+}
+
+/// Passes for WASM managers while WASM updates are not paused.
+pub fn caller_may_update_wasm() -> Result<(), String> {
+    caller_is_wasm_manager()?;
+    ensure_not_paused(PausableOperation::WasmUpdates)
+        .map_err(|err| format!("{err:?}: WASM updates are paused."))
+}
+
+/// Passes for controllers and for the principals `role` is granted to.
+fn caller_has_role(role: Role) -> Result<(), String> {
+    let caller = caller();
+    if caller != Principal::anonymous() && has_role(caller, role) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Caller is not a controller and lacks the {role:?} role."
+        ))
+    }
+}
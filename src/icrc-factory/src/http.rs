@@ -0,0 +1,54 @@
+use serde_bytes::ByteBuf;
+
+use crate::{
+    certification::http_certificate_header,
+    metrics::encode_metrics,
+    tokens::token_list_page,
+    types::http::{HttpGatewayRequest, HttpGatewayResponse},
+};
+
+/// Serves the HTTP routes of the factory:
+/// - `GET /metrics`: the factory metrics, in the Prometheus text exposition format.
+/// - `GET /tokens.json` and `GET /tokens/{page}.json`: the certified pages of the token list.
+/// - `GET /tokens/index.json`: the certified index of the token list pages.
+#[must_use]
+pub fn http_request(request: &HttpGatewayRequest) -> HttpGatewayResponse {
+    let path = request.url.split('?').next().unwrap_or_default();
+
+    if request.method != "GET" {
+        return text_response(405, "Method not allowed");
+    }
+
+    match path {
+        "/metrics" => match encode_metrics() {
+            Ok(body) => response(200, "text/plain; version=0.0.4", body),
+            Err(err) => text_response(500, &format!("Failed to encode metrics: {err}")),
+        },
+        _ => match token_list_page(path) {
+            Some(body) => {
+                let mut response = response(200, "application/json", body);
+                response
+                    .headers
+                    .push(("Access-Control-Allow-Origin".to_string(), "*".to_string()));
+                response.headers.extend(http_certificate_header(path));
+                response
+            }
+            None => text_response(404, "Not found"),
+        },
+    }
+}
+
+fn response(status_code: u16, content_type: &str, body: Vec<u8>) -> HttpGatewayResponse {
+    HttpGatewayResponse {
+        status_code,
+        headers: vec![
+            ("Content-Type".to_string(), content_type.to_string()),
+            ("Content-Length".to_string(), body.len().to_string()),
+        ],
+        body: ByteBuf::from(body),
+    }
+}
+
+fn text_response(status_code: u16, message: &str) -> HttpGatewayResponse {
+    response(status_code, "text/plain", message.as_bytes().to_vec())
+}
@@ -0,0 +1,223 @@
+use std::{cell::Cell, time::Duration};
+
+use candid::{Nat, Principal};
+use ic_cdk::id;
+use ic_cdk_timers::{set_timer, set_timer_interval};
+use icrc_ledger_types::{
+    icrc1::{
+        account::{Account, Subaccount},
+        transfer::Memo,
+    },
+    icrc2::transfer_from::{TransferFromArgs, TransferFromError},
+};
+
+use crate::{
+    cmc::{self, NotifyTopUpError, MEMO_TOP_UP_CANISTER},
+    logging::LogContext,
+    prepaid::credit,
+    state::{cmc_canister, icp_ledger, mutate_state, read_state},
+    types::{
+        prepaid::PrepaidReason, results::create_canister::CreateCanisterError,
+        stored_principal::StoredPrincipal,
+    },
+};
+
+/// Transfer fee of the mainnet ICP ledger, in e8s.
+const DEFAULT_ICP_LEDGER_FEE: u64 = 10_000;
+
+const ICP_XDR_RATE_REFRESH_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// How often the CMC is notified again of ICP payments it has not converted yet.
+const PENDING_ICP_TOP_UP_RETRY_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+thread_local! {
+    /// ICP/XDR conversion rate of the CMC, as last read from it.
+    static ICP_XDR_RATE: Cell<Option<u64>> = const { Cell::new(None) };
+
+    /// Transfer fee of the ICP ledger, as last read from it.
+    static ICP_LEDGER_FEE: Cell<Option<u64>> = const { Cell::new(None) };
+}
+
+/// Converts `cycles` to e8s of ICP at `rate`, rounding up so that the factory is never short.
+#[must_use]
+pub fn cycles_to_e8s(cycles: u64, rate: u64) -> u64 {
+    cycles.div_ceil(rate)
+}
+
+/// ICP/XDR conversion rate last read from the CMC, or `None` before it has been read.
+#[must_use]
+pub fn cached_icp_xdr_rate() -> Option<u64> {
+    ICP_XDR_RATE.with(Cell::get)
+}
+
+/// Transfer fee of the ICP ledger, in e8s; the fee of the mainnet ICP ledger until the fee has
+/// been read from the configured one.
+#[must_use]
+pub fn icp_ledger_fee() -> u64 {
+    ICP_LEDGER_FEE
+        .with(Cell::get)
+        .unwrap_or(DEFAULT_ICP_LEDGER_FEE)
+}
+
+/// Reads the transfer fee of the ICP ledger once the canister is running, since queries cannot
+/// call other canisters.
+pub fn schedule_icp_ledger_fee_refresh() {
+    set_timer(Duration::ZERO, || ic_cdk::spawn(refresh_icp_ledger_fee()));
+}
+
+async fn refresh_icp_ledger_fee() {
+    if let Ok((fee,)) = ic_cdk::call::<(), (Nat,)>(icp_ledger(), "icrc1_fee", ()).await {
+        if let Ok(fee) = u64::try_from(fee.0) {
+            ICP_LEDGER_FEE.with(|cell| cell.set(Some(fee)));
+        }
+    }
+}
+
+/// Reads the ICP/XDR conversion rate now and then periodically, so that queries can quote ICP
+/// payments.
+pub fn start_icp_xdr_rate_timer() {
+    set_timer(Duration::ZERO, || ic_cdk::spawn(refresh_icp_xdr_rate()));
+    set_timer_interval(ICP_XDR_RATE_REFRESH_INTERVAL, || {
+        ic_cdk::spawn(refresh_icp_xdr_rate());
+    });
+}
+
+async fn refresh_icp_xdr_rate() {
+    if let Ok(rate) = cmc::icp_xdr_rate(cmc_canister()).await {
+        ICP_XDR_RATE.with(|cell| cell.set(Some(rate)));
+    }
+}
+
+/// Takes the ICP equivalent of `cycles` from `payer` and converts it to cycles for the factory.
+///
+/// The amount is priced at the current CMC conversion rate, pulled with ICRC-2 `transfer_from`
+/// into the CMC top-up account of the factory, then converted with `notify_top_up`. The payer
+/// pays the ledger's transfer fee on top of the amount.
+///
+/// # Errors
+/// - `IcpPaymentFailed` if the rate cannot be read, the transfer is refused or the CMC will never
+///   convert the transfer.
+/// - `IcpPaymentPending` if the ICP was taken but the conversion failed. The payment is kept, and
+///   retried by [`start_pending_icp_top_up_timer`] until the cycles can be credited to `payer`.
+pub async fn charge_icp(payer: Principal, cycles: u64) -> Result<(), CreateCanisterError> {
+    let cmc = cmc_canister();
+
+    let rate = cmc::icp_xdr_rate(cmc)
+        .await
+        .map_err(CreateCanisterError::IcpPaymentFailed)?;
+
+    ICP_XDR_RATE.with(|cell| cell.set(Some(rate)));
+
+    let to = Account {
+        owner: cmc,
+        subaccount: Some(principal_to_subaccount(id())),
+    };
+
+    let block_index = transfer_from(payer, to, cycles_to_e8s(cycles, rate))
+        .await
+        .map_err(CreateCanisterError::IcpPaymentFailed)?;
+
+    match cmc::notify_top_up(cmc, block_index, id()).await {
+        Ok(_) => Ok(()),
+        Err(NotifyTopUpError::Final(message)) => {
+            Err(CreateCanisterError::IcpPaymentFailed(message))
+        }
+        Err(NotifyTopUpError::Retryable(message)) => {
+            mutate_state(|s| {
+                s.pending_icp_top_ups
+                    .insert(block_index, StoredPrincipal(payer))
+            });
+            LogContext::start(
+                None,
+                &format!("ICP payment in block {block_index} is pending"),
+            )
+            .warn(&message);
+
+            Err(CreateCanisterError::IcpPaymentPending { block_index })
+        }
+    }
+}
+
+/// Notifies the CMC again, periodically, of the ICP payments it has not converted yet, and
+/// credits the cycles of every converted payment to its payer's prepaid balance. Timers do not
+/// survive upgrades, so this runs on every install and upgrade.
+pub fn start_pending_icp_top_up_timer() {
+    set_timer_interval(PENDING_ICP_TOP_UP_RETRY_INTERVAL, || {
+        ic_cdk::spawn(retry_pending_icp_top_ups());
+    });
+}
+
+async fn retry_pending_icp_top_ups() {
+    let pending: Vec<u64> = read_state(|s| s.pending_icp_top_ups.keys().collect());
+
+    for block_index in pending {
+        // Taken out before the call, so that an overlapping retry cannot credit the same payment.
+        let Some(StoredPrincipal(payer)) =
+            mutate_state(|s| s.pending_icp_top_ups.remove(&block_index))
+        else {
+            continue;
+        };
+
+        match cmc::notify_top_up(cmc_canister(), block_index, id()).await {
+            Ok(cycles) => credit(
+                payer,
+                u64::try_from(cycles).unwrap_or(u64::MAX),
+                PrepaidReason::IcpPaymentRecovered { block_index },
+            ),
+            Err(NotifyTopUpError::Retryable(_)) => {
+                mutate_state(|s| {
+                    s.pending_icp_top_ups
+                        .insert(block_index, StoredPrincipal(payer))
+                });
+            }
+            Err(NotifyTopUpError::Final(message)) => {
+                LogContext::start(
+                    None,
+                    &format!("ICP payment in block {block_index} is dropped"),
+                )
+                .error(&message);
+            }
+        }
+    }
+}
+
+/// Pulls `amount` e8s from the default account of `payer` into `to`, tagged as a canister top-up.
+///
+/// Returns the index of the ledger block recording the transfer.
+async fn transfer_from(payer: Principal, to: Account, amount: u64) -> Result<u64, String> {
+    let args = TransferFromArgs {
+        spender_subaccount: None,
+        from: Account {
+            owner: payer,
+            subaccount: None,
+        },
+        to,
+        amount: Nat::from(amount),
+        fee: None,
+        memo: Some(Memo::from(MEMO_TOP_UP_CANISTER.to_le_bytes().to_vec())),
+        created_at_time: None,
+    };
+
+    let (result,) = ic_cdk::call::<(TransferFromArgs,), (Result<Nat, TransferFromError>,)>(
+        icp_ledger(),
+        "icrc2_transfer_from",
+        (args,),
+    )
+    .await
+    .map_err(|(code, msg)| format!("Failed to call the ICP ledger: {code:?} - {msg}"))?;
+
+    let block_index = result.map_err(|err| format!("The ICP transfer was refused: {err:?}"))?;
+
+    u64::try_from(block_index.0)
+        .map_err(|_| "The ICP block index does not fit in a u64".to_string())
+}
+
+/// The CMC subaccount that top-ups of `principal` are sent to: the length of the principal
+/// followed by its bytes.
+fn principal_to_subaccount(principal: Principal) -> Subaccount {
+    let bytes = principal.as_slice();
+    let mut subaccount = [0; 32];
+    subaccount[0] = u8::try_from(bytes.len()).expect("principals are at most 29 bytes long");
+    subaccount[1..=bytes.len()].copy_from_slice(bytes);
+    subaccount
+}
@@ -0,0 +1,175 @@
+use std::collections::BTreeMap;
+
+use candid::Nat;
+use ic_cdk::api::data_certificate;
+use icrc_ledger_types::{
+    icrc::generic_value::ICRC3Value,
+    icrc3::{
+        archive::ICRC3ArchiveInfo,
+        blocks::{
+            BlockWithId, GetBlocksRequest, GetBlocksResult, ICRC3DataCertificate,
+            SupportedBlockType,
+        },
+    },
+};
+use serde_bytes::ByteBuf;
+
+use crate::{
+    certification::{cbor, certify_tip, tip_witness},
+    icrc3_value,
+    state::{mutate_state, read_state, State},
+    types::{
+        candid::Candid,
+        event::{Event, EventKind},
+    },
+};
+
+/// Maximum number of blocks returned by a single `icrc3_get_blocks` call, across all requests.
+const MAX_BLOCKS_PER_RESPONSE: u64 = 100;
+
+/// Prefix of the block types of factory actions, which are not defined by an ICRC standard.
+const BLOCK_TYPE_PREFIX: &str = "factory_";
+
+/// Documentation of the factory block types.
+const BLOCK_TYPES_URL: &str = "https://github.com/AntonioVentilii/icrc-factory#icrc-3-blocks";
+
+/// Block type of events of `event_type`: the type in snake case, e.g.
+/// `factory_icrc_ledger_created`.
+fn block_type(event_type: &str) -> String {
+    let mut block_type = BLOCK_TYPE_PREFIX.to_string();
+    for (i, c) in event_type.chars().enumerate() {
+        if c.is_ascii_uppercase() && i > 0 {
+            block_type.push('_');
+        }
+        block_type.push(c.to_ascii_lowercase());
+    }
+    block_type
+}
+
+/// Appends a block for every event not yet in the block log, then certifies the new tip.
+///
+/// Events are turned into blocks once, when they are recorded, so that their hashes never change
+/// even as `EventKind` grows. Events recorded before the block log existed are converted on the
+/// next append or upgrade.
+///
+/// # Panics
+/// - If the block log cannot grow, which only happens when stable memory is exhausted.
+pub fn sync_blocks(state: &mut State) {
+    for index in state.blocks.len()..state.events.len() {
+        let Some(Candid(event)) = state.events.get(index) else {
+            break;
+        };
+
+        let parent_hash = last_block_hash(state);
+
+        state
+            .blocks
+            .append(&Candid(block(&event, parent_hash)))
+            .expect("failed to append block to the block log");
+    }
+
+    if let Some(hash) = last_block_hash(state) {
+        certify_tip(state.blocks.len() - 1, hash);
+    }
+}
+
+/// Converts events recorded before the block log existed, and certifies the tip again.
+pub fn init_blocks() {
+    mutate_state(sync_blocks);
+}
+
+/// Returns the blocks in the requested ranges, oldest first, up to `MAX_BLOCKS_PER_RESPONSE`.
+#[must_use]
+pub fn get_blocks(requests: Vec<GetBlocksRequest>) -> GetBlocksResult {
+    read_state(|state| {
+        let log_length = state.blocks.len();
+        let mut budget = MAX_BLOCKS_PER_RESPONSE;
+        let mut blocks = Vec::new();
+
+        for GetBlocksRequest { start, length } in requests {
+            let start = u64::try_from(start.0).unwrap_or(u64::MAX);
+            let length = u64::try_from(length.0).unwrap_or(u64::MAX).min(budget);
+            let end = start.saturating_add(length).min(log_length);
+
+            for id in start..end {
+                if let Some(Candid(block)) = state.blocks.get(id) {
+                    blocks.push(BlockWithId {
+                        id: Nat::from(id),
+                        block,
+                    });
+                }
+            }
+
+            budget -= end.saturating_sub(start);
+        }
+
+        GetBlocksResult {
+            log_length: Nat::from(log_length),
+            blocks,
+            archived_blocks: Vec::new(),
+        }
+    })
+}
+
+/// Returns the certificate of the last block, or `None` while the log is empty.
+///
+/// Only available in queries, where the IC provides the certificate of the certified data.
+#[must_use]
+pub fn get_tip_certificate() -> Option<ICRC3DataCertificate> {
+    let certificate = data_certificate()?;
+    let tree = tip_witness()?;
+
+    Some(ICRC3DataCertificate {
+        certificate: ByteBuf::from(certificate),
+        hash_tree: ByteBuf::from(cbor(&tree)),
+    })
+}
+
+/// The factory keeps every block itself, so it never has archives.
+#[must_use]
+pub fn get_archives() -> Vec<ICRC3ArchiveInfo> {
+    Vec::new()
+}
+
+/// One block type per `EventKind`.
+#[must_use]
+pub fn supported_block_types() -> Vec<SupportedBlockType> {
+    EventKind::EVENT_TYPES
+        .iter()
+        .map(|event_type| SupportedBlockType {
+            block_type: block_type(event_type),
+            url: BLOCK_TYPES_URL.to_string(),
+        })
+        .collect()
+}
+
+/// The block of `event`: its type, timestamp, parent hash and `tx`, see [`icrc3_value::tx`].
+fn block(event: &Event, parent_hash: Option<[u8; 32]>) -> ICRC3Value {
+    let mut block = BTreeMap::from([
+        (
+            "btype".to_string(),
+            ICRC3Value::Text(block_type(event.kind.event_type())),
+        ),
+        (
+            "ts".to_string(),
+            ICRC3Value::Nat(Nat::from(event.timestamp)),
+        ),
+        ("tx".to_string(), icrc3_value::tx(event)),
+    ]);
+    if let Some(parent_hash) = parent_hash {
+        block.insert(
+            "phash".to_string(),
+            ICRC3Value::Blob(ByteBuf::from(parent_hash.to_vec())),
+        );
+    }
+
+    ICRC3Value::Map(block)
+}
+
+fn last_block_hash(state: &State) -> Option<[u8; 32]> {
+    let last_index = state.blocks.len().checked_sub(1)?;
+    state
+        .blocks
+        .get(last_index)
+        .map(|Candid(block)| block.hash())
+}
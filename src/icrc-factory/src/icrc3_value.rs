@@ -0,0 +1,426 @@
+//! The ICRC-3 values of the `tx` of factory blocks.
+//!
+//! Events are written with the generic ICRC-3 values rather than as Candid blobs, so that indexers
+//! can read them without the factory's Candid types:
+//! - principals and hashes are `Blob`s, and accounts the `Array` of their owner and subaccount
+//!   `Blob`s, as in ICRC-1 blocks;
+//! - numbers are `Nat`s, and flags `Nat`s of 0 or 1;
+//! - strings are `Text`s, and enums without data the `Text` of their variant;
+//! - records are `Map`s, and enums with data the `Map` of their fields along with the variant as
+//!   `type`;
+//! - optional fields are left out when `None`.
+use std::collections::BTreeMap;
+
+use candid::{Nat, Principal};
+use icrc_ledger_types::{icrc::generic_value::ICRC3Value, icrc1::account::Account};
+use serde_bytes::ByteBuf;
+
+use crate::types::{
+    access_list::{AccessList, CreationAccess},
+    config::{Config, PauseFlags},
+    discounts::{Discount, PromoCode},
+    event::{Event, EventKind, EventOutcome, EventPayment},
+    pricing::Pricing,
+    quote::PaymentMethod,
+    rate_limit::{RateLimit, RateLimits},
+    role::Role,
+};
+
+/// A value with an ICRC-3 representation.
+trait ToIcrc3Value {
+    fn to_icrc3_value(&self) -> ICRC3Value;
+}
+
+/// The fields of a `Map` value, built up one field at a time.
+#[derive(Default)]
+struct Fields(BTreeMap<String, ICRC3Value>);
+
+impl Fields {
+    fn with(mut self, name: &str, value: &impl ToIcrc3Value) -> Self {
+        self.0.insert(name.to_string(), value.to_icrc3_value());
+        self
+    }
+
+    /// Adds the field unless `value` is `None`.
+    fn with_opt(self, name: &str, value: Option<&impl ToIcrc3Value>) -> Self {
+        match value {
+            Some(value) => self.with(name, value),
+            None => self,
+        }
+    }
+
+    fn with_blob(mut self, name: &str, bytes: &[u8]) -> Self {
+        self.0.insert(name.to_string(), blob(bytes));
+        self
+    }
+
+    /// The fields of an enum variant with data, tagged with the variant name as `type`.
+    fn variant(name: &str) -> Self {
+        Self::default().with("type", &name.to_string())
+    }
+
+    fn into_value(self) -> ICRC3Value {
+        ICRC3Value::Map(self.0)
+    }
+}
+
+fn blob(bytes: &[u8]) -> ICRC3Value {
+    ICRC3Value::Blob(ByteBuf::from(bytes))
+}
+
+/// The `tx` of the block of `event`: the caller, the fields of its kind, and the payment and
+/// outcome of a paid action. The kind itself is given by the block type.
+pub fn tx(event: &Event) -> ICRC3Value {
+    kind_fields(&event.kind)
+        .with("caller", &event.caller)
+        .with_opt("payment", event.payment.as_ref())
+        .with_opt("outcome", event.outcome.as_ref())
+        .into_value()
+}
+
+#[allow(clippy::too_many_lines)]
+fn kind_fields(kind: &EventKind) -> Fields {
+    let fields = Fields::default();
+
+    match kind {
+        EventKind::IcrcLedgerCreated {
+            canister_id,
+            symbol,
+            name,
+        } => fields
+            .with_opt("canister_id", canister_id.as_ref())
+            .with_opt("symbol", symbol.as_ref())
+            .with_opt("name", name.as_ref()),
+        EventKind::IcrcIndexCreated {
+            canister_id,
+            ledger_id,
+        } => fields
+            .with_opt("canister_id", canister_id.as_ref())
+            .with("ledger_id", ledger_id),
+        EventKind::LedgerWasmSet { module_hash, url }
+        | EventKind::IndexWasmSet { module_hash, url } => fields
+            .with_blob("module_hash", module_hash)
+            .with_opt("url", url.as_ref()),
+        EventKind::SymbolSet { ledger_id, symbol } => {
+            fields.with("ledger_id", ledger_id).with("symbol", symbol)
+        }
+        EventKind::NameSet { ledger_id, name } => {
+            fields.with("ledger_id", ledger_id).with("name", name)
+        }
+        EventKind::IndexCanisterSet {
+            ledger_id,
+            index_id,
+        } => fields
+            .with("ledger_id", ledger_id)
+            .with("index_id", index_id),
+        EventKind::ConfigUpdated { config } => fields.with("config", config),
+        EventKind::OwnershipTransferred {
+            canister_id,
+            from,
+            to,
+        } => fields
+            .with("canister_id", canister_id)
+            .with("from", from)
+            .with("to", to),
+        EventKind::FactoryControlRenounced { canister_id }
+        | EventKind::AutoTopUpRemoved { canister_id }
+        | EventKind::CanisterStopped { canister_id }
+        | EventKind::CanisterStarted { canister_id } => fields.with("canister_id", canister_id),
+        EventKind::LedgerMadeImmutable {
+            canister_id,
+            module_hash,
+        } => fields
+            .with("canister_id", canister_id)
+            .with_blob("module_hash", module_hash),
+        EventKind::ControllerAdded {
+            canister_id,
+            controller,
+        }
+        | EventKind::ControllerRemoved {
+            canister_id,
+            controller,
+        } => fields
+            .with("canister_id", canister_id)
+            .with("controller", controller),
+        EventKind::CanisterToppedUp {
+            canister_id,
+            cycles,
+        } => fields
+            .with("canister_id", canister_id)
+            .with("cycles", cycles),
+        EventKind::PrepaidBalanceFunded { owner, cycles }
+        | EventKind::ExcessCyclesCredited { owner, cycles } => {
+            fields.with("owner", owner).with("cycles", cycles)
+        }
+        EventKind::AutoTopUpSet {
+            canister_id,
+            threshold,
+            refill,
+        } => fields
+            .with("canister_id", canister_id)
+            .with("threshold", threshold)
+            .with("refill", refill),
+        EventKind::AutoToppedUp {
+            owner,
+            canister_id,
+            cycles,
+        } => fields
+            .with("owner", owner)
+            .with("canister_id", canister_id)
+            .with("cycles", cycles),
+        EventKind::AutoTopUpFailed {
+            canister_id,
+            reason,
+        } => fields
+            .with("canister_id", canister_id)
+            .with("reason", reason),
+        EventKind::PrepaidBalanceExhausted {
+            owner,
+            canister_id,
+            balance,
+            required,
+        } => fields
+            .with("owner", owner)
+            .with("canister_id", canister_id)
+            .with("balance", balance)
+            .with("required", required),
+        EventKind::CanisterDeleted {
+            canister_id,
+            recovered_cycles,
+            recovered_to,
+        } => fields
+            .with("canister_id", canister_id)
+            .with("recovered_cycles", recovered_cycles)
+            .with_opt("recovered_to", recovered_to.as_ref()),
+        EventKind::PricingUpdated { pricing } => fields.with("pricing", pricing),
+        EventKind::PrepaidBalanceWithdrawn { owner, cycles, to } => fields
+            .with("owner", owner)
+            .with("cycles", cycles)
+            .with("to", to),
+        EventKind::FreeQuotaSet {
+            principal,
+            creations,
+        } => fields
+            .with("principal", principal)
+            .with("creations", creations),
+        EventKind::PromoCodeSet { promo_code } => fields.with("promo_code", promo_code),
+        EventKind::PromoCodeRemoved { code } => fields.with("code", code),
+        EventKind::CustomPricingSet { principal, pricing } => fields
+            .with("principal", principal)
+            .with_opt("pricing", pricing.as_ref()),
+        EventKind::RoleGranted { principal, role } | EventKind::RoleRevoked { principal, role } => {
+            fields.with("principal", principal).with("role", role)
+        }
+        EventKind::CreationAccessListUpdated {
+            list,
+            principals,
+            listed,
+        } => fields
+            .with("list", list)
+            .with("principals", principals)
+            .with("listed", listed),
+    }
+}
+
+impl ToIcrc3Value for u16 {
+    fn to_icrc3_value(&self) -> ICRC3Value {
+        ICRC3Value::Nat(Nat::from(*self))
+    }
+}
+
+impl ToIcrc3Value for u32 {
+    fn to_icrc3_value(&self) -> ICRC3Value {
+        ICRC3Value::Nat(Nat::from(*self))
+    }
+}
+
+impl ToIcrc3Value for u64 {
+    fn to_icrc3_value(&self) -> ICRC3Value {
+        ICRC3Value::Nat(Nat::from(*self))
+    }
+}
+
+impl ToIcrc3Value for bool {
+    fn to_icrc3_value(&self) -> ICRC3Value {
+        ICRC3Value::Nat(Nat::from(u8::from(*self)))
+    }
+}
+
+impl ToIcrc3Value for Nat {
+    fn to_icrc3_value(&self) -> ICRC3Value {
+        ICRC3Value::Nat(self.clone())
+    }
+}
+
+impl ToIcrc3Value for String {
+    fn to_icrc3_value(&self) -> ICRC3Value {
+        ICRC3Value::Text(self.clone())
+    }
+}
+
+impl ToIcrc3Value for Principal {
+    fn to_icrc3_value(&self) -> ICRC3Value {
+        blob(self.as_slice())
+    }
+}
+
+impl<T: ToIcrc3Value> ToIcrc3Value for Vec<T> {
+    fn to_icrc3_value(&self) -> ICRC3Value {
+        ICRC3Value::Array(self.iter().map(ToIcrc3Value::to_icrc3_value).collect())
+    }
+}
+
+impl ToIcrc3Value for Account {
+    fn to_icrc3_value(&self) -> ICRC3Value {
+        let mut account = vec![blob(self.owner.as_slice())];
+        if let Some(subaccount) = &self.subaccount {
+            account.push(blob(subaccount));
+        }
+        ICRC3Value::Array(account)
+    }
+}
+
+impl ToIcrc3Value for Config {
+    fn to_icrc3_value(&self) -> ICRC3Value {
+        Fields::default()
+            .with("cycles_ledger", &self.cycles_ledger)
+            .with_opt("blackhole_canister", self.blackhole_canister.as_ref())
+            .with_opt("icp_ledger", self.icp_ledger.as_ref())
+            .with_opt("cmc", self.cmc.as_ref())
+            .with_opt("paused", self.paused.as_ref())
+            .with_opt("rate_limits", self.rate_limits.as_ref())
+            .with_opt("creation_access", self.creation_access.as_ref())
+            .into_value()
+    }
+}
+
+impl ToIcrc3Value for PauseFlags {
+    fn to_icrc3_value(&self) -> ICRC3Value {
+        Fields::default()
+            .with("creation", &self.creation)
+            .with("upgrades", &self.upgrades)
+            .with("wasm_updates", &self.wasm_updates)
+            .into_value()
+    }
+}
+
+impl ToIcrc3Value for RateLimits {
+    fn to_icrc3_value(&self) -> ICRC3Value {
+        Fields::default()
+            .with_opt("per_principal", self.per_principal.as_ref())
+            .with_opt("global", self.global.as_ref())
+            .into_value()
+    }
+}
+
+impl ToIcrc3Value for RateLimit {
+    fn to_icrc3_value(&self) -> ICRC3Value {
+        Fields::default()
+            .with("max_calls", &self.max_calls)
+            .with("window_secs", &self.window_secs)
+            .into_value()
+    }
+}
+
+impl ToIcrc3Value for CreationAccess {
+    fn to_icrc3_value(&self) -> ICRC3Value {
+        let access = match self {
+            CreationAccess::Open => "Open",
+            CreationAccess::AllowlistOnly => "AllowlistOnly",
+        };
+        ICRC3Value::Text(access.to_string())
+    }
+}
+
+impl ToIcrc3Value for AccessList {
+    fn to_icrc3_value(&self) -> ICRC3Value {
+        let list = match self {
+            AccessList::Allowlist => "Allowlist",
+            AccessList::Denylist => "Denylist",
+        };
+        ICRC3Value::Text(list.to_string())
+    }
+}
+
+impl ToIcrc3Value for Role {
+    fn to_icrc3_value(&self) -> ICRC3Value {
+        let role = match self {
+            Role::WasmManager => "WasmManager",
+            Role::PricingManager => "PricingManager",
+            Role::Operator => "Operator",
+            Role::Auditor => "Auditor",
+        };
+        ICRC3Value::Text(role.to_string())
+    }
+}
+
+impl ToIcrc3Value for Pricing {
+    fn to_icrc3_value(&self) -> ICRC3Value {
+        Fields::default()
+            .with("create_icrc_ledger", &self.create_icrc_ledger)
+            .with("create_icrc_index", &self.create_icrc_index)
+            .with("top_up_canister", &self.top_up_canister)
+            .with("margin_bps", &self.margin_bps)
+            .with_opt("updated_at", self.updated_at.as_ref())
+            .into_value()
+    }
+}
+
+impl ToIcrc3Value for PromoCode {
+    fn to_icrc3_value(&self) -> ICRC3Value {
+        Fields::default()
+            .with("code", &self.code)
+            .with("discount_bps", &self.discount_bps)
+            .with_opt("expires_at", self.expires_at.as_ref())
+            .with_opt("max_uses", self.max_uses.as_ref())
+            .with("uses", &self.uses)
+            .into_value()
+    }
+}
+
+impl ToIcrc3Value for EventPayment {
+    fn to_icrc3_value(&self) -> ICRC3Value {
+        Fields::default()
+            .with("method", &self.method)
+            .with("cycles", &self.cycles)
+            .with_opt("discount", self.discount.as_ref())
+            .into_value()
+    }
+}
+
+impl ToIcrc3Value for PaymentMethod {
+    fn to_icrc3_value(&self) -> ICRC3Value {
+        let method = Fields::variant(self.name());
+
+        match self {
+            PaymentMethod::CallerPaysIcrc2Tokens { ledger }
+            | PaymentMethod::PatronPaysIcrc2Tokens { ledger }
+            | PaymentMethod::CallerPaysIcp { ledger } => method.with("ledger", ledger),
+            PaymentMethod::AttachedCycles
+            | PaymentMethod::CallerPaysIcrc2Cycles
+            | PaymentMethod::PatronPaysIcrc2Cycles
+            | PaymentMethod::PrepaidBalance => method,
+        }
+        .into_value()
+    }
+}
+
+impl ToIcrc3Value for Discount {
+    fn to_icrc3_value(&self) -> ICRC3Value {
+        match self {
+            Discount::FreeQuota => Fields::variant("FreeQuota"),
+            Discount::PromoCode { code } => Fields::variant("PromoCode").with("code", code),
+        }
+        .into_value()
+    }
+}
+
+impl ToIcrc3Value for EventOutcome {
+    fn to_icrc3_value(&self) -> ICRC3Value {
+        match self {
+            EventOutcome::Succeeded => Fields::variant("Succeeded"),
+            EventOutcome::Failed { reason } => Fields::variant("Failed").with("reason", reason),
+        }
+        .into_value()
+    }
+}
@@ -1,10 +1,13 @@
-use candid::{CandidType, Nat};
-use icrc_ledger_types::icrc1::account::Account;
+use candid::{CandidType, Nat, Principal};
+use icrc_ledger_types::{
+    icrc::generic_metadata_value::MetadataValue as Value, icrc1::account::Account,
+};
 use serde::{Deserialize, Serialize};
 
 use crate::types::ledger_suite::{
     common::FeatureFlags,
     ledger::{
+        archive_info::ArchiveInfo,
         init_args::{ArchiveOptions, InitArgs},
         upgrade_args::UpgradeArgs,
     },
@@ -22,13 +25,16 @@ pub fn create_default_ledger_init_args(
     transfer_fee: u64,
     decimals: u8,
     minting_account: Account,
+    logo: Option<String>,
 ) -> LedgerArgs {
     LedgerArgs::Init(InitArgs {
         token_symbol: symbol,
         token_name: name,
         transfer_fee: Nat::from(transfer_fee),
         decimals: Some(decimals),
-        metadata: vec![],
+        metadata: logo
+            .map(|logo| vec![("icrc1:logo".to_string(), Value::Text(logo))])
+            .unwrap_or_default(),
         feature_flags: Some(FeatureFlags { icrc2: true }),
         minting_account,
         initial_balances: vec![],
@@ -47,3 +53,11 @@ pub fn create_default_ledger_init_args(
         max_memo_length: None,
     })
 }
+
+/// Lists the archive canisters that a ledger has spawned so far.
+pub async fn list_archives(ledger_id: Principal) -> Result<Vec<Principal>, String> {
+    ic_cdk::call::<(), (Vec<ArchiveInfo>,)>(ledger_id, "archives", ())
+        .await
+        .map(|(archives,)| archives.into_iter().map(|a| a.canister_id).collect())
+        .map_err(|(code, msg)| format!("Failed to list archives: {code:?} - {msg}"))
+}
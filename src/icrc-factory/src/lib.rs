@@ -1,43 +1,111 @@
+mod access_list;
+mod auto_top_up;
 mod canister;
+mod certification;
+mod cmc;
+mod controllers;
+mod cycles_ledger;
+mod discounts;
+mod events;
 mod generic;
 mod guards;
+mod http;
+mod icp;
+mod icrc3;
+mod icrc3_value;
 mod index;
 mod ledger;
+mod lifecycle;
+mod logging;
 pub mod methods;
+mod metrics;
 mod mgmt;
+mod ownership;
+mod pause;
+mod payment;
+mod prepaid;
+mod pricing;
+mod rate_limit;
+mod roles;
+mod settings;
 mod state;
+mod status;
+mod tokens;
+mod top_up;
 pub mod types;
 mod user_canister;
 mod wasm;
 
+use candid::Principal;
 use ic_cdk::{
     api::management_canister::http_request::{HttpResponse, TransformArgs},
     export_candid, init, post_upgrade, query, update,
 };
 use ic_papi_api::PaymentType;
+use icrc_ledger_types::icrc3::{
+    archive::{GetArchivesArgs, ICRC3ArchiveInfo},
+    blocks::{GetBlocksRequest, GetBlocksResult, ICRC3DataCertificate, SupportedBlockType},
+};
 
 use crate::{
     canister::upgrade_ledger_canister,
-    guards::{caller_is_controller, caller_is_not_anonymous},
+    certification::certificate_with_witness,
+    discounts::{creation_charge, pay_for_creation},
+    events::record_event,
+    guards::{
+        caller_is_auditor, caller_is_controller, caller_is_not_anonymous, caller_is_operator,
+        caller_is_pricing_manager, caller_may_update_wasm,
+    },
     ledger::LedgerArgs,
     methods::SignerMethods,
     mgmt::upgrade_wasm,
-    state::{read_config, read_state, set_config, PAYMENT_GUARD},
+    payment::{charge, charge_with_guard, check_max_fee, payment_method},
+    state::{read_config, read_state, set_config},
     types::{
-        args::create_canister::{
-            CreateIcrcIndexArgs, CreateIcrcLedgerArgs, SetIndexCanisterArgs, SetNameArgs,
-            SetSymbolArgs, UpgradeLedgerCanisterArgs,
+        access_list::{AccessList, AccessListEntry, CreationAccess},
+        args::{
+            access_list::SetAccessListArgs,
+            auto_top_up::{FundPrepaidBalanceArgs, SetAutoTopUpArgs, WithdrawPrepaidBalanceArgs},
+            controllers::{AddControllerArgs, RemoveControllerArgs},
+            create_canister::{
+                CreateIcrcIndexArgs, CreateIcrcLedgerArgs, SetIndexCanisterArgs, SetNameArgs,
+                SetSymbolArgs, UpgradeLedgerCanisterArgs,
+            },
+            discounts::{SetCustomPricingArgs, SetFreeQuotaArgs, SetPromoCodeArgs},
+            lifecycle::DeleteCanisterArgs,
+            ownership::{
+                AcceptOwnershipTransferArgs, MakeLedgerImmutableArgs, ProposeOwnershipTransferArgs,
+            },
+            pause::SetPausedArgs,
+            pricing::SetPricingArgs,
+            roles::RoleArgs,
+            top_up::TopUpCanisterArgs,
         },
-        candid::Candid,
+        auto_top_up::AutoTopUp,
+        certified::{CertifiedConfig, CertifiedUserCanisters},
         config::{Args, Config},
+        discounts::{CustomPricing, PromoCode, PromoCodeRedemption},
+        event::{Event, EventFilter, EventKind, EventPayment},
+        http::{HttpGatewayRequest, HttpGatewayResponse},
         ledger_suite::ledger::upgrade_args::UpgradeArgs,
+        log::{LogEntry, LogFilter},
+        payment::FactoryPaymentType,
+        prepaid::PrepaidJournalEntry,
+        pricing::Pricing,
+        rate_limit::RateLimits,
         results::{
+            canister_status::{CanisterStatusEntry, CanisterStatusResult},
             create_canister::{CreateCanisterError, CreateCanisterResult, SetCanisterResult},
+            lifecycle::DeleteCanisterResult,
+            logs::ListLogsResult,
+            quote::QuoteResult,
             set_wasm::SetWasmResult,
         },
+        role::{Role, RoleAssignment},
         stored_principal::StoredPrincipal,
         user_canister::UserCanister,
     },
+    user_canister::list_canisters_page,
     wasm::ledger_wasm::get_stored_ledger_wasm,
 };
 
@@ -58,6 +126,15 @@ pub fn init(args: Args) {
         Args::Init(args) => set_config(args),
         Args::Upgrade => ic_cdk::trap("upgrade args in init"),
     }
+
+    tokens::refresh_token_list();
+
+    auto_top_up::start_auto_top_up_timer();
+    payment::schedule_payment_ledger_fee_refresh();
+    icp::schedule_icp_ledger_fee_refresh();
+    icp::start_icp_xdr_rate_timer();
+    icp::start_pending_icp_top_up_timer();
+    rate_limit::start_rate_limit_cleanup_timer();
 }
 
 /// Restores or validates state after a canister upgrade.
@@ -68,6 +145,15 @@ pub fn init(args: Args) {
 /// # Behaviour
 /// - If `Args::Init` is provided, the configuration is overwritten.
 /// - Otherwise, the existing configuration is validated.
+/// - Registry entries stored before the owner index existed are indexed by canister ID.
+/// - The configuration and the registry are certified again.
+/// - Events recorded before the ICRC-3 block log existed are converted to blocks, and the tip is
+///   certified again.
+/// - The token list is built and certified again, and the metadata of ledgers created before it
+///   existed is read from the ledgers.
+/// - The auto top-up, pending ICP payment and rate limit cleanup timers are restarted, and the
+///   payment and ICP ledger fees and the ICP/XDR rate are read again. Calls made before the upgrade
+///   no longer count against the rate limits.
 ///
 /// # Panics
 /// - If the canister is upgraded without an existing configuration, indicating an invalid upgrade
@@ -84,6 +170,20 @@ pub fn post_upgrade(arg: Option<Args>) {
             });
         }
     }
+
+    certification::certify_config(&read_config(std::clone::Clone::clone));
+    user_canister::init_canister_owners();
+    user_canister::init_certified_registry();
+    icrc3::init_blocks();
+    tokens::refresh_token_list();
+    tokens::schedule_token_metadata_backfill();
+
+    auto_top_up::start_auto_top_up_timer();
+    payment::schedule_payment_ledger_fee_refresh();
+    icp::schedule_icp_ledger_fee_refresh();
+    icp::start_icp_xdr_rate_timer();
+    icp::start_pending_icp_top_up_timer();
+    rate_limit::start_rate_limit_cleanup_timer();
 }
 
 /// Returns the current canister configuration.
@@ -98,6 +198,28 @@ pub fn config() -> Config {
     read_config(std::clone::Clone::clone)
 }
 
+/// Returns the current canister configuration, with a certificate.
+///
+/// # Access Control
+/// - Caller must not be anonymous.
+///
+/// # Returns
+/// - The stored `Config`, the certificate of the factory, and a witness revealing the SHA-256 hash
+///   of the Candid-encoded configuration at `config`.
+///
+/// # Panics
+/// - If called as an update, since the IC only provides certificates to queries.
+#[query(guard = "caller_is_not_anonymous")]
+fn config_certified() -> CertifiedConfig {
+    let (certificate, witness) = certificate_with_witness(&certification::config_witness());
+
+    CertifiedConfig {
+        config: read_config(std::clone::Clone::clone),
+        certificate,
+        witness,
+    }
+}
+
 /// Transforms HTTP responses when fetching WASM binaries.
 ///
 /// # Purpose
@@ -113,14 +235,34 @@ fn transform_wasm_response(args: TransformArgs) -> HttpResponse {
     crate::wasm::utils::transform_wasm_response(args)
 }
 
+/// Serves the factory over HTTP.
+///
+/// # Routes
+/// - `GET /metrics`: canisters by kind and install status, creations by outcome, the cycles
+///   balance, stable memory usage, stored WASM sizes and hashes, and payment totals by payment
+///   method, in the Prometheus text exposition format.
+/// - `GET /tokens.json`: the installed ledgers with their symbol, name, decimals, logo, index and
+///   owner, as a certified token list of up to 100 tokens, and 1 MiB of tokens, per page. Further
+///   pages are served at `/tokens/{page}.json`.
+/// - `GET /tokens/index.json`: the number of listed tokens and the path of every page of the token
+///   list.
+///
+/// # Returns
+/// - `404` for unknown paths, and `405` for methods other than `GET`.
+#[query]
+fn http_request(request: HttpGatewayRequest) -> HttpGatewayResponse {
+    http::http_request(&request)
+}
+
 /// Stores a new ICRC ledger WASM binary.
 ///
 /// # Access Control
-/// - Caller must be a controller.
+/// - Caller must be a controller or hold the `WasmManager` role.
+/// - WASM updates must not be paused.
 ///
 /// # Arguments
 /// - `wasm`: Raw WASM bytecode to store.
-#[update(guard = "caller_is_controller")]
+#[update(guard = "caller_may_update_wasm")]
 fn set_ledger_wasm(wasm: Vec<u8>) {
     crate::wasm::ledger_wasm::set_ledger_wasm(wasm);
 }
@@ -128,14 +270,15 @@ fn set_ledger_wasm(wasm: Vec<u8>) {
 /// Fetches and stores an ICRC ledger WASM binary from a URL.
 ///
 /// # Access Control
-/// - Caller must be a controller.
+/// - Caller must be a controller or hold the `WasmManager` role.
+/// - WASM updates must not be paused.
 ///
 /// # Arguments
 /// - `url`: URL pointing to the WASM binary.
 ///
 /// # Returns
 /// - `SetWasmResult` indicating success or failure.
-#[update(guard = "caller_is_controller")]
+#[update(guard = "caller_may_update_wasm")]
 async fn set_ledger_wasm_from_url(url: String) -> SetWasmResult {
     crate::wasm::ledger_wasm::set_ledger_wasm_from_url(url)
         .await
@@ -145,11 +288,12 @@ async fn set_ledger_wasm_from_url(url: String) -> SetWasmResult {
 /// Stores a new ICRC index WASM binary.
 ///
 /// # Access Control
-/// - Caller must be a controller.
+/// - Caller must be a controller or hold the `WasmManager` role.
+/// - WASM updates must not be paused.
 ///
 /// # Arguments
 /// - `wasm`: Raw WASM bytecode to store.
-#[update(guard = "caller_is_controller")]
+#[update(guard = "caller_may_update_wasm")]
 fn set_index_wasm(wasm: Vec<u8>) {
     crate::wasm::index_wasm::set_index_wasm(wasm);
 }
@@ -157,20 +301,387 @@ fn set_index_wasm(wasm: Vec<u8>) {
 /// Fetches and stores an ICRC index WASM binary from a URL.
 ///
 /// # Access Control
-/// - Caller must be a controller.
+/// - Caller must be a controller or hold the `WasmManager` role.
+/// - WASM updates must not be paused.
 ///
 /// # Arguments
 /// - `url`: URL pointing to the WASM binary.
 ///
 /// # Returns
 /// - `SetWasmResult` indicating success or failure.
-#[update(guard = "caller_is_controller")]
+#[update(guard = "caller_may_update_wasm")]
 async fn set_index_wasm_from_url(url: String) -> SetWasmResult {
     crate::wasm::index_wasm::set_index_wasm_from_url(url)
         .await
         .into()
 }
 
+/// Updates the pricing table.
+///
+/// # Access Control
+/// - Caller must be a controller or hold the `PricingManager` role.
+///
+/// # Arguments
+/// - `args`: [`SetPricingArgs`]
+///   - `create_icrc_ledger`, `create_icrc_index`, `top_up_canister`: optional base fees, in cycles.
+///   - `margin_bps`: optional service margin, in basis points, added to every fee.
+///   - Omitted fields keep their current value.
+///
+/// # Behaviour
+/// - The change is timestamped and recorded as a `PricingUpdated` event.
+///
+/// # Returns
+/// - `SetCanisterResult::Ok(())` once the pricing is stored.
+/// - `SetCanisterResult::Err(CreateCanisterError::InvalidPricing)` if a creation fee does not cover
+///   the cycles a new canister starts with, or the margin is above 100%.
+#[update(guard = "caller_is_pricing_manager")]
+fn set_pricing(args: SetPricingArgs) -> SetCanisterResult {
+    pricing::set_pricing(args)
+}
+
+/// Returns the current pricing table.
+///
+/// Fees charged by paid methods are the base fee, plus any requested resource allocation, plus the
+/// service margin.
+#[query]
+fn get_pricing() -> Pricing {
+    pricing::get_pricing()
+}
+
+/// Sets the number of free canister creations a principal has left.
+///
+/// # Access Control
+/// - Caller must be a controller or hold the `PricingManager` role.
+///
+/// # Arguments
+/// - `args`: [`SetFreeQuotaArgs`]
+///   - `principal`: **required** principal granted the quota.
+///   - `creations`: **required** free creations left; 0 removes the quota.
+///
+/// # Behaviour
+/// - Each `create_icrc_ledger` or `create_icrc_index` call of the principal uses one free creation
+///   before any other discount, and charges nothing.
+///
+/// # Returns
+/// - `SetCanisterResult::Ok(())` once the quota is stored.
+#[update(guard = "caller_is_pricing_manager")]
+fn set_free_quota(args: SetFreeQuotaArgs) -> SetCanisterResult {
+    discounts::set_free_quota(args)
+}
+
+/// Returns the number of free canister creations the caller has left.
+#[query(guard = "caller_is_not_anonymous")]
+fn free_quota() -> u32 {
+    discounts::free_quota(ic_cdk::caller())
+}
+
+/// Creates or updates a promo code discounting canister creations.
+///
+/// # Access Control
+/// - Caller must be a controller or hold the `PricingManager` role.
+///
+/// # Arguments
+/// - `args`: [`SetPromoCodeArgs`]
+///   - `code`: **required** code, 1 to 32 bytes long.
+///   - `discount_bps`: **required** discount, in basis points, taken off the fee; at most 10 000.
+///   - `expires_at`: optional IC time, in nanoseconds, after which the code is refused.
+///   - `max_uses`: optional number of redemptions allowed, including past ones.
+///
+/// # Behaviour
+/// - Updating an existing code keeps its number of redemptions.
+///
+/// # Returns
+/// - `SetCanisterResult::Ok(())` once the code is stored.
+/// - `SetCanisterResult::Err(CreateCanisterError::InvalidPromoCode)` if the code is empty or too
+///   long, or the discount is above 100%.
+#[update(guard = "caller_is_pricing_manager")]
+fn set_promo_code(args: SetPromoCodeArgs) -> SetCanisterResult {
+    discounts::set_promo_code(args)
+}
+
+/// Removes a promo code.
+///
+/// # Access Control
+/// - Caller must be a controller or hold the `PricingManager` role.
+///
+/// # Returns
+/// - `SetCanisterResult::Ok(())` once the code is removed; its redemptions stay in the audit trail.
+/// - `SetCanisterResult::Err(CreateCanisterError::InvalidPromoCode)` if the code is unknown.
+#[update(guard = "caller_is_pricing_manager")]
+fn remove_promo_code(code: String) -> SetCanisterResult {
+    discounts::remove_promo_code(code)
+}
+
+/// Returns every promo code, with its number of redemptions.
+///
+/// # Access Control
+/// - Caller must be a controller or hold the `Auditor` role.
+#[query(guard = "caller_is_auditor")]
+fn list_promo_codes() -> Vec<PromoCode> {
+    discounts::list_promo_codes()
+}
+
+/// Returns the audit trail of promo code redemptions, oldest first.
+///
+/// # Access Control
+/// - Caller must be a controller or hold the `Auditor` role.
+///
+/// # Arguments
+/// - `offset`: Optional number of redemptions to skip. Defaults to 0.
+/// - `limit`: Optional maximum number of redemptions to return. Defaults to 50.
+///
+/// # Returns
+/// - Every paid-for redemption, with its redeemer, method, and fee before and after the discount.
+#[query(guard = "caller_is_auditor")]
+fn list_promo_code_redemptions(
+    offset: Option<u64>,
+    limit: Option<u64>,
+) -> Vec<PromoCodeRedemption> {
+    discounts::list_promo_code_redemptions(offset.unwrap_or(0), limit.unwrap_or(50))
+}
+
+/// Allowlists a principal with custom pricing, or removes it from the allowlist.
+///
+/// # Access Control
+/// - Caller must be a controller or hold the `PricingManager` role.
+///
+/// # Arguments
+/// - `args`: [`SetCustomPricingArgs`]
+///   - `principal`: **required** principal to allowlist.
+///   - `pricing`: optional [`SetPricingArgs`]; omitted fields keep the value the principal is
+///     currently charged. `None` removes the principal from the allowlist.
+///
+/// # Behaviour
+/// - The principal's creations, top-ups and auto top-ups are charged from its custom pricing.
+///
+/// # Returns
+/// - `SetCanisterResult::Ok(())` once the pricing is stored or removed.
+/// - `SetCanisterResult::Err(CreateCanisterError::InvalidPricing)` if the pricing is refused, as by
+///   `set_pricing`.
+#[update(guard = "caller_is_pricing_manager")]
+fn set_custom_pricing(args: SetCustomPricingArgs) -> SetCanisterResult {
+    discounts::set_custom_pricing(args)
+}
+
+/// Returns the allowlisted principals and their custom pricing.
+///
+/// # Access Control
+/// - Caller must be a controller or hold the `Auditor` role.
+#[query(guard = "caller_is_auditor")]
+fn list_custom_pricing() -> Vec<CustomPricing> {
+    discounts::list_custom_pricing()
+}
+
+/// Grants an admin role to a principal.
+///
+/// # Access Control
+/// - Caller must be a controller.
+///
+/// # Arguments
+/// - `args`: [`RoleArgs`]
+///   - `principal`: **required** principal granted the role.
+///   - `role`: **required** [`Role`]: `WasmManager` stores WASMs, `PricingManager` sets pricing and
+///     discounts, `Operator` runs operations, and `Auditor` reads the audit data.
+///
+/// # Behaviour
+/// - The grant is recorded as a `RoleGranted` event; granting a role already held changes nothing.
+///
+/// # Returns
+/// - `SetCanisterResult::Ok(())` once the principal holds the role.
+#[update(guard = "caller_is_controller")]
+fn grant_role(args: RoleArgs) -> SetCanisterResult {
+    roles::grant_role(args.principal, args.role);
+    SetCanisterResult::Ok()
+}
+
+/// Revokes an admin role from a principal.
+///
+/// # Access Control
+/// - Caller must be a controller.
+///
+/// # Behaviour
+/// - The revocation is recorded as a `RoleRevoked` event; revoking a role not held changes nothing.
+///
+/// # Returns
+/// - `SetCanisterResult::Ok(())` once the principal no longer holds the role.
+#[update(guard = "caller_is_controller")]
+fn revoke_role(args: RoleArgs) -> SetCanisterResult {
+    roles::revoke_role(args.principal, args.role);
+    SetCanisterResult::Ok()
+}
+
+/// Returns every principal holding an admin role, with its roles.
+///
+/// Controllers hold every role without being listed.
+///
+/// # Access Control
+/// - Caller must be a controller or hold the `Auditor` role.
+#[query(guard = "caller_is_auditor")]
+fn list_roles() -> Vec<RoleAssignment> {
+    roles::list_roles()
+}
+
+/// Pauses or resumes classes of operations in an emergency, without upgrading the factory.
+///
+/// # Access Control
+/// - Caller must be a controller or hold the `Operator` role.
+///
+/// # Arguments
+/// - `args`: [`SetPausedArgs`]; omitted flags keep their current value.
+///   - `creation`: pauses `create_icrc_ledger` and `create_icrc_index`.
+///   - `upgrades`: pauses the ledger upgrades of `set_symbol`, `set_name` and `set_index_canister`.
+///   - `wasm_updates`: pauses the replacement of the stored ledger and index WASMs.
+///
+/// # Behaviour
+/// - Paused operations are refused with `CreateCanisterError::Paused` before any payment is taken;
+///   paused WASM updates are rejected by their guard.
+/// - The flags are part of the configuration, returned by [`config`], and the change is recorded as
+///   a `ConfigUpdated` event.
+///
+/// # Returns
+/// - `SetCanisterResult::Ok(())` once the flags are stored.
+#[update(guard = "caller_is_operator")]
+fn set_paused(args: SetPausedArgs) -> SetCanisterResult {
+    pause::set_paused(args);
+    SetCanisterResult::Ok()
+}
+
+/// Sets the rate limits of creations and ledger upgrades.
+///
+/// # Access Control
+/// - Caller must be a controller or hold the `Operator` role.
+///
+/// # Arguments
+/// - `limits`: [`RateLimits`]; each limit allows `max_calls` calls within any `window_secs`
+///   seconds.
+///   - `per_principal`: optional limit of the calls of each principal.
+///   - `global`: optional limit of the calls of all principals together.
+///
+/// # Behaviour
+/// - `create_icrc_ledger`, `create_icrc_index`, `set_symbol`, `set_name` and `set_index_canister`
+///   share the limits, and calls over a limit fail with `CreateCanisterError::RateLimited` before
+///   any payment is taken. Refused calls, creations that are not paid for and failed upgrades are
+///   not counted.
+/// - The limits are part of the configuration, returned by [`config`], and the change is recorded
+///   as a `ConfigUpdated` event.
+///
+/// # Returns
+/// - `SetCanisterResult::Ok(())` once the limits are stored.
+/// - `SetCanisterResult::Err(CreateCanisterError::InvalidRateLimit)` if a limit allows no call or
+///   has an empty window.
+#[update(guard = "caller_is_operator")]
+fn set_rate_limits(limits: RateLimits) -> SetCanisterResult {
+    match rate_limit::set_rate_limits(limits) {
+        Ok(()) => SetCanisterResult::Ok(),
+        Err(err) => SetCanisterResult::Err(err),
+    }
+}
+
+/// Sets who may create canisters.
+///
+/// # Access Control
+/// - Caller must be a controller.
+///
+/// # Arguments
+/// - `access`: [`CreationAccess`]
+///   - `Open`: every principal may create canisters.
+///   - `AllowlistOnly`: only the principals on the creation allowlist may.
+///
+/// # Behaviour
+/// - Principals on the creation denylist are refused in both modes.
+/// - The mode is part of the configuration, returned by [`config`], and the change is recorded as a
+///   `ConfigUpdated` event.
+///
+/// # Returns
+/// - `SetCanisterResult::Ok(())` once the mode is stored.
+#[update(guard = "caller_is_controller")]
+fn set_creation_access(access: CreationAccess) -> SetCanisterResult {
+    access_list::set_creation_access(access);
+    SetCanisterResult::Ok()
+}
+
+/// Adds principals to the creation allowlist, or removes them from it.
+///
+/// # Access Control
+/// - Caller must be a controller.
+///
+/// # Arguments
+/// - `args`: [`SetAccessListArgs`]
+///   - `principals`: **required** principals to add or remove.
+///   - `listed`: **required** `true` to add the principals, `false` to remove them.
+///
+/// # Behaviour
+/// - The allowlist only applies while the creation access is `AllowlistOnly`.
+/// - The change is recorded as a `CreationAccessListUpdated` event.
+///
+/// # Returns
+/// - `SetCanisterResult::Ok(())` once the allowlist is updated.
+#[update(guard = "caller_is_controller")]
+fn set_creation_allowlist(args: SetAccessListArgs) -> SetCanisterResult {
+    access_list::set_access_list(AccessList::Allowlist, args);
+    SetCanisterResult::Ok()
+}
+
+/// Adds principals to the creation denylist, or removes them from it.
+///
+/// # Access Control
+/// - Caller must be a controller.
+///
+/// # Arguments
+/// - `args`: [`SetAccessListArgs`], as for [`set_creation_allowlist`].
+///
+/// # Behaviour
+/// - Denylisted principals may not create canisters, even if they are on the allowlist.
+/// - The change is recorded as a `CreationAccessListUpdated` event.
+///
+/// # Returns
+/// - `SetCanisterResult::Ok(())` once the denylist is updated.
+#[update(guard = "caller_is_controller")]
+fn set_creation_denylist(args: SetAccessListArgs) -> SetCanisterResult {
+    access_list::set_access_list(AccessList::Denylist, args);
+    SetCanisterResult::Ok()
+}
+
+/// Returns principals on the creation allowlist, sorted by principal.
+///
+/// # Access Control
+/// - Caller must be a controller or hold the `Auditor` role.
+///
+/// # Arguments
+/// - `offset`: Optional number of principals to skip. Defaults to 0.
+/// - `limit`: Optional maximum number of principals to return. Defaults to 50, and is capped at
+///   100.
+#[query(guard = "caller_is_auditor")]
+fn list_creation_allowlist(offset: Option<u64>, limit: Option<u64>) -> Vec<AccessListEntry> {
+    access_list::list_access_list(
+        AccessList::Allowlist,
+        offset.unwrap_or(0),
+        limit.unwrap_or(50),
+    )
+}
+
+/// Returns principals on the creation denylist, sorted by principal.
+///
+/// # Access Control
+/// - Caller must be a controller or hold the `Auditor` role.
+///
+/// # Arguments
+/// - `offset`, `limit`: as for [`list_creation_allowlist`].
+#[query(guard = "caller_is_auditor")]
+fn list_creation_denylist(offset: Option<u64>, limit: Option<u64>) -> Vec<AccessListEntry> {
+    access_list::list_access_list(
+        AccessList::Denylist,
+        offset.unwrap_or(0),
+        limit.unwrap_or(50),
+    )
+}
+
+/// Returns the admin roles granted to the caller.
+#[query(guard = "caller_is_not_anonymous")]
+fn my_roles() -> Vec<Role> {
+    roles::roles_of(ic_cdk::caller())
+}
+
 /// Creates a new ICRC ledger canister.
 ///
 /// # Access Control
@@ -184,30 +695,94 @@ async fn set_index_wasm_from_url(url: String) -> SetWasmResult {
 ///     - `transfer_fee`: optional fee (smallest unit)
 ///     - `decimals`: optional decimals
 ///     - `minting_account`: optional minting account
+///     - `settings`: optional `CanisterSettingsArgs` of the new canister
+///     - `logo`: optional token logo, a `data:` or `https:` URI of at most 8 KiB, listed in the
+///       token list
 ///   - Any omitted fields fall back to the ledger’s defaults.
-/// - `payment`: Optional [`PaymentType`]
-///   - If `None`, defaults to `PaymentType::AttachedCycles`.
+/// - `payment`: Optional [`FactoryPaymentType`]
+///   - If `None`, defaults to `FactoryPaymentType::AttachedCycles`.
+///   - `FactoryPaymentType::PrepaidBalance` draws the amount from the caller's prepaid balance.
+///   - `FactoryPaymentType::CallerPaysIcp` pulls the ICP equivalent, at the CMC rate, from the
+///     caller with ICRC-2 `transfer_from` on the ICP ledger.
+///   - Cycles attached beyond the amount charged are credited to the caller's prepaid balance.
+/// - `max_fee`: Optional highest fee, in cycles, the caller agrees to pay, after discounts.
+/// - `promo_code`: Optional promo code taken off the fee.
+///
+/// # Behaviour
+/// - The fee follows the caller's custom pricing if a controller allowlisted them.
+/// - A free creation left in the caller's quota is used first, and nothing is charged; the promo
+///   code is then not redeemed.
+/// - Discounts are consumed before the payment and handed back if it fails.
 ///
 /// # Returns
 /// - `CreateCanisterResult::Ok(Principal)` containing the newly created ledger canister ID.
-/// - `CreateCanisterResult::Err(CreateCanisterError)` if payment deduction fails or if canister
-///   creation / init-args encoding / WASM installation fails.
+/// - `CreateCanisterResult::Err(CreateCanisterError)` if the logo is invalid, creations are paused,
+///   the caller may not create canisters or is rate limited, the settings are out of bounds, the
+///   promo code is unknown, expired or fully redeemed, the fee exceeds `max_fee`, payment deduction
+///   fails or if canister creation / init-args encoding / WASM installation fails.
 #[update(guard = "caller_is_not_anonymous")]
 async fn create_icrc_ledger(
     args: CreateIcrcLedgerArgs,
-    payment: Option<PaymentType>,
+    payment: Option<FactoryPaymentType>,
+    max_fee: Option<u64>,
+    promo_code: Option<String>,
 ) -> CreateCanisterResult {
-    if let Err(err) = PAYMENT_GUARD
-        .deduct(
-            payment.unwrap_or(PaymentType::AttachedCycles),
-            SignerMethods::CreateIcrcLedger.fee(),
-        )
-        .await
-    {
-        return CreateCanisterResult::Err(CreateCanisterError::PaymentError(err));
+    // The logo is checked before anything is charged.
+    if let Some(Err(err)) = args.logo.as_deref().map(tokens::validate_logo) {
+        let result = CreateCanisterResult::Err(err);
+        metrics::record_creation(&result);
+        return result;
     }
 
-    generic::create_icrc_ledger(args).await
+    let result = match pay_for_creation(
+        SignerMethods::CreateIcrcLedger,
+        args.settings.as_ref(),
+        payment,
+        max_fee,
+        promo_code.as_deref(),
+    )
+    .await
+    {
+        Ok(event_payment) => generic::create_icrc_ledger(args, event_payment).await,
+        Err(err) => CreateCanisterResult::Err(err),
+    };
+
+    metrics::record_creation(&result);
+
+    result
+}
+
+/// Quotes the fee of a `create_icrc_ledger` call with the same arguments.
+///
+/// # Arguments
+/// - `args`: [`CreateIcrcLedgerArgs`], as they would be passed to `create_icrc_ledger`.
+/// - `promo_code`: Optional promo code, as it would be passed to `create_icrc_ledger`.
+///
+/// # Behaviour
+/// - The fee is computed exactly as `create_icrc_ledger` computes the amount it charges, including
+///   requested resource allocations, the service margin and the caller's discounts. No discount is
+///   consumed.
+/// - For ICRC-2 payment methods, the quote includes the allowance to grant the factory, which
+///   covers the ledger's transfer fee too.
+/// - ICP payments are quoted in e8s at the CMC conversion rate last read by the factory, and are
+///   left out until it has been read. The rate applied when charging is read again.
+///
+/// # Returns
+/// - `QuoteResult::Ok(FeeQuote)` with the fee in cycles and its amount for every supported payment
+///   method.
+/// - `QuoteResult::Err(CreateCanisterError)` if the settings are out of bounds, or the promo code
+///   is unknown, expired or fully redeemed.
+#[query]
+fn quote_create_icrc_ledger(args: CreateIcrcLedgerArgs, promo_code: Option<String>) -> QuoteResult {
+    match creation_charge(
+        ic_cdk::caller(),
+        SignerMethods::CreateIcrcLedger,
+        args.settings.as_ref(),
+        promo_code.as_deref(),
+    ) {
+        Ok(creation) => QuoteResult::Ok(payment::quote(creation.charged)),
+        Err(err) => QuoteResult::Err(err),
+    }
 }
 
 /// Creates a new ICRC index canister for an existing ledger.
@@ -218,44 +793,232 @@ async fn create_icrc_ledger(
 /// # Arguments
 /// - `args`: [`CreateIcrcIndexArgs`]
 ///   - `ledger_id`: **required** principal of the ledger to index.
-/// - `payment`: Optional [`PaymentType`]
-///   - If `None`, defaults to `PaymentType::AttachedCycles`.
+///   - `settings`: optional `CanisterSettingsArgs` of the new canister.
+/// - `payment`: Optional [`FactoryPaymentType`]
+///   - If `None`, defaults to `FactoryPaymentType::AttachedCycles`.
+///   - `FactoryPaymentType::PrepaidBalance` draws the amount from the caller's prepaid balance.
+///   - `FactoryPaymentType::CallerPaysIcp` pulls the ICP equivalent, at the CMC rate, from the
+///     caller with ICRC-2 `transfer_from` on the ICP ledger.
+///   - Cycles attached beyond the amount charged are credited to the caller's prepaid balance.
+/// - `max_fee`: Optional highest fee, in cycles, the caller agrees to pay, after discounts.
+/// - `promo_code`: Optional promo code taken off the fee.
+///
+/// # Behaviour
+/// - The fee follows the caller's custom pricing if a controller allowlisted them.
+/// - A free creation left in the caller's quota is used first, and nothing is charged; the promo
+///   code is then not redeemed.
+/// - Discounts are consumed before the payment and handed back if it fails.
 ///
 /// # Returns
 /// - `CreateCanisterResult::Ok(Principal)` containing the newly created index canister ID.
-/// - `CreateCanisterResult::Err(CreateCanisterError)` if payment deduction fails or if canister
-///   creation / init-args encoding / WASM installation fails.
+/// - `CreateCanisterResult::Err(CreateCanisterError)` if creations are paused, the caller may not
+///   create canisters or is rate limited, the settings are out of bounds, the promo code is
+///   unknown, expired or fully redeemed, the fee exceeds `max_fee`, payment deduction fails or if
+///   canister creation / init-args encoding / WASM installation fails.
 #[update(guard = "caller_is_not_anonymous")]
 async fn create_icrc_index(
     args: CreateIcrcIndexArgs,
-    payment: Option<PaymentType>,
+    payment: Option<FactoryPaymentType>,
+    max_fee: Option<u64>,
+    promo_code: Option<String>,
 ) -> CreateCanisterResult {
-    if let Err(err) = PAYMENT_GUARD
-        .deduct(
-            payment.unwrap_or(PaymentType::AttachedCycles),
-            SignerMethods::CreateIcrcIndex.fee(),
-        )
-        .await
+    let result = match pay_for_creation(
+        SignerMethods::CreateIcrcIndex,
+        args.settings.as_ref(),
+        payment,
+        max_fee,
+        promo_code.as_deref(),
+    )
+    .await
     {
-        return CreateCanisterResult::Err(CreateCanisterError::PaymentError(err));
+        Ok(event_payment) => generic::create_icrc_index(args, event_payment).await,
+        Err(err) => CreateCanisterResult::Err(err),
+    };
+
+    metrics::record_creation(&result);
+
+    result
+}
+
+/// Deposits cycles into a canister created by the factory.
+///
+/// # Access Control
+/// - Caller must not be anonymous. Anyone may top up any registered canister.
+///
+/// # Arguments
+/// - `args`: [`TopUpCanisterArgs`]
+///   - `canister_id`: **required** principal of a canister in the factory registry.
+///   - `cycles`: **required** cycles to deposit, at least 100 billion.
+/// - `payment`: Optional [`FactoryPaymentType`]
+///   - If `None`, defaults to `FactoryPaymentType::AttachedCycles`.
+///   - `FactoryPaymentType::PrepaidBalance` draws the amount from the caller's prepaid balance.
+///   - `FactoryPaymentType::CallerPaysIcp` pulls the ICP equivalent, at the CMC rate, from the
+///     caller with ICRC-2 `transfer_from` on the ICP ledger.
+///   - Cycles attached beyond the amount charged are credited to the caller's prepaid balance.
+/// - `max_fee`: Optional highest service fee, in cycles, the caller agrees to pay; the deposited
+///   cycles are not included.
+///
+/// # Behaviour
+/// - The caller pays `cycles` plus the `SignerMethods::TopUpCanister` service fee, from their
+///   custom pricing if a controller allowlisted them.
+/// - The cycles are forwarded to the canister with the management canister's `deposit_cycles`.
+/// - If the deposit fails, the cycles and the service fee are credited to the caller's prepaid
+///   balance.
+///
+/// # Returns
+/// - `SetCanisterResult::Ok(())` once the cycles have been deposited.
+/// - `SetCanisterResult::Err(CreateCanisterError)` if the amount is too small, the canister is not
+///   registered, the fee exceeds `max_fee`, payment deduction fails or the deposit fails.
+#[update(guard = "caller_is_not_anonymous")]
+async fn top_up_canister(
+    args: TopUpCanisterArgs,
+    payment: Option<FactoryPaymentType>,
+    max_fee: Option<u64>,
+) -> SetCanisterResult {
+    if let Err(err) = top_up::validate_top_up(&args) {
+        return SetCanisterResult::Err(err);
+    }
+
+    let fee = SignerMethods::TopUpCanister.fee_for(ic_cdk::caller(), None);
+
+    if let Err(err) = check_max_fee(fee, max_fee) {
+        return SetCanisterResult::Err(err);
+    }
+
+    let amount = fee.saturating_add(args.cycles);
+    let event_payment = EventPayment {
+        method: payment_method(payment.as_ref()),
+        cycles: amount,
+        discount: None,
+    };
+
+    if let Err(err) = charge(payment, amount).await {
+        return SetCanisterResult::Err(err);
     }
 
-    generic::create_icrc_index(args).await
+    top_up::top_up_canister(args, event_payment).await
 }
 
-/// Associates an index canister with a ledger by upgrading the ledger configuration.
+/// Adds cycles to the caller's prepaid balance, which funds their auto top-ups.
 ///
 /// # Access Control
 /// - Caller must not be anonymous.
 ///
 /// # Arguments
+/// - `args`: [`FundPrepaidBalanceArgs`]
+///   - `cycles`: **required** cycles to add, at least 100 billion.
+/// - `payment`: Optional [`PaymentType`]
+///   - If `None`, defaults to `PaymentType::AttachedCycles`.
+///   - Cycles attached beyond the amount charged are credited to the caller's prepaid balance.
+///
+/// # Returns
+/// - `SetCanisterResult::Ok(())` once the balance has been credited.
+/// - `SetCanisterResult::Err(CreateCanisterError)` if the amount is too small or payment deduction
+///   fails.
+#[update(guard = "caller_is_not_anonymous")]
+async fn fund_prepaid_balance(
+    args: FundPrepaidBalanceArgs,
+    payment: Option<PaymentType>,
+) -> SetCanisterResult {
+    if args.cycles < top_up::MIN_TOP_UP_CYCLES {
+        return SetCanisterResult::Err(CreateCanisterError::InvalidTopUpAmount);
+    }
+
+    let payment = payment.unwrap_or(PaymentType::AttachedCycles);
+    let event_payment = EventPayment {
+        method: payment_method(Some(&payment.clone().into())),
+        cycles: args.cycles,
+        discount: None,
+    };
+
+    if let Err(err) = charge_with_guard(payment, args.cycles).await {
+        return SetCanisterResult::Err(err);
+    }
+
+    prepaid::fund_prepaid_balance(args, event_payment)
+}
+
+/// Withdraws cycles from the caller's prepaid balance to a cycles ledger account.
+///
+/// # Access Control
+/// - Caller must not be anonymous.
+///
+/// # Arguments
+/// - `args`: [`WithdrawPrepaidBalanceArgs`]
+///   - `cycles`: **required** cycles to withdraw.
+///   - `to`: **required** cycles ledger account to credit.
+///
+/// # Behaviour
+/// - The cycles are deposited with the cycles ledger's `deposit`; if that fails, the prepaid
+///   balance is left unchanged.
+///
+/// # Returns
+/// - `SetCanisterResult::Ok(())` once the cycles have been deposited.
+/// - `SetCanisterResult::Err(CreateCanisterError)` if the balance is too low or the deposit fails.
+#[update(guard = "caller_is_not_anonymous")]
+async fn withdraw_prepaid_balance(args: WithdrawPrepaidBalanceArgs) -> SetCanisterResult {
+    prepaid::withdraw_prepaid_balance(args).await
+}
+
+/// Subscribes a canister to automatic top-ups funded by the caller's prepaid balance.
+///
+/// # Access Control
+/// - Caller must own the canister, and the factory must still control it.
+///
+/// # Arguments
+/// - `args`: [`SetAutoTopUpArgs`]
+///   - `canister_id`: **required** principal of the canister to watch.
+///   - `threshold`: **required** balance, in cycles, below which the canister is topped up.
+///   - `refill`: **required** cycles deposited on each top-up, at least 100 billion.
+///
+/// # Behaviour
+/// - A timer polls `canister_status` of subscribed canisters in batches; for ledgers, the archives
+///   they spawned are checked too.
+/// - Each top-up costs `refill` plus the `SignerMethods::TopUpCanister` service fee, taken from the
+///   prepaid balance, and is recorded as an event.
+/// - When the prepaid balance runs dry, a `PrepaidBalanceExhausted` event is recorded.
+/// - An existing subscription for the canister is replaced.
+///
+/// # Returns
+/// - `SetCanisterResult::Ok(())` once the subscription is stored.
+/// - `SetCanisterResult::Err(CreateCanisterError)` if the refill is too small or the caller does
+///   not own the canister.
+#[update(guard = "caller_is_not_anonymous")]
+fn set_auto_top_up(args: SetAutoTopUpArgs) -> SetCanisterResult {
+    auto_top_up::set_auto_top_up(args)
+}
+
+/// Cancels the auto top-up subscription of a canister.
+///
+/// # Access Control
+/// - Caller must own the canister.
+///
+/// # Arguments
+/// - `canister_id`: **required** principal of the subscribed canister.
+///
+/// # Returns
+/// - `SetCanisterResult::Ok(())` once the subscription is removed.
+/// - `SetCanisterResult::Err(CreateCanisterError)` if the caller does not own the canister or it
+///   has no subscription.
+#[update(guard = "caller_is_not_anonymous")]
+fn remove_auto_top_up(canister_id: Principal) -> SetCanisterResult {
+    auto_top_up::remove_auto_top_up(canister_id)
+}
+
+/// Associates an index canister with a ledger by upgrading the ledger configuration.
+///
+/// # Access Control
+/// - Caller must own the ledger.
+///
+/// # Arguments
 /// - `args`: [`SetIndexCanisterArgs`]
 ///   - `ledger_id`: **required** principal of the ledger to update.
 ///   - `index_id`: **required** principal of the index canister to associate.
 ///
 /// # Returns
 /// - `SetCanisterResult::Ok(())` on success.
-/// - `SetCanisterResult::Err(CreateCanisterError)` if the ledger upgrade fails.
+/// - `SetCanisterResult::Err(CreateCanisterError)` if upgrades are paused, the caller is rate
+///   limited or does not own the ledger, the canister is not a ledger, or the ledger upgrade fails.
 #[update(guard = "caller_is_not_anonymous")]
 async fn set_index_canister(args: SetIndexCanisterArgs) -> SetCanisterResult {
     let upgrade_arg = LedgerArgs::Upgrade(Some(UpgradeArgs {
@@ -263,17 +1026,29 @@ async fn set_index_canister(args: SetIndexCanisterArgs) -> SetCanisterResult {
         ..Default::default()
     }));
 
-    upgrade_ledger_canister(UpgradeLedgerCanisterArgs {
+    let result = upgrade_ledger_canister(UpgradeLedgerCanisterArgs {
         ledger_id: args.ledger_id,
         args: upgrade_arg,
     })
-    .await
+    .await;
+
+    if result == SetCanisterResult::Ok() {
+        tokens::update_token_metadata(args.ledger_id, |metadata| {
+            metadata.index_id = Some(args.index_id);
+        });
+        record_event(EventKind::IndexCanisterSet {
+            ledger_id: args.ledger_id,
+            index_id: args.index_id,
+        });
+    }
+
+    result
 }
 
 /// Updates a ledger’s token symbol by upgrading the ledger configuration.
 ///
 /// # Access Control
-/// - Caller must not be anonymous.
+/// - Caller must own the ledger.
 ///
 /// # Arguments
 /// - `args`: [`SetSymbolArgs`]
@@ -282,25 +1057,38 @@ async fn set_index_canister(args: SetIndexCanisterArgs) -> SetCanisterResult {
 ///
 /// # Returns
 /// - `SetCanisterResult::Ok(())` on success.
-/// - `SetCanisterResult::Err(CreateCanisterError)` if the ledger upgrade fails.
+/// - `SetCanisterResult::Err(CreateCanisterError)` if upgrades are paused, the caller is rate
+///   limited or does not own the ledger, the canister is not a ledger, or the ledger upgrade fails.
 #[update(guard = "caller_is_not_anonymous")]
 async fn set_symbol(args: SetSymbolArgs) -> SetCanisterResult {
     let upgrade_arg = LedgerArgs::Upgrade(Some(UpgradeArgs {
-        token_symbol: Some(args.symbol),
+        token_symbol: Some(args.symbol.clone()),
         ..Default::default()
     }));
 
-    upgrade_ledger_canister(UpgradeLedgerCanisterArgs {
+    let result = upgrade_ledger_canister(UpgradeLedgerCanisterArgs {
         ledger_id: args.ledger_id,
         args: upgrade_arg,
     })
-    .await
+    .await;
+
+    if result == SetCanisterResult::Ok() {
+        tokens::update_token_metadata(args.ledger_id, |metadata| {
+            metadata.symbol.clone_from(&args.symbol);
+        });
+        record_event(EventKind::SymbolSet {
+            ledger_id: args.ledger_id,
+            symbol: args.symbol,
+        });
+    }
+
+    result
 }
 
 /// Updates a ledger’s token name by upgrading the ledger configuration.
 ///
 /// # Access Control
-/// - Caller must not be anonymous.
+/// - Caller must own the ledger.
 ///
 /// # Arguments
 /// - `args`: [`SetNameArgs`]
@@ -309,19 +1097,252 @@ async fn set_symbol(args: SetSymbolArgs) -> SetCanisterResult {
 ///
 /// # Returns
 /// - `SetCanisterResult::Ok(())` on success.
-/// - `SetCanisterResult::Err(CreateCanisterError)` if the ledger upgrade fails.
+/// - `SetCanisterResult::Err(CreateCanisterError)` if upgrades are paused, the caller is rate
+///   limited or does not own the ledger, the canister is not a ledger, or the ledger upgrade fails.
 #[update(guard = "caller_is_not_anonymous")]
 async fn set_name(args: SetNameArgs) -> SetCanisterResult {
     let upgrade_arg = LedgerArgs::Upgrade(Some(UpgradeArgs {
-        token_name: Some(args.name),
+        token_name: Some(args.name.clone()),
         ..Default::default()
     }));
 
-    upgrade_ledger_canister(UpgradeLedgerCanisterArgs {
+    let result = upgrade_ledger_canister(UpgradeLedgerCanisterArgs {
         ledger_id: args.ledger_id,
         args: upgrade_arg,
     })
-    .await
+    .await;
+
+    if result == SetCanisterResult::Ok() {
+        tokens::update_token_metadata(args.ledger_id, |metadata| {
+            metadata.name.clone_from(&args.name);
+        });
+        record_event(EventKind::NameSet {
+            ledger_id: args.ledger_id,
+            name: args.name,
+        });
+    }
+
+    result
+}
+
+/// Proposes a new owner for a canister created by the factory.
+///
+/// The transfer only completes once the proposed owner calls [`accept_ownership_transfer`].
+///
+/// # Access Control
+/// - Caller must own the canister.
+///
+/// # Arguments
+/// - `args`: [`ProposeOwnershipTransferArgs`]
+///   - `canister_id`: **required** principal of the canister to transfer.
+///   - `new_owner`: optional principal of the proposed owner; `None` cancels a pending proposal.
+///
+/// # Returns
+/// - `SetCanisterResult::Ok(())` once the proposal is stored.
+/// - `SetCanisterResult::Err(CreateCanisterError)` if the caller does not own the canister or the
+///   proposed owner is invalid.
+#[update(guard = "caller_is_not_anonymous")]
+fn propose_ownership_transfer(args: ProposeOwnershipTransferArgs) -> SetCanisterResult {
+    ownership::propose_ownership_transfer(args)
+}
+
+/// Accepts a pending ownership transfer of a canister created by the factory.
+///
+/// # Access Control
+/// - Caller must be the proposed owner.
+///
+/// # Arguments
+/// - `args`: [`AcceptOwnershipTransferArgs`]
+///   - `canister_id`: **required** principal of the canister to take over.
+///
+/// # Behaviour
+/// - Swaps the previous owner for the caller in the canister's controllers.
+/// - Moves the registry entry from the previous owner to the caller.
+/// - Records an `OwnershipTransferred` audit event.
+///
+/// # Returns
+/// - `SetCanisterResult::Ok(())` once ownership has moved.
+/// - `SetCanisterResult::Err(CreateCanisterError)` if no transfer to the caller is pending, the
+///   canister has been deleted or the controllers could not be updated.
+#[update(guard = "caller_is_not_anonymous")]
+async fn accept_ownership_transfer(args: AcceptOwnershipTransferArgs) -> SetCanisterResult {
+    ownership::accept_ownership_transfer(args).await
+}
+
+/// Removes the factory from the controllers of a canister it created.
+///
+/// # Access Control
+/// - Caller must own the canister.
+///
+/// # Arguments
+/// - `canister_id`: **required** principal of the canister to release.
+///
+/// # Behaviour
+/// - For ledgers, existing archives are handed over to the owner and the ledger is upgraded so that
+///   `change_archive_options.controller_id` points to the owner. The upgrade keeps the ledger's
+///   code, and is refused with `CreateCanisterError::ModuleHashMismatch` if the ledger does not run
+///   the stored ledger WASM.
+/// - Once the archives are handed over, the registry entry records it in `archive_controller`; if a
+///   later step fails, calling again resumes from there.
+/// - The factory is replaced by the owner in the canister's controllers.
+/// - The registry entry is marked as sovereign; later factory-mediated management calls are refused
+///   with `CreateCanisterError::Sovereign`.
+///
+/// # Returns
+/// - `SetCanisterResult::Ok(())` once the factory no longer controls the canister.
+/// - `SetCanisterResult::Err(CreateCanisterError)` if the caller does not own the canister, the
+///   ledger does not run the stored ledger WASM or any management call fails.
+#[update(guard = "caller_is_not_anonymous")]
+async fn renounce_factory_control(canister_id: Principal) -> SetCanisterResult {
+    controllers::renounce_factory_control(canister_id).await
+}
+
+/// Makes a ledger immutable by handing its controllers over to a blackhole.
+///
+/// # Access Control
+/// - Caller must own the ledger.
+///
+/// # Arguments
+/// - `args`: [`MakeLedgerImmutableArgs`]
+///   - `ledger_id`: **required** principal of the ledger to make immutable.
+///   - `confirm_ledger_id`: **required** must repeat `ledger_id`.
+///   - `acknowledge_irreversible`: **required** must be `true`.
+///
+/// # Behaviour
+/// - The module hash of the ledger is read before anything else.
+/// - Archives are handed over to the configured blackhole canister, or to the ledger itself if none
+///   is configured, by an upgrade that keeps the ledger's code; it is refused with
+///   `CreateCanisterError::ModuleHashMismatch` if the ledger does not run the stored ledger WASM.
+/// - Once the archives are handed over, the registry entry records it in `archive_controller`; if a
+///   later step fails, calling again resumes from there.
+/// - The ledger's controllers are set to the configured blackhole canister, or to an empty list.
+/// - The module hash read first is recorded in the registry as the final one, and later upgrade
+///   attempts are refused with `CreateCanisterError::Immutable`.
+///
+/// # Returns
+/// - `SetCanisterResult::Ok(())` once the ledger is immutable.
+/// - `SetCanisterResult::Err(CreateCanisterError)` if the confirmation does not match, the caller
+///   does not own the ledger, the ledger does not run the stored ledger WASM or any management call
+///   fails.
+#[update(guard = "caller_is_not_anonymous")]
+async fn make_ledger_immutable(args: MakeLedgerImmutableArgs) -> SetCanisterResult {
+    controllers::make_ledger_immutable(args).await
+}
+
+/// Adds a controller to a canister created by the factory.
+///
+/// # Access Control
+/// - Caller must own the canister.
+///
+/// # Arguments
+/// - `args`: [`AddControllerArgs`]
+///   - `canister_id`: **required** principal of the canister.
+///   - `controller`: **required** principal to add; must be neither anonymous nor the factory.
+///
+/// # Behaviour
+/// - The controller is added to the live controller list, keeping every existing controller.
+/// - The registry's view of the canister's controllers is refreshed.
+///
+/// # Returns
+/// - `SetCanisterResult::Ok(())` once the controller has been added.
+/// - `SetCanisterResult::Err(CreateCanisterError)` if the controller is invalid, the caller does
+///   not own the canister or any management call fails.
+#[update(guard = "caller_is_not_anonymous")]
+async fn add_controller(args: AddControllerArgs) -> SetCanisterResult {
+    controllers::add_controller(args).await
+}
+
+/// Removes a controller from a canister created by the factory.
+///
+/// # Access Control
+/// - Caller must own the canister.
+///
+/// # Arguments
+/// - `args`: [`RemoveControllerArgs`]
+///   - `canister_id`: **required** principal of the canister.
+///   - `controller`: **required** principal to remove; the factory leaves through
+///     [`renounce_factory_control`] instead.
+///
+/// # Behaviour
+/// - The controller is removed from the live controller list.
+/// - Removing the last controller besides the factory is refused with
+///   `CreateCanisterError::LastController`.
+/// - The registry's view of the canister's controllers is refreshed.
+///
+/// # Returns
+/// - `SetCanisterResult::Ok(())` once the controller has been removed.
+/// - `SetCanisterResult::Err(CreateCanisterError)` if the controller is invalid or the last one,
+///   the caller does not own the canister or any management call fails.
+#[update(guard = "caller_is_not_anonymous")]
+async fn remove_controller(args: RemoveControllerArgs) -> SetCanisterResult {
+    controllers::remove_controller(args).await
+}
+
+/// Stops a canister created by the factory.
+///
+/// # Access Control
+/// - Caller must own the canister.
+///
+/// # Arguments
+/// - `canister_id`: **required** principal of the canister.
+///
+/// # Returns
+/// - `SetCanisterResult::Ok(())` once the canister is stopped.
+/// - `SetCanisterResult::Err(CreateCanisterError)` if the caller does not own the canister, the
+///   factory no longer controls it or the management call fails.
+#[update(guard = "caller_is_not_anonymous")]
+async fn stop_canister(canister_id: Principal) -> SetCanisterResult {
+    lifecycle::stop_canister(canister_id).await
+}
+
+/// Starts a canister created by the factory.
+///
+/// # Access Control
+/// - Caller must own the canister.
+///
+/// # Arguments
+/// - `canister_id`: **required** principal of the canister.
+///
+/// # Returns
+/// - `SetCanisterResult::Ok(())` once the canister is running.
+/// - `SetCanisterResult::Err(CreateCanisterError)` if the caller does not own the canister, the
+///   factory no longer controls it or the management call fails.
+#[update(guard = "caller_is_not_anonymous")]
+async fn start_canister(canister_id: Principal) -> SetCanisterResult {
+    lifecycle::start_canister(canister_id).await
+}
+
+/// Deletes a canister created by the factory, recovering its remaining cycles.
+///
+/// # Access Control
+/// - Caller must own the canister.
+///
+/// # Arguments
+/// - `args`: [`DeleteCanisterArgs`]
+///   - `canister_id`: **required** principal of the canister.
+///   - `recover_cycles_to`: *optional* cycles ledger account credited with the recovered cycles; if
+///     not provided, they are kept by the factory.
+///
+/// # Behaviour
+/// - Any pending ownership transfer is withdrawn.
+/// - The canister is reinstalled with a cycles drainer that sends its balance, minus a 10B cycles
+///   reserve paying for the transfer, back to the factory. It is then stopped and deleted.
+/// - For a ledger, its archives are deleted the same way; an archive that cannot be deleted is left
+///   in place.
+/// - Once the drainer is installed, the registry entry is kept and marked as deleted, and any auto
+///   top-up is removed. Should stopping or deleting the canister then fail, it is left in place
+///   running the drainer.
+/// - Should the deposit to the cycles ledger fail, the recovered cycles are credited to the
+///   caller's prepaid balance instead.
+///
+/// # Returns
+/// - `DeleteCanisterResult::Ok(Nat)` with the recovered cycles.
+/// - `DeleteCanisterResult::Err(CreateCanisterError)` if the caller does not own the canister, the
+///   factory no longer controls it, the drainer could not be installed or the recovered cycles
+///   could not be deposited.
+#[update(guard = "caller_is_not_anonymous")]
+async fn delete_canister(args: DeleteCanisterArgs) -> DeleteCanisterResult {
+    lifecycle::delete_canister(args).await
 }
 
 #[query(guard = "caller_is_not_anonymous")]
@@ -330,34 +1351,272 @@ fn list_user_canisters() -> Vec<UserCanister> {
     read_state(|s| s.user_canister.get(&stored_principal).unwrap_or_default().0)
 }
 
+/// Returns the caller's registry entries, with a certificate.
+///
+/// # Access Control
+/// - Caller must not be anonymous.
+///
+/// # Returns
+/// - The entries of [`list_user_canisters`], the certificate of the factory, and a witness
+///   revealing every entry of the caller under `registry/<caller>`, or proving that there is none.
+///
+/// # Panics
+/// - If called as an update, since the IC only provides certificates to queries.
+#[query(guard = "caller_is_not_anonymous")]
+fn list_user_canisters_certified() -> CertifiedUserCanisters {
+    let caller = ic_cdk::caller();
+    let canisters = read_state(|s| s.user_canister.get(&StoredPrincipal(caller)))
+        .unwrap_or_default()
+        .0;
+    let (certificate, witness) = certificate_with_witness(&certification::owner_witness(caller));
+
+    CertifiedUserCanisters {
+        canisters,
+        certificate,
+        witness,
+    }
+}
+
+/// Returns the status of a canister created by the factory.
+///
+/// # Access Control
+/// - Caller must own the canister.
+///
+/// # Arguments
+/// - `canister_id`: **required** principal of the canister.
+///
+/// # Behaviour
+/// - The factory calls the management canister's `canister_status` as a controller of the canister
+///   and trims the response to cycles, memory, module hash, controllers and run status.
+///
+/// # Returns
+/// - `CanisterStatusResult::Ok(CanisterStatus)` with the current status.
+/// - `CanisterStatusResult::Err(CreateCanisterError)` if the caller does not own the canister, the
+///   factory no longer controls it or the management call fails.
+#[update(guard = "caller_is_not_anonymous")]
+async fn canister_status(canister_id: Principal) -> CanisterStatusResult {
+    status::canister_status(canister_id).await
+}
+
+/// Returns the status of every canister owned by the caller, e.g. to refresh a dashboard.
+///
+/// # Access Control
+/// - Caller must not be anonymous; only the caller's own canisters are reported.
+///
+/// # Behaviour
+/// - The statuses are read in batches of 50 concurrent management calls.
+///
+/// # Returns
+/// - One `CanisterStatusEntry` per canister in the caller's registry, in registry order. Canisters
+///   the factory no longer controls are reported with `CreateCanisterError::Sovereign`,
+///   `CreateCanisterError::Immutable` or `CreateCanisterError::Deleted`.
+#[update(guard = "caller_is_not_anonymous")]
+async fn canister_statuses() -> Vec<CanisterStatusEntry> {
+    status::canister_statuses().await
+}
+
+/// Returns the caller's prepaid cycles balance.
+///
+/// The balance funds auto top-ups and paid calls made with `FactoryPaymentType::PrepaidBalance`,
+/// and collects cycles attached to paid calls beyond their fee.
+#[query(guard = "caller_is_not_anonymous")]
+fn prepaid_balance() -> u64 {
+    prepaid::prepaid_balance(ic_cdk::caller())
+}
+
+/// Returns the caller's prepaid balance journal, oldest first.
+///
+/// # Arguments
+/// - `offset`: Optional number of the caller's entries to skip. Defaults to 0.
+/// - `limit`: Optional maximum number of entries to return. Defaults to 50, and is capped at 100.
+///
+/// # Returns
+/// - Every credit and debit of the caller's prepaid balance, with the balance it left and its
+///   reason.
+#[query(guard = "caller_is_not_anonymous")]
+fn prepaid_journal(offset: Option<u64>, limit: Option<u64>) -> Vec<PrepaidJournalEntry> {
+    prepaid::prepaid_journal(ic_cdk::caller(), offset.unwrap_or(0), limit.unwrap_or(50))
+}
+
+/// Returns the auto top-up subscriptions funded by the caller.
+#[query(guard = "caller_is_not_anonymous")]
+fn list_auto_top_ups() -> Vec<AutoTopUp> {
+    auto_top_up::list_auto_top_ups(ic_cdk::caller())
+}
+
+/// Returns recorded events, oldest first.
+///
+/// Every factory action is recorded here, with its caller, and with its payment and outcome if it
+/// is paid for: creations, WASM replacements, renames, ownership changes, top-ups, auto top-ups,
+/// exhausted prepaid balances, pricing and configuration changes, …
+///
+/// # Arguments
+/// - `offset`: Optional number of matching events to skip. Defaults to 0.
+/// - `limit`: Optional maximum number of events to return. Defaults to 50, and is capped at 100.
+/// - `filter`: Optional [`EventFilter`]; only events meeting all its criteria are returned.
+///   - `principal`: events called by the principal, or about it, e.g. as owner or controller.
+///   - `canister_id`: events about the canister.
+///   - `event_type`: events of the type, e.g. `"IcrcLedgerCreated"`.
+#[query(guard = "caller_is_not_anonymous")]
+fn list_events(offset: Option<u64>, limit: Option<u64>, filter: Option<EventFilter>) -> Vec<Event> {
+    events::list_events(
+        &filter.unwrap_or_default(),
+        offset.unwrap_or(0),
+        limit.unwrap_or(50),
+    )
+}
+
+/// Returns log entries, oldest first.
+///
+/// Every step of canister creations and ledger upgrades is logged, with the correlation ID of its
+/// flow. Only the latest 10,000 entries are kept.
+///
+/// # Access Control
+/// - Caller must be a controller or hold the `Auditor` role.
+///
+/// # Arguments
+/// - `offset`: Optional number of matching entries to skip. Defaults to 0.
+/// - `limit`: Optional maximum number of entries to return. Defaults to 50, and is capped at 100.
+/// - `filter`: Optional [`LogFilter`]; only entries meeting all its criteria are returned.
+///   - `min_level`: entries of the level or a more severe one.
+///   - `from`, `to`: entries recorded within the time range, in nanoseconds since the epoch.
+///   - `canister_id`: entries about the canister.
+///   - `correlation_id`: entries of the flow.
+#[query(guard = "caller_is_auditor")]
+fn list_logs(offset: Option<u64>, limit: Option<u64>, filter: Option<LogFilter>) -> Vec<LogEntry> {
+    logging::list_logs(
+        &filter.unwrap_or_default(),
+        offset.unwrap_or(0),
+        limit.unwrap_or(50),
+    )
+}
+
+/// Returns the log entries about a canister created by the factory, oldest first.
+///
+/// # Access Control
+/// - Caller must own the canister, including a deleted one.
+///
+/// # Arguments
+/// - `canister_id`: **required** principal of the canister.
+/// - `offset`, `limit` and `filter`: as for [`list_logs`]; the `canister_id` of the filter is
+///   ignored.
+///
+/// # Returns
+/// - `ListLogsResult::Ok(Vec<LogEntry>)` with the matching entries.
+/// - `ListLogsResult::Err(CreateCanisterError)` if the caller does not own the canister.
+#[query(guard = "caller_is_not_anonymous")]
+fn list_canister_logs(
+    canister_id: Principal,
+    offset: Option<u64>,
+    limit: Option<u64>,
+    filter: Option<LogFilter>,
+) -> ListLogsResult {
+    match logging::list_canister_logs(
+        ic_cdk::caller(),
+        canister_id,
+        filter.unwrap_or_default(),
+        offset.unwrap_or(0),
+        limit.unwrap_or(50),
+    ) {
+        Ok(entries) => ListLogsResult::Ok(entries),
+        Err(err) => ListLogsResult::Err(err),
+    }
+}
+
+/// Returns ICRC-3 blocks of the event log, one per recorded event.
+///
+/// # Arguments
+/// - `args`: Ranges of block indices to return, each with a `start` and a `length`.
+///
+/// # Returns
+/// - The blocks in the ranges, up to 100 per call, and the length of the log. The factory never
+///   archives blocks.
 #[query]
-fn list_all_canisters_paginated(offset: Option<u64>, limit: Option<u64>) -> Vec<UserCanister> {
-    let offset = offset.unwrap_or(0);
-    let limit = limit.unwrap_or(50);
+fn icrc3_get_blocks(args: Vec<GetBlocksRequest>) -> GetBlocksResult {
+    icrc3::get_blocks(args)
+}
 
-    read_state(|s| {
-        let mut out = Vec::new();
-        let mut skipped = 0u64;
+/// Returns the certificate of the last block index and hash of the ICRC-3 block log.
+///
+/// # Returns
+/// - `None` while no event has been recorded.
+#[query]
+fn icrc3_get_tip_certificate() -> Option<ICRC3DataCertificate> {
+    icrc3::get_tip_certificate()
+}
 
-        for candid_vec in s.user_canister.values() {
-            let Candid(canisters) = candid_vec;
+/// Returns the archives of the ICRC-3 block log; always empty, as the factory keeps every block.
+#[query]
+fn icrc3_get_archives(_args: GetArchivesArgs) -> Vec<ICRC3ArchiveInfo> {
+    icrc3::get_archives()
+}
 
-            for canister in canisters {
-                if skipped < offset {
-                    skipped += 1;
-                    continue;
-                }
+/// Returns the block types of the ICRC-3 block log, one per `EventKind`.
+///
+/// # Returns
+/// - `factory_` followed by each kind in snake case, e.g. `factory_icrc_ledger_created`, with the
+///   URL of the block schema.
+///
+/// # Block Schema
+/// Every block is a `Map` of `btype`, `ts` and, but for the first, `phash`, along with a `tx` map
+/// of:
+/// - `caller`: the principal of the caller, as a `Blob`;
+/// - the fields of the `EventKind`, e.g. `canister_id`, `symbol` and `name` for
+///   `factory_icrc_ledger_created`;
+/// - `payment`: for paid actions, a `Map` of the `method`, the `cycles` charged and any `discount`;
+/// - `outcome`: for paid actions, a `Map` whose `type` is `Succeeded` or `Failed`, the latter with
+///   a `reason`.
+///
+/// Principals and hashes are `Blob`s, accounts `Array`s of their owner and subaccount `Blob`s,
+/// numbers `Nat`s and flags `Nat`s of 0 or 1, strings `Text`s, and records `Map`s. Enums are the
+/// `Text` of their variant, or a `Map` of their fields with the variant as `type` when they carry
+/// data. Fields that are `None` are left out.
+#[query]
+fn icrc3_supported_block_types() -> Vec<SupportedBlockType> {
+    icrc3::supported_block_types()
+}
 
-                if out.len() as u64 == limit {
-                    return out;
-                }
+#[query]
+fn list_all_canisters_paginated(offset: Option<u64>, limit: Option<u64>) -> Vec<UserCanister> {
+    read_state(|s| list_canisters_page(&s.user_canister, offset.unwrap_or(0), limit.unwrap_or(50)))
+        .into_iter()
+        .map(|(_, canister)| canister)
+        .collect()
+}
 
-                out.push(canister);
-            }
-        }
+/// Returns a page of the registry entries of all owners, with a certificate.
+///
+/// # Arguments
+/// - `offset`: optional number of entries to skip; defaults to 0.
+/// - `limit`: optional maximum number of entries to return; defaults to 50.
+///
+/// # Returns
+/// - The entries of [`list_all_canisters_paginated`], the certificate of the factory, and a witness
+///   revealing every entry at `registry/<owner>/<canister ID>`.
+///
+/// # Panics
+/// - If called as an update, since the IC only provides certificates to queries.
+#[query]
+fn list_all_canisters_paginated_certified(
+    offset: Option<u64>,
+    limit: Option<u64>,
+) -> CertifiedUserCanisters {
+    let page = read_state(|s| {
+        list_canisters_page(&s.user_canister, offset.unwrap_or(0), limit.unwrap_or(50))
+    });
 
-        out
-    })
+    let entries: Vec<(Principal, Principal)> = page
+        .iter()
+        .map(|(owner, canister)| (*owner, canister.canister_id))
+        .collect();
+    let (certificate, witness) =
+        certificate_with_witness(&certification::canisters_witness(&entries));
+
+    CertifiedUserCanisters {
+        canisters: page.into_iter().map(|(_, canister)| canister).collect(),
+        certificate,
+        witness,
+    }
 }
 
 export_candid!();
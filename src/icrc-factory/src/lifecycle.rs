@@ -0,0 +1,214 @@
+use candid::{Nat, Principal};
+use ic_cdk::{
+    api::{call::call_raw128, management_canister::main::CanisterIdRecord},
+    caller, id,
+};
+use icrc_ledger_types::icrc1::account::Account;
+
+use crate::{
+    auto_top_up::drop_auto_top_up,
+    cycles_ledger,
+    events::record_event,
+    ledger::list_archives,
+    mgmt::{
+        delete_canister_by_id, reinstall_wasm, release_cycle_reserves, start_canister_by_id,
+        stop_canister_by_id,
+    },
+    prepaid::credit,
+    state::{mutate_state, read_config, read_state},
+    types::{
+        args::lifecycle::DeleteCanisterArgs,
+        event::EventKind,
+        prepaid::PrepaidReason,
+        results::{
+            create_canister::{CreateCanisterError, SetCanisterResult},
+            lifecycle::DeleteCanisterResult,
+        },
+        stored_principal::StoredPrincipal,
+        user_canister::{UserCanister, UserCanisterKind},
+    },
+    user_canister::{get_managed_user_canister, upsert_user_canister},
+};
+
+/// Module installed over a canister right before it is deleted, so that its cycles can be sent
+/// back to the factory. See `wasm/cycles-drainer.wat` for its source.
+const CYCLES_DRAINER_WASM: &[u8] = include_bytes!("../wasm/cycles-drainer.wasm");
+
+pub async fn stop_canister(canister_id: Principal) -> SetCanisterResult {
+    if let Err(err) = read_state(|s| get_managed_user_canister(s, caller(), canister_id)) {
+        return SetCanisterResult::Err(err);
+    }
+
+    if let Err(err) = stop_canister_by_id(canister_id).await {
+        return SetCanisterResult::Err(CreateCanisterError::CanisterStopFailed(err));
+    }
+
+    record_event(EventKind::CanisterStopped { canister_id });
+
+    SetCanisterResult::Ok()
+}
+
+pub async fn start_canister(canister_id: Principal) -> SetCanisterResult {
+    if let Err(err) = read_state(|s| get_managed_user_canister(s, caller(), canister_id)) {
+        return SetCanisterResult::Err(err);
+    }
+
+    if let Err(err) = start_canister_by_id(canister_id).await {
+        return SetCanisterResult::Err(CreateCanisterError::CanisterStartFailed(err));
+    }
+
+    record_event(EventKind::CanisterStarted { canister_id });
+
+    SetCanisterResult::Ok()
+}
+
+pub async fn delete_canister(args: DeleteCanisterArgs) -> DeleteCanisterResult {
+    let owner = caller();
+    let DeleteCanisterArgs {
+        canister_id,
+        recover_cycles_to,
+    } = args;
+
+    let user_canister = match read_state(|s| get_managed_user_canister(s, owner, canister_id)) {
+        Ok(user_canister) => UserCanister {
+            pending_owner: None,
+            ..user_canister
+        },
+        Err(err) => return DeleteCanisterResult::Err(err),
+    };
+
+    // A pending ownership transfer is withdrawn before anything is awaited, so that it cannot be
+    // accepted while the canister is being deleted.
+    mutate_state(|state| {
+        upsert_user_canister(StoredPrincipal(owner), state, user_canister.clone());
+    });
+
+    // Archives are listed first: once the ledger is wiped, nothing knows about them anymore.
+    let archives = if user_canister.kind == UserCanisterKind::IcrcLedger {
+        match list_archives(canister_id).await {
+            Ok(archives) => archives,
+            Err(err) => {
+                return DeleteCanisterResult::Err(CreateCanisterError::ArchiveListingFailed(err))
+            }
+        }
+    } else {
+        vec![]
+    };
+
+    if let Err(err) = wipe(canister_id).await {
+        return DeleteCanisterResult::Err(err);
+    }
+
+    // The canister's code and state are gone from here on, so it is recorded as deleted even if
+    // deleting the canister itself fails below.
+    mutate_state(|state| {
+        upsert_user_canister(
+            StoredPrincipal(owner),
+            state,
+            UserCanister {
+                deleted: Some(true),
+                ..user_canister
+            },
+        );
+    });
+
+    drop_auto_top_up(canister_id);
+
+    let mut recovered = drain_and_delete(canister_id).await;
+
+    // The ledger is gone, so archives are deleted on a best-effort basis: one that cannot be
+    // wiped is left in place, still controlled by the factory.
+    for archive_id in archives {
+        if wipe(archive_id).await.is_ok() {
+            recovered = recovered.saturating_add(drain_and_delete(archive_id).await);
+        }
+    }
+
+    let recovery = match recover_cycles_to {
+        Some(to) if recovered > 0 => recover_cycles(owner, canister_id, to, recovered).await,
+        _ => Ok(()),
+    };
+
+    record_event(EventKind::CanisterDeleted {
+        canister_id,
+        recovered_cycles: Nat::from(recovered),
+        recovered_to: recover_cycles_to.filter(|_| recovery.is_ok()),
+    });
+
+    match recovery {
+        Ok(()) => DeleteCanisterResult::Ok(Nat::from(recovered)),
+        Err(err) => DeleteCanisterResult::Err(err),
+    }
+}
+
+/// Sends recovered cycles to a cycles ledger account.
+///
+/// Should the deposit fail, the cycles are credited to the owner's prepaid balance instead, so
+/// that they can still be spent through the factory.
+async fn recover_cycles(
+    owner: Principal,
+    canister_id: Principal,
+    to: Account,
+    cycles: u128,
+) -> Result<(), CreateCanisterError> {
+    let cycles_ledger = read_config(|config| config.cycles_ledger);
+
+    if let Err(err) = cycles_ledger::deposit(cycles_ledger, to, cycles).await {
+        credit(
+            owner,
+            u64::try_from(cycles).unwrap_or(u64::MAX),
+            PrepaidReason::CyclesRecovered { canister_id },
+        );
+        return Err(CreateCanisterError::CyclesRecoveryFailed(err));
+    }
+
+    Ok(())
+}
+
+/// Reinstalls a canister with the cycles drainer, wiping its code and state.
+///
+/// The canister is started first, as the drainer has to run and the owner may have stopped it.
+async fn wipe(canister_id: Principal) -> Result<(), CreateCanisterError> {
+    start_canister_by_id(canister_id)
+        .await
+        .map_err(CreateCanisterError::CanisterStartFailed)?;
+
+    release_cycle_reserves(canister_id)
+        .await
+        .map_err(CreateCanisterError::UpdateSettingsFailed)?;
+
+    reinstall_wasm(canister_id, CYCLES_DRAINER_WASM.to_vec(), vec![])
+        .await
+        .map_err(CreateCanisterError::WasmInstallationFailed)
+}
+
+/// Moves the cycles of a canister wiped by [`wipe`] to the factory, then deletes the canister.
+///
+/// A canister holding too few cycles to pay for the drain is deleted without recovering anything.
+/// One that cannot be stopped or deleted is left in place running the drainer, still controlled
+/// by the factory. Returns the recovered cycles.
+async fn drain_and_delete(canister_id: Principal) -> u128 {
+    let recovered = drain(canister_id).await.unwrap_or(0);
+
+    if stop_canister_by_id(canister_id).await.is_ok() {
+        let _ = delete_canister_by_id(canister_id).await;
+    }
+
+    recovered
+}
+
+/// Asks a canister running the cycles drainer to deposit its balance into the factory.
+async fn drain(canister_id: Principal) -> Result<u128, String> {
+    let arg = candid::encode_one(CanisterIdRecord { canister_id: id() })
+        .map_err(|err| format!("Failed to encode drain argument: {err}"))?;
+
+    let reply = call_raw128(canister_id, "drain", arg, 0)
+        .await
+        .map_err(|(code, msg)| format!("Failed to drain cycles: {code:?} - {msg}"))?;
+
+    let amount: [u8; 16] = reply
+        .try_into()
+        .map_err(|_| "Unexpected reply from the cycles drainer".to_string())?;
+
+    Ok(u128::from_le_bytes(amount))
+}
@@ -0,0 +1,146 @@
+use candid::Principal;
+use ic_cdk::{api::time, caller};
+
+use crate::{
+    state::{mutate_state, read_state},
+    types::{
+        candid::Candid,
+        log::{LogEntry, LogFilter, LogLevel},
+        results::create_canister::CreateCanisterError,
+        stored_principal::StoredPrincipal,
+    },
+    user_canister::find_user_canister,
+};
+
+/// Maximum number of log entries kept in stable memory; the oldest are dropped first.
+const MAX_LOG_ENTRIES: u64 = 10_000;
+
+/// Largest number of log entries returned by a single call of [`list_logs`].
+pub const MAX_LOG_ENTRIES_PER_PAGE: u64 = 100;
+
+/// The flow that log entries belong to, such as a canister creation or upgrade.
+///
+/// Every entry of a flow shares the ID of its first entry as correlation ID, so that the steps of
+/// concurrent flows can be told apart.
+#[derive(Clone, Copy, Debug)]
+pub struct LogContext {
+    correlation_id: u64,
+    canister_id: Option<Principal>,
+}
+
+impl LogContext {
+    /// Starts a flow about `canister_id`, if already known, by logging `message` at info level.
+    pub fn start(canister_id: Option<Principal>, message: &str) -> Self {
+        let correlation_id = append(LogLevel::Info, None, canister_id, message);
+
+        Self {
+            correlation_id,
+            canister_id,
+        }
+    }
+
+    /// The same flow, now about `canister_id`, e.g. once the canister is created.
+    #[must_use]
+    pub fn with_canister(self, canister_id: Principal) -> Self {
+        Self {
+            canister_id: Some(canister_id),
+            ..self
+        }
+    }
+
+    pub fn debug(&self, message: &str) {
+        self.log(LogLevel::Debug, message);
+    }
+
+    pub fn info(&self, message: &str) {
+        self.log(LogLevel::Info, message);
+    }
+
+    pub fn warn(&self, message: &str) {
+        self.log(LogLevel::Warn, message);
+    }
+
+    pub fn error(&self, message: &str) {
+        self.log(LogLevel::Error, message);
+    }
+
+    fn log(&self, level: LogLevel, message: &str) {
+        append(level, Some(self.correlation_id), self.canister_id, message);
+    }
+}
+
+/// Appends a log entry, dropping the oldest entries beyond `MAX_LOG_ENTRIES`. Returns the ID of the
+/// entry; without a `correlation_id`, the entry starts a flow and is its own correlation ID.
+fn append(
+    level: LogLevel,
+    correlation_id: Option<u64>,
+    canister_id: Option<Principal>,
+    message: &str,
+) -> u64 {
+    mutate_state(|s| {
+        let id = s.logs.last_key_value().map_or(0, |(id, _)| id + 1);
+        let correlation_id = correlation_id.unwrap_or(id);
+
+        s.logs.insert(
+            id,
+            Candid(LogEntry {
+                id,
+                timestamp: time(),
+                level,
+                correlation_id,
+                caller: caller(),
+                canister_id,
+                message: message.to_string(),
+            }),
+        );
+
+        while s.logs.len() > MAX_LOG_ENTRIES {
+            s.logs.pop_first();
+        }
+
+        id
+    })
+}
+
+/// Returns at most [`MAX_LOG_ENTRIES_PER_PAGE`] log entries matching `filter`, oldest first;
+/// `offset` counts matching entries only.
+#[must_use]
+pub fn list_logs(filter: &LogFilter, offset: u64, limit: u64) -> Vec<LogEntry> {
+    let limit = limit.min(MAX_LOG_ENTRIES_PER_PAGE);
+
+    read_state(|s| {
+        s.logs
+            .values()
+            .map(|Candid(entry)| entry)
+            .filter(|entry| filter.matches(entry))
+            .skip(usize::try_from(offset).unwrap_or(usize::MAX))
+            .take(usize::try_from(limit).unwrap_or(usize::MAX))
+            .collect()
+    })
+}
+
+/// Returns the log entries about a canister owned by `owner`, including a deleted one.
+///
+/// # Errors
+/// - `CanisterNotFound` if the canister is not in the registry.
+/// - `NotOwner` if the canister is registered under a different owner.
+pub fn list_canister_logs(
+    owner: Principal,
+    canister_id: Principal,
+    filter: LogFilter,
+    offset: u64,
+    limit: u64,
+) -> Result<Vec<LogEntry>, CreateCanisterError> {
+    match read_state(|s| find_user_canister(s, canister_id)) {
+        Some((StoredPrincipal(registered_owner), _)) if registered_owner == owner => {}
+        Some(_) => return Err(CreateCanisterError::NotOwner),
+        None => return Err(CreateCanisterError::CanisterNotFound),
+    }
+
+    let filter = LogFilter {
+        canister_id: Some(canister_id),
+        ..filter
+    };
+
+    Ok(list_logs(&filter, offset, limit))
+}
@@ -1,19 +1,63 @@
-use crate::generic::MIN_CYCLES_FOR_CANISTER_CREATION;
+use candid::{CandidType, Deserialize, Principal};
+use serde::Serialize;
 
+use crate::{
+    discounts::pricing_for,
+    pricing::get_pricing,
+    settings::allocation_fee,
+    types::{args::create_canister::CanisterSettingsArgs, pricing::Pricing},
+};
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Eq, PartialEq, Debug)]
 pub enum SignerMethods {
     CreateIcrcLedger,
     CreateIcrcIndex,
+    TopUpCanister,
 }
 
 impl SignerMethods {
     /// The cost, in cycles, of every paid API method.
+    ///
+    /// Base fees and the service margin come from the pricing table set by controllers. Resource
+    /// allocations requested in `settings` are charged on top of the base fee, since the factory
+    /// has to fund them when creating the canister.
+    #[must_use]
+    pub fn fee(self, settings: Option<&CanisterSettingsArgs>) -> u64 {
+        self.fee_with(&get_pricing(), settings)
+    }
+
+    /// The cost, in cycles, of a paid API method for `payer`.
+    ///
+    /// Same as [`SignerMethods::fee`], except that allowlisted principals are charged from their
+    /// custom pricing.
     #[must_use]
-    #[allow(clippy::match_same_arms)] // Entries are sorted by method, as this makes them easier to manage.
-    pub fn fee(&self) -> u64 {
-        // Note: Fees are determined with the aid of scripts/check-pricing
+    pub fn fee_for(self, payer: Principal, settings: Option<&CanisterSettingsArgs>) -> u64 {
+        self.fee_with(&pricing_for(payer), settings)
+    }
+
+    /// The cycles a canister created by a paid API method is funded with.
+    ///
+    /// The base fee plus the cost of the allocations requested in `settings`, taken from the
+    /// pricing table; the service margin is kept by the factory.
+    #[must_use]
+    pub fn provisioned_cycles(self, settings: Option<&CanisterSettingsArgs>) -> u64 {
+        self.base_fee(&get_pricing())
+            .saturating_add(allocation_fee(settings))
+    }
+
+    fn fee_with(self, pricing: &Pricing, settings: Option<&CanisterSettingsArgs>) -> u64 {
+        pricing.with_margin(
+            self.base_fee(pricing)
+                .saturating_add(allocation_fee(settings)),
+        )
+    }
+
+    fn base_fee(self, pricing: &Pricing) -> u64 {
         match self {
-            SignerMethods::CreateIcrcLedger => MIN_CYCLES_FOR_CANISTER_CREATION + 400_000_000_000,
-            SignerMethods::CreateIcrcIndex => MIN_CYCLES_FOR_CANISTER_CREATION + 400_000_000_000,
+            SignerMethods::CreateIcrcLedger => pricing.create_icrc_ledger,
+            SignerMethods::CreateIcrcIndex => pricing.create_icrc_index,
+            // Service fee only; the deposited cycles are charged on top of it.
+            SignerMethods::TopUpCanister => pricing.top_up_canister,
         }
     }
 }
@@ -0,0 +1,181 @@
+use std::{collections::BTreeMap, io};
+
+use ic_cdk::api::{canister_balance128, stable::stable_size, time};
+use ic_metrics_encoder::MetricsEncoder;
+
+use crate::{
+    state::{memory_pages, mutate_state, read_state},
+    types::{
+        candid::Candid,
+        event::EventPayment,
+        results::create_canister::CreateCanisterResult,
+        user_canister::{UserCanister, UserCanisterKind},
+    },
+    wasm::utils::module_hash,
+};
+
+/// Counts a creation under its outcome: `Ok`, or the name of the error it failed with.
+pub fn record_creation(result: &CreateCanisterResult) {
+    let outcome = match result {
+        CreateCanisterResult::Ok(_) => "Ok",
+        CreateCanisterResult::Err(err) => err.name(),
+    };
+
+    mutate_state(|s| {
+        let count = s.creation_outcomes.get(&outcome.to_string()).unwrap_or(0);
+        s.creation_outcomes
+            .insert(outcome.to_string(), count.saturating_add(1));
+    });
+}
+
+/// Adds the cycles charged by a paid action to the total of its payment method.
+pub fn record_payment(payment: &EventPayment) {
+    let method = payment.method.name().to_string();
+
+    mutate_state(|s| {
+        let total = s.payment_totals.get(&method).unwrap_or(0);
+        s.payment_totals
+            .insert(method, total.saturating_add(payment.cycles));
+    });
+}
+
+/// Encodes the factory metrics in the Prometheus text exposition format.
+///
+/// # Errors
+/// - If a metric cannot be written, which does not happen when writing to memory.
+pub fn encode_metrics() -> io::Result<Vec<u8>> {
+    let now_millis = i64::try_from(time() / 1_000_000).unwrap_or(i64::MAX);
+    let mut w = MetricsEncoder::new(Vec::new(), now_millis);
+
+    w.encode_gauge(
+        "icrc_factory_cycles_balance",
+        float(canister_balance128()),
+        "Cycles balance of the factory.",
+    )?;
+
+    let mut canisters = w.gauge_vec(
+        "icrc_factory_canisters",
+        "Canisters in the registry, by kind and install status.",
+    )?;
+    for ((kind, status), count) in canister_counts() {
+        canisters = canisters.value(
+            &[("kind", kind), ("status", status)],
+            float(u128::from(count)),
+        )?;
+    }
+
+    let outcomes = read_state(|s| {
+        s.creation_outcomes
+            .keys()
+            .filter_map(|outcome| s.creation_outcomes.get(&outcome).map(|n| (outcome, n)))
+            .collect::<Vec<_>>()
+    });
+    let mut creations = w.counter_vec(
+        "icrc_factory_creations_total",
+        "Ledger and index creations, by outcome: Ok or the error they failed with.",
+    )?;
+    for (outcome, count) in &outcomes {
+        creations = creations.value(&[("outcome", outcome.as_str())], float(u128::from(*count)))?;
+    }
+
+    let totals = read_state(|s| {
+        s.payment_totals
+            .keys()
+            .filter_map(|method| s.payment_totals.get(&method).map(|n| (method, n)))
+            .collect::<Vec<_>>()
+    });
+    let mut payments = w.counter_vec(
+        "icrc_factory_payments_cycles_total",
+        "Cycles charged by paid actions, after discounts, by payment method.",
+    )?;
+    for (method, cycles) in &totals {
+        payments = payments.value(&[("method", method.as_str())], float(u128::from(*cycles)))?;
+    }
+
+    w.encode_gauge(
+        "icrc_factory_stable_memory_pages",
+        float(u128::from(stable_size())),
+        "Stable memory of the factory, in WASM pages of 64 KiB.",
+    )?;
+
+    let mut memories = w.gauge_vec(
+        "icrc_factory_memory_pages",
+        "Stable memory used by each memory of the state, in WASM pages of 64 KiB.",
+    )?;
+    for (memory, pages) in memory_pages() {
+        memories = memories.value(&[("memory", memory)], float(u128::from(pages)))?;
+    }
+
+    let wasms = read_state(|s| {
+        [
+            ("ledger", s.icrc_ledger_wasm.get()),
+            ("index", s.icrc_index_wasm.get()),
+        ]
+        .map(|(wasm, bytes)| (wasm, bytes.len(), wasm_hash(bytes)))
+    });
+    let mut wasm_sizes = w.gauge_vec(
+        "icrc_factory_wasm_size_bytes",
+        "Size of the stored WASM modules, labeled with their SHA-256 hash.",
+    )?;
+    for (wasm, size, hash) in wasms {
+        wasm_sizes = wasm_sizes.value(
+            &[("wasm", wasm), ("module_hash", hash.as_str())],
+            float(u128::try_from(size).unwrap_or(u128::MAX)),
+        )?;
+    }
+
+    Ok(w.into_inner())
+}
+
+/// Number of registered canisters by kind and status: `installed`, `not_installed` or `deleted`.
+fn canister_counts() -> BTreeMap<(&'static str, &'static str), u64> {
+    read_state(|s| {
+        let mut counts = BTreeMap::new();
+
+        for Candid(canisters) in s.user_canister.values() {
+            for canister in canisters {
+                let count = counts
+                    .entry((kind_label(&canister), status_label(&canister)))
+                    .or_insert(0);
+                *count += 1;
+            }
+        }
+
+        counts
+    })
+}
+
+fn kind_label(canister: &UserCanister) -> &'static str {
+    match canister.kind {
+        UserCanisterKind::IcrcLedger => "IcrcLedger",
+        UserCanisterKind::IcrcIndex => "IcrcIndex",
+    }
+}
+
+fn status_label(canister: &UserCanister) -> &'static str {
+    if canister.is_deleted() {
+        "deleted"
+    } else if canister.installed {
+        "installed"
+    } else {
+        "not_installed"
+    }
+}
+
+/// Hex-encoded SHA-256 hash of a stored WASM, or an empty string while none is stored.
+fn wasm_hash(wasm: &[u8]) -> String {
+    if wasm.is_empty() {
+        return String::new();
+    }
+
+    module_hash(wasm)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// Metrics are floats: counts and cycles lose precision only beyond 2^53.
+#[allow(clippy::cast_precision_loss)]
+fn float(value: u128) -> f64 {
+    value as f64
+}
@@ -1,8 +1,11 @@
 use candid::Principal;
 use ic_cdk::api::management_canister::{
     main::{
-        create_canister, install_code, CanisterIdRecord, CanisterInstallMode, CanisterSettings,
-        CreateCanisterArgument, InstallCodeArgument,
+        canister_info, canister_status, create_canister, delete_canister, deposit_cycles,
+        install_code, start_canister, stop_canister, update_settings, CanisterIdRecord,
+        CanisterInfoRequest, CanisterInfoResponse, CanisterInstallMode, CanisterSettings,
+        CanisterStatusResponse, CreateCanisterArgument, InstallCodeArgument,
+        UpdateSettingsArgument,
     },
     provisional::CanisterId,
 };
@@ -49,3 +52,105 @@ pub async fn upgrade_wasm(
         .await
         .map_err(|(code, msg)| format!("Failed to upgrade code: {code:?} - {msg}"))
 }
+
+pub async fn reinstall_wasm(
+    canister_id: CanisterId,
+    wasm_module: Vec<u8>,
+    arg: Vec<u8>,
+) -> Result<(), String> {
+    let args = InstallCodeArgument {
+        mode: CanisterInstallMode::Reinstall,
+        canister_id,
+        wasm_module,
+        arg,
+    };
+    install_code(args)
+        .await
+        .map_err(|(code, msg)| format!("Failed to reinstall code: {code:?} - {msg}"))
+}
+
+pub async fn get_canister_status(
+    canister_id: CanisterId,
+) -> Result<CanisterStatusResponse, String> {
+    canister_status(CanisterIdRecord { canister_id })
+        .await
+        .map(|(status,)| status)
+        .map_err(|(code, msg)| format!("Failed to get canister status: {code:?} - {msg}"))
+}
+
+/// Reads the controllers and module hash of a canister, which, unlike its status, works for
+/// canisters the factory does not control.
+pub async fn get_canister_info(canister_id: CanisterId) -> Result<CanisterInfoResponse, String> {
+    canister_info(CanisterInfoRequest {
+        canister_id,
+        num_requested_changes: None,
+    })
+    .await
+    .map(|(info,)| info)
+    .map_err(|(code, msg)| format!("Failed to get canister info: {code:?} - {msg}"))
+}
+
+pub async fn set_controllers(
+    canister_id: CanisterId,
+    controllers: Vec<Principal>,
+) -> Result<(), String> {
+    let args = UpdateSettingsArgument {
+        canister_id,
+        settings: CanisterSettings {
+            controllers: Some(controllers),
+            compute_allocation: None,
+            memory_allocation: None,
+            freezing_threshold: None,
+            reserved_cycles_limit: None,
+            log_visibility: None,
+            wasm_memory_limit: None,
+        },
+    };
+    update_settings(args)
+        .await
+        .map_err(|(code, msg)| format!("Failed to update settings: {code:?} - {msg}"))
+}
+
+pub async fn deposit_cycles_to(canister_id: CanisterId, cycles: u128) -> Result<(), String> {
+    deposit_cycles(CanisterIdRecord { canister_id }, cycles)
+        .await
+        .map_err(|(code, msg)| format!("Failed to deposit cycles: {code:?} - {msg}"))
+}
+
+/// Lifts the settings that would keep cycles locked in a canister: a freezing threshold and a
+/// compute allocation both make part of the balance unspendable.
+pub async fn release_cycle_reserves(canister_id: CanisterId) -> Result<(), String> {
+    let args = UpdateSettingsArgument {
+        canister_id,
+        settings: CanisterSettings {
+            controllers: None,
+            compute_allocation: Some(0u8.into()),
+            memory_allocation: None,
+            freezing_threshold: Some(0u8.into()),
+            reserved_cycles_limit: None,
+            log_visibility: None,
+            wasm_memory_limit: None,
+        },
+    };
+    update_settings(args)
+        .await
+        .map_err(|(code, msg)| format!("Failed to update settings: {code:?} - {msg}"))
+}
+
+pub async fn start_canister_by_id(canister_id: CanisterId) -> Result<(), String> {
+    start_canister(CanisterIdRecord { canister_id })
+        .await
+        .map_err(|(code, msg)| format!("Failed to start canister: {code:?} - {msg}"))
+}
+
+pub async fn stop_canister_by_id(canister_id: CanisterId) -> Result<(), String> {
+    stop_canister(CanisterIdRecord { canister_id })
+        .await
+        .map_err(|(code, msg)| format!("Failed to stop canister: {code:?} - {msg}"))
+}
+
+pub async fn delete_canister_by_id(canister_id: CanisterId) -> Result<(), String> {
+    delete_canister(CanisterIdRecord { canister_id })
+        .await
+        .map_err(|(code, msg)| format!("Failed to delete canister: {code:?} - {msg}"))
+}
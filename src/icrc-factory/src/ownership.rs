@@ -0,0 +1,121 @@
+use candid::Principal;
+use ic_cdk::caller;
+
+use crate::{
+    auto_top_up::drop_auto_top_up,
+    controllers::swap_controller,
+    events::record_event,
+    state::{mutate_state, read_state},
+    types::{
+        args::ownership::{AcceptOwnershipTransferArgs, ProposeOwnershipTransferArgs},
+        event::EventKind,
+        results::create_canister::{CreateCanisterError, SetCanisterResult},
+        stored_principal::StoredPrincipal,
+        user_canister::UserCanister,
+    },
+    user_canister::{
+        find_user_canister, get_managed_user_canister, remove_user_canister, upsert_user_canister,
+    },
+};
+
+pub fn propose_ownership_transfer(args: ProposeOwnershipTransferArgs) -> SetCanisterResult {
+    let owner = caller();
+
+    if let Some(new_owner) = args.new_owner {
+        if new_owner == owner || new_owner == Principal::anonymous() {
+            return SetCanisterResult::Err(CreateCanisterError::InvalidNewOwner);
+        }
+    }
+
+    let user_canister = match read_state(|s| get_managed_user_canister(s, owner, args.canister_id))
+    {
+        Ok(user_canister) => user_canister,
+        Err(err) => return SetCanisterResult::Err(err),
+    };
+
+    mutate_state(|state| {
+        upsert_user_canister(
+            StoredPrincipal(owner),
+            state,
+            UserCanister {
+                pending_owner: args.new_owner,
+                ..user_canister
+            },
+        );
+    });
+
+    SetCanisterResult::Ok()
+}
+
+pub async fn accept_ownership_transfer(args: AcceptOwnershipTransferArgs) -> SetCanisterResult {
+    let new_owner = caller();
+    let canister_id = args.canister_id;
+
+    let Some((StoredPrincipal(old_owner), user_canister)) =
+        read_state(|s| find_user_canister(s, canister_id))
+    else {
+        return SetCanisterResult::Err(CreateCanisterError::CanisterNotFound);
+    };
+
+    if user_canister.pending_owner != Some(new_owner) {
+        return SetCanisterResult::Err(CreateCanisterError::NoPendingOwnershipTransfer);
+    }
+
+    if user_canister.is_sovereign() {
+        return SetCanisterResult::Err(CreateCanisterError::Sovereign);
+    }
+
+    if user_canister.is_deleted() {
+        return SetCanisterResult::Err(CreateCanisterError::Deleted);
+    }
+
+    let controllers = match swap_controller(canister_id, old_owner, new_owner).await {
+        Ok(controllers) => controllers,
+        Err(err) => return SetCanisterResult::Err(err),
+    };
+
+    // The entry may have changed during the swap: the offer withdrawn or re-proposed, the canister
+    // deleted or released, or the transfer completed by a concurrent call.
+    let transferred = mutate_state(|state| match find_user_canister(state, canister_id) {
+        Some((StoredPrincipal(owner), entry))
+            if owner == old_owner && !entry.is_sovereign() && !entry.is_deleted() =>
+        {
+            if entry.pending_owner != Some(new_owner) {
+                return Err(true);
+            }
+
+            remove_user_canister(StoredPrincipal(old_owner), state, canister_id);
+            upsert_user_canister(
+                StoredPrincipal(new_owner),
+                state,
+                UserCanister {
+                    pending_owner: None,
+                    controllers: Some(controllers),
+                    ..entry
+                },
+            );
+            Ok(())
+        }
+        _ => Err(false),
+    });
+
+    if let Err(still_owned) = transferred {
+        // The previous owner withdrew the offer while it was being accepted: hand control back.
+        // Best effort, as the registry already reflects their ownership.
+        if still_owned {
+            let _ = swap_controller(canister_id, new_owner, old_owner).await;
+        }
+        return SetCanisterResult::Err(CreateCanisterError::NoPendingOwnershipTransfer);
+    }
+
+    // Top-ups were funded by the previous owner; the new owner subscribes on their own terms.
+    drop_auto_top_up(canister_id);
+
+    record_event(EventKind::OwnershipTransferred {
+        canister_id,
+        from: old_owner,
+        to: new_owner,
+    });
+
+    SetCanisterResult::Ok()
+}
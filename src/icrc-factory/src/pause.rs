@@ -0,0 +1,51 @@
+use crate::{
+    state::{read_config, update_config},
+    types::{
+        args::pause::SetPausedArgs, config::PauseFlags,
+        results::create_canister::CreateCanisterError,
+    },
+};
+
+/// A class of operations that can be paused.
+#[derive(Clone, Copy, Debug)]
+pub enum PausableOperation {
+    Creation,
+    Upgrades,
+    WasmUpdates,
+}
+
+/// Returns the current pause flags.
+#[must_use]
+pub fn pause_flags() -> PauseFlags {
+    read_config(|config| config.paused.unwrap_or_default())
+}
+
+/// Refuses `operation` while it is paused.
+///
+/// # Errors
+/// - `Paused` if an admin paused the operation.
+pub fn ensure_not_paused(operation: PausableOperation) -> Result<(), CreateCanisterError> {
+    let flags = pause_flags();
+    let paused = match operation {
+        PausableOperation::Creation => flags.creation,
+        PausableOperation::Upgrades => flags.upgrades,
+        PausableOperation::WasmUpdates => flags.wasm_updates,
+    };
+
+    if paused {
+        Err(CreateCanisterError::Paused)
+    } else {
+        Ok(())
+    }
+}
+
+/// Pauses or resumes classes of operations, and records the new configuration.
+pub fn set_paused(args: SetPausedArgs) {
+    update_config(|config| {
+        let mut flags = config.paused.unwrap_or_default();
+        flags.creation = args.creation.unwrap_or(flags.creation);
+        flags.upgrades = args.upgrades.unwrap_or(flags.upgrades);
+        flags.wasm_updates = args.wasm_updates.unwrap_or(flags.wasm_updates);
+        config.paused = Some(flags);
+    });
+}
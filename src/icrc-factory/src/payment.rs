@@ -0,0 +1,243 @@
+use std::{cell::Cell, time::Duration};
+
+use candid::{Nat, Principal};
+use ic_cdk::caller;
+use ic_cdk_timers::set_timer;
+use ic_papi_api::PaymentType;
+use ic_papi_guard::guards::any::VendorPaymentConfig;
+
+use crate::{
+    icp::{cached_icp_xdr_rate, charge_icp, cycles_to_e8s, icp_ledger_fee},
+    methods::SignerMethods,
+    prepaid::{credit_excess_attached_cycles, debit},
+    settings::validate_canister_settings,
+    state::{icp_ledger, payment_ledger, PAYMENT_GUARD},
+    types::{
+        args::create_canister::CanisterSettingsArgs,
+        payment::FactoryPaymentType,
+        prepaid::PrepaidReason,
+        quote::{FeeQuote, PaymentMethod, PaymentQuote},
+        results::create_canister::CreateCanisterError,
+    },
+};
+
+/// Transfer fee of the cycles ledger, which ICRC-2 cycles payments go through.
+const CYCLES_LEDGER_FEE: u64 = 100_000_000;
+
+thread_local! {
+    /// Transfer fee of the payment ledger, as last read from it.
+    static PAYMENT_LEDGER_FEE: Cell<Option<u64>> = const { Cell::new(None) };
+}
+
+/// The payment methods accepted by [`PAYMENT_GUARD`], in the order it lists them.
+#[must_use]
+pub fn supported_payment_methods() -> [PaymentMethod; 5] {
+    let ledger = payment_ledger();
+
+    [
+        PaymentMethod::AttachedCycles,
+        PaymentMethod::CallerPaysIcrc2Cycles,
+        PaymentMethod::PatronPaysIcrc2Cycles,
+        PaymentMethod::CallerPaysIcrc2Tokens { ledger },
+        PaymentMethod::PatronPaysIcrc2Tokens { ledger },
+    ]
+}
+
+impl From<PaymentMethod> for VendorPaymentConfig {
+    fn from(method: PaymentMethod) -> Self {
+        match method {
+            PaymentMethod::AttachedCycles => VendorPaymentConfig::AttachedCycles,
+            PaymentMethod::CallerPaysIcrc2Cycles => VendorPaymentConfig::CallerPaysIcrc2Cycles,
+            PaymentMethod::PatronPaysIcrc2Cycles => VendorPaymentConfig::PatronPaysIcrc2Cycles,
+            PaymentMethod::CallerPaysIcrc2Tokens { ledger } => {
+                VendorPaymentConfig::CallerPaysIcrc2Tokens { ledger }
+            }
+            PaymentMethod::PatronPaysIcrc2Tokens { ledger } => {
+                VendorPaymentConfig::PatronPaysIcrc2Tokens { ledger }
+            }
+            PaymentMethod::PrepaidBalance | PaymentMethod::CallerPaysIcp { .. } => {
+                unreachable!("{method:?} is settled by the factory, not the payment guard")
+            }
+        }
+    }
+}
+
+/// The payment method a payment is made with, as recorded in the event log.
+#[must_use]
+pub fn payment_method(payment: Option<&FactoryPaymentType>) -> PaymentMethod {
+    match payment.unwrap_or(&FactoryPaymentType::AttachedCycles) {
+        FactoryPaymentType::AttachedCycles => PaymentMethod::AttachedCycles,
+        FactoryPaymentType::CallerPaysIcrc2Cycles => PaymentMethod::CallerPaysIcrc2Cycles,
+        FactoryPaymentType::PatronPaysIcrc2Cycles(_) => PaymentMethod::PatronPaysIcrc2Cycles,
+        FactoryPaymentType::CallerPaysIcrc2Tokens(payment) => {
+            PaymentMethod::CallerPaysIcrc2Tokens {
+                ledger: payment.ledger,
+            }
+        }
+        FactoryPaymentType::PatronPaysIcrc2Tokens(payment) => {
+            PaymentMethod::PatronPaysIcrc2Tokens {
+                ledger: payment.ledger,
+            }
+        }
+        FactoryPaymentType::PrepaidBalance => PaymentMethod::PrepaidBalance,
+        FactoryPaymentType::CallerPaysIcp => PaymentMethod::CallerPaysIcp {
+            ledger: icp_ledger(),
+        },
+    }
+}
+
+/// Fee of a canister creation for `caller`, before discounts.
+///
+/// # Errors
+/// - `InvalidCanisterSettings` if `settings` are out of bounds.
+pub fn creation_fee(
+    caller: Principal,
+    method: SignerMethods,
+    settings: Option<&CanisterSettingsArgs>,
+) -> Result<u64, CreateCanisterError> {
+    if let Some(settings) = settings {
+        validate_canister_settings(settings)?;
+    }
+
+    Ok(method.fee_for(caller, settings))
+}
+
+/// Breaks a fee down for every supported payment method, followed by the prepaid balance and ICP.
+///
+/// Fees are charged one to one, in cycles or in tokens of the payment ledger. ICP payments are
+/// priced at the CMC conversion rate last read, and left out until it has been read. ICRC-2
+/// payments also need the allowance to cover the ledger's transfer fee, unless nothing is charged.
+#[must_use]
+pub fn quote(fee: u64) -> FeeQuote {
+    let icp = PaymentMethod::CallerPaysIcp {
+        ledger: icp_ledger(),
+    };
+
+    let payments = supported_payment_methods()
+        .into_iter()
+        .chain([PaymentMethod::PrepaidBalance, icp])
+        .filter_map(|method| {
+            let (amount, ledger_fee) = match method {
+                PaymentMethod::AttachedCycles | PaymentMethod::PrepaidBalance => (fee, None),
+                PaymentMethod::CallerPaysIcrc2Cycles | PaymentMethod::PatronPaysIcrc2Cycles => {
+                    (fee, Some(CYCLES_LEDGER_FEE))
+                }
+                PaymentMethod::CallerPaysIcrc2Tokens { .. }
+                | PaymentMethod::PatronPaysIcrc2Tokens { .. } => (fee, Some(payment_ledger_fee())),
+                PaymentMethod::CallerPaysIcp { .. } => (
+                    cycles_to_e8s(fee, cached_icp_xdr_rate()?),
+                    Some(icp_ledger_fee()),
+                ),
+            };
+
+            Some(PaymentQuote {
+                method,
+                amount,
+                allowance: ledger_fee
+                    .filter(|_| fee > 0)
+                    .map(|ledger_fee| amount.saturating_add(ledger_fee)),
+            })
+        })
+        .collect();
+
+    FeeQuote {
+        cycles: fee,
+        payments,
+    }
+}
+
+/// Transfer fee of the payment ledger; the payment ledger is the cycles ledger until the fee has
+/// been read from it.
+fn payment_ledger_fee() -> u64 {
+    PAYMENT_LEDGER_FEE
+        .with(Cell::get)
+        .unwrap_or(CYCLES_LEDGER_FEE)
+}
+
+/// Reads the transfer fee of the payment ledger once the canister is running, since queries cannot
+/// call other canisters.
+pub fn schedule_payment_ledger_fee_refresh() {
+    set_timer(Duration::ZERO, || {
+        ic_cdk::spawn(refresh_payment_ledger_fee())
+    });
+}
+
+async fn refresh_payment_ledger_fee() {
+    if let Ok((fee,)) = ic_cdk::call::<(), (Nat,)>(payment_ledger(), "icrc1_fee", ()).await {
+        if let Ok(fee) = u64::try_from(fee.0) {
+            PAYMENT_LEDGER_FEE.with(|cell| cell.set(Some(fee)));
+        }
+    }
+}
+
+/// Refuses a fee above the caller's `max_fee`, e.g. after a price change since they were quoted.
+///
+/// # Errors
+/// - `FeeExceedsMax` if `fee` is greater than `max_fee`.
+pub fn check_max_fee(fee: u64, max_fee: Option<u64>) -> Result<(), CreateCanisterError> {
+    match max_fee {
+        Some(max_fee) if fee > max_fee => Err(CreateCanisterError::FeeExceedsMax { fee, max_fee }),
+        _ => Ok(()),
+    }
+}
+
+/// Takes `amount` cycles from the caller with the given payment method.
+///
+/// This is the one place paid calls settle: payments from the prepaid balance are debited here,
+/// ICP is converted through the CMC, and every other method is collected by [`PAYMENT_GUARD`].
+/// Cycles attached beyond `amount` are credited to the caller's prepaid balance.
+///
+/// # Errors
+/// - `InsufficientPrepaidBalance` if paying from a prepaid balance that does not cover `amount`.
+/// - `IcpPaymentFailed` if the ICP payment cannot be collected or converted.
+/// - `PaymentError` if the payment guard refuses or fails to collect the payment.
+pub async fn charge(
+    payment: Option<FactoryPaymentType>,
+    amount: u64,
+) -> Result<(), CreateCanisterError> {
+    let payment = match payment.unwrap_or(FactoryPaymentType::AttachedCycles) {
+        FactoryPaymentType::PrepaidBalance => {
+            return debit(caller(), amount, PrepaidReason::Payment);
+        }
+        FactoryPaymentType::CallerPaysIcp => return charge_icp(caller(), amount).await,
+        FactoryPaymentType::AttachedCycles => PaymentType::AttachedCycles,
+        FactoryPaymentType::CallerPaysIcrc2Cycles => PaymentType::CallerPaysIcrc2Cycles,
+        FactoryPaymentType::PatronPaysIcrc2Cycles(patron) => {
+            PaymentType::PatronPaysIcrc2Cycles(patron)
+        }
+        FactoryPaymentType::CallerPaysIcrc2Tokens(payment) => {
+            PaymentType::CallerPaysIcrc2Tokens(payment)
+        }
+        FactoryPaymentType::PatronPaysIcrc2Tokens(payment) => {
+            PaymentType::PatronPaysIcrc2Tokens(payment)
+        }
+    };
+
+    charge_with_guard(payment, amount).await
+}
+
+/// Takes `amount` cycles from the caller through [`PAYMENT_GUARD`].
+///
+/// Cycles attached beyond `amount` are credited to the caller's prepaid balance.
+///
+/// # Errors
+/// - `PaymentError` if the payment guard refuses or fails to collect the payment.
+pub async fn charge_with_guard(
+    payment: PaymentType,
+    amount: u64,
+) -> Result<(), CreateCanisterError> {
+    let attached_cycles = matches!(payment, PaymentType::AttachedCycles);
+
+    PAYMENT_GUARD
+        .deduct(payment, amount)
+        .await
+        .map_err(CreateCanisterError::PaymentError)?;
+
+    // The guard only accepts the fee; the surplus is kept for the caller rather than refunded, as
+    // callers going through a wallet would not get it back.
+    if attached_cycles {
+        credit_excess_attached_cycles();
+    }
+
+    Ok(())
+}
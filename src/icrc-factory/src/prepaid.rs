@@ -0,0 +1,183 @@
+use candid::Principal;
+use ic_cdk::{
+    api::{
+        call::{msg_cycles_accept128, msg_cycles_available128},
+        time,
+    },
+    caller,
+};
+
+use crate::{
+    cycles_ledger,
+    events::{record_event, record_paid_event},
+    state::{mutate_state, read_config, read_state, State},
+    types::{
+        args::auto_top_up::{FundPrepaidBalanceArgs, WithdrawPrepaidBalanceArgs},
+        candid::Candid,
+        event::{EventKind, EventPayment},
+        prepaid::{PrepaidChange, PrepaidJournalEntry, PrepaidReason},
+        results::create_canister::{CreateCanisterError, SetCanisterResult},
+        stored_principal::StoredPrincipal,
+    },
+};
+
+/// Largest number of journal entries returned by a single call of [`prepaid_journal`].
+pub const MAX_JOURNAL_ENTRIES_PER_PAGE: u64 = 100;
+
+/// Returns the prepaid cycles balance of `owner`.
+#[must_use]
+pub fn prepaid_balance(owner: Principal) -> u64 {
+    read_state(|s| s.prepaid_balances.get(&StoredPrincipal(owner)).unwrap_or(0))
+}
+
+/// Adds `cycles` to the prepaid balance of `owner`, and journals the change.
+pub fn credit(owner: Principal, cycles: u64, reason: PrepaidReason) {
+    mutate_state(|s| {
+        let balance = s
+            .prepaid_balances
+            .get(&StoredPrincipal(owner))
+            .unwrap_or(0)
+            .saturating_add(cycles);
+
+        s.prepaid_balances.insert(StoredPrincipal(owner), balance);
+
+        journal(s, owner, PrepaidChange::Credit(cycles), balance, reason);
+    });
+}
+
+/// Takes `cycles` from the prepaid balance of `owner`, and journals the change.
+///
+/// # Errors
+/// - `InsufficientPrepaidBalance` if the balance does not cover `cycles`; nothing is taken.
+pub fn debit(
+    owner: Principal,
+    cycles: u64,
+    reason: PrepaidReason,
+) -> Result<(), CreateCanisterError> {
+    mutate_state(|s| {
+        let balance = s.prepaid_balances.get(&StoredPrincipal(owner)).unwrap_or(0);
+
+        let Some(remaining) = balance.checked_sub(cycles) else {
+            return Err(CreateCanisterError::InsufficientPrepaidBalance {
+                balance,
+                required: cycles,
+            });
+        };
+
+        if remaining == 0 {
+            s.prepaid_balances.remove(&StoredPrincipal(owner));
+        } else {
+            s.prepaid_balances.insert(StoredPrincipal(owner), remaining);
+        }
+
+        journal(s, owner, PrepaidChange::Debit(cycles), remaining, reason);
+
+        Ok(())
+    })
+}
+
+/// Appends an entry to the prepaid balance journal.
+///
+/// # Panics
+/// - If the journal cannot grow, which only happens when stable memory is exhausted.
+fn journal(
+    state: &mut State,
+    owner: Principal,
+    change: PrepaidChange,
+    balance: u64,
+    reason: PrepaidReason,
+) {
+    let entry = PrepaidJournalEntry {
+        timestamp: time(),
+        owner,
+        change,
+        balance,
+        reason,
+    };
+
+    state
+        .prepaid_journal
+        .append(&Candid(entry))
+        .expect("failed to append entry to the prepaid journal");
+}
+
+/// Returns at most [`MAX_JOURNAL_ENTRIES_PER_PAGE`] journal entries of `owner`, oldest first.
+#[must_use]
+pub fn prepaid_journal(owner: Principal, offset: u64, limit: u64) -> Vec<PrepaidJournalEntry> {
+    let limit = limit.min(MAX_JOURNAL_ENTRIES_PER_PAGE);
+
+    read_state(|s| {
+        s.prepaid_journal
+            .iter()
+            .map(|Candid(entry)| entry)
+            .filter(|entry| entry.owner == owner)
+            .skip(usize::try_from(offset).unwrap_or(usize::MAX))
+            .take(usize::try_from(limit).unwrap_or(usize::MAX))
+            .collect()
+    })
+}
+
+/// Credits already paid-for cycles to the caller's prepaid balance, and records the funding with
+/// its payment.
+pub fn fund_prepaid_balance(
+    args: FundPrepaidBalanceArgs,
+    payment: EventPayment,
+) -> SetCanisterResult {
+    let owner = caller();
+
+    credit(owner, args.cycles, PrepaidReason::Funded);
+
+    record_paid_event(
+        EventKind::PrepaidBalanceFunded {
+            owner,
+            cycles: args.cycles,
+        },
+        payment,
+        None,
+    );
+
+    SetCanisterResult::Ok()
+}
+
+/// Moves cycles from the caller's prepaid balance to a cycles ledger account.
+///
+/// The balance is debited before the deposit and credited back if the deposit fails.
+pub async fn withdraw_prepaid_balance(args: WithdrawPrepaidBalanceArgs) -> SetCanisterResult {
+    let owner = caller();
+    let WithdrawPrepaidBalanceArgs { cycles, to } = args;
+
+    if let Err(err) = debit(owner, cycles, PrepaidReason::Withdrawal { to }) {
+        return SetCanisterResult::Err(err);
+    }
+
+    let cycles_ledger = read_config(|config| config.cycles_ledger);
+
+    if let Err(err) = cycles_ledger::deposit(cycles_ledger, to, cycles.into()).await {
+        credit(owner, cycles, PrepaidReason::WithdrawalReverted);
+        return SetCanisterResult::Err(CreateCanisterError::WithdrawalFailed(err));
+    }
+
+    record_event(EventKind::PrepaidBalanceWithdrawn { owner, cycles, to });
+
+    SetCanisterResult::Ok()
+}
+
+/// Accepts the cycles attached to the current call beyond what has already been charged, and
+/// credits them to the caller's prepaid balance.
+///
+/// Must run before the call awaits any other canister, while the attached cycles are available.
+pub fn credit_excess_attached_cycles() {
+    let excess = msg_cycles_available128();
+
+    if excess == 0 {
+        return;
+    }
+
+    let accepted = msg_cycles_accept128(excess);
+    let cycles = u64::try_from(accepted).unwrap_or(u64::MAX);
+    let owner = caller();
+
+    credit(owner, cycles, PrepaidReason::ExcessAttachedCycles);
+
+    record_event(EventKind::ExcessCyclesCredited { owner, cycles });
+}
@@ -0,0 +1,90 @@
+use ic_cdk::api::time;
+
+use crate::{
+    events::record_event,
+    generic::MIN_CYCLES_FOR_CANISTER_CREATION,
+    state::{mutate_state, read_state},
+    types::{
+        args::pricing::SetPricingArgs,
+        candid::Candid,
+        event::EventKind,
+        pricing::Pricing,
+        results::create_canister::{CreateCanisterError, SetCanisterResult},
+    },
+};
+
+/// Largest service margin accepted, i.e. fees may at most be doubled.
+pub const MAX_MARGIN_BPS: u16 = 10_000;
+
+/// Returns the current pricing, falling back to the defaults until a controller changes it.
+#[must_use]
+pub fn get_pricing() -> Pricing {
+    read_state(|s| {
+        s.pricing
+            .get()
+            .as_ref()
+            .map(|Candid(pricing)| pricing.clone())
+            .unwrap_or_default()
+    })
+}
+
+pub fn set_pricing(args: SetPricingArgs) -> SetCanisterResult {
+    let pricing = match updated_pricing(&get_pricing(), args) {
+        Ok(pricing) => pricing,
+        Err(err) => return SetCanisterResult::Err(err),
+    };
+
+    mutate_state(|s| {
+        s.pricing.set(Some(Candid(pricing.clone())));
+    });
+
+    record_event(EventKind::PricingUpdated { pricing });
+
+    SetCanisterResult::Ok()
+}
+
+/// Applies `args` on top of `current`, keeping the current value of omitted fields.
+///
+/// # Errors
+/// - `InvalidPricing` if the resulting pricing is refused by [`validate_pricing`].
+pub fn updated_pricing(
+    current: &Pricing,
+    args: SetPricingArgs,
+) -> Result<Pricing, CreateCanisterError> {
+    let pricing = Pricing {
+        create_icrc_ledger: args
+            .create_icrc_ledger
+            .unwrap_or(current.create_icrc_ledger),
+        create_icrc_index: args.create_icrc_index.unwrap_or(current.create_icrc_index),
+        top_up_canister: args.top_up_canister.unwrap_or(current.top_up_canister),
+        margin_bps: args.margin_bps.unwrap_or(current.margin_bps),
+        updated_at: Some(time()),
+    };
+
+    validate_pricing(&pricing)?;
+
+    Ok(pricing)
+}
+
+/// Checks that a pricing never makes the factory pay for a creation out of its own balance.
+///
+/// # Errors
+/// - `InvalidPricing` if a creation fee does not cover the cycles the new canister starts with, or
+///   the margin exceeds [`MAX_MARGIN_BPS`].
+fn validate_pricing(pricing: &Pricing) -> Result<(), CreateCanisterError> {
+    if pricing.create_icrc_ledger < MIN_CYCLES_FOR_CANISTER_CREATION
+        || pricing.create_icrc_index < MIN_CYCLES_FOR_CANISTER_CREATION
+    {
+        return Err(CreateCanisterError::InvalidPricing(format!(
+            "creation fees must be at least {MIN_CYCLES_FOR_CANISTER_CREATION} cycles"
+        )));
+    }
+
+    if pricing.margin_bps > MAX_MARGIN_BPS {
+        return Err(CreateCanisterError::InvalidPricing(format!(
+            "margin_bps must be at most {MAX_MARGIN_BPS}"
+        )));
+    }
+
+    Ok(())
+}
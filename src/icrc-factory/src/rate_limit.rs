@@ -0,0 +1,167 @@
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, VecDeque},
+    time::Duration,
+};
+
+use candid::Principal;
+use ic_cdk::api::time;
+use ic_cdk_timers::set_timer_interval;
+
+use crate::{
+    state::{read_config, update_config},
+    types::{
+        rate_limit::{RateLimit, RateLimits},
+        results::create_canister::CreateCanisterError,
+    },
+};
+
+/// Interval at which the timestamps of calls out of every window are dropped.
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(60);
+
+const NANOS_PER_SEC: u64 = 1_000_000_000;
+
+thread_local! {
+    /// Timestamps of the rate-limited calls of each principal, oldest first.
+    static PRINCIPAL_CALLS: RefCell<BTreeMap<Principal, VecDeque<u64>>> =
+        const { RefCell::new(BTreeMap::new()) };
+
+    /// Timestamps of the rate-limited calls of all principals, oldest first.
+    static GLOBAL_CALLS: RefCell<VecDeque<u64>> = const { RefCell::new(VecDeque::new()) };
+}
+
+/// Returns the rate limits in force.
+#[must_use]
+pub fn rate_limits() -> RateLimits {
+    read_config(|config| config.rate_limits.unwrap_or_default())
+}
+
+/// Sets the rate limits, and records the new configuration.
+///
+/// # Errors
+/// - `InvalidRateLimit` if a limit allows no call or has an empty window.
+pub fn set_rate_limits(limits: RateLimits) -> Result<(), CreateCanisterError> {
+    for limit in [limits.per_principal, limits.global].into_iter().flatten() {
+        if limit.max_calls == 0 || limit.window_secs == 0 {
+            return Err(CreateCanisterError::InvalidRateLimit(
+                "max_calls and window_secs must be positive".to_string(),
+            ));
+        }
+    }
+
+    update_config(|config| config.rate_limits = Some(limits));
+
+    Ok(())
+}
+
+/// Counts a call of `caller` against the rate limits, or refuses it if a limit is reached.
+///
+/// Refused calls are not counted, so that retrying too early does not push the retry further back.
+/// Returns the time the call is counted at, with which [`release_rate_limit`] stops counting it.
+///
+/// # Errors
+/// - `RateLimited` with the number of seconds to wait if the caller or all principals together made
+///   too many calls within the window.
+pub fn check_rate_limit(caller: Principal) -> Result<u64, CreateCanisterError> {
+    let limits = rate_limits();
+    let now = time();
+
+    GLOBAL_CALLS.with(|global| {
+        PRINCIPAL_CALLS.with(|principals| {
+            let mut global = global.borrow_mut();
+            let mut principals = principals.borrow_mut();
+            let mut calls = limits
+                .per_principal
+                .map(|limit| (limit, principals.entry(caller).or_default()));
+
+            if let Some(limit) = limits.global {
+                check(&mut global, limit, now)?;
+            }
+            if let Some((limit, calls)) = &mut calls {
+                check(calls, *limit, now)?;
+            }
+
+            if limits.global.is_some() {
+                global.push_back(now);
+            }
+            if let Some((_, calls)) = calls {
+                calls.push_back(now);
+            }
+
+            Ok(now)
+        })
+    })
+}
+
+/// Stops counting a call of `caller` counted at `counted_at` by [`check_rate_limit`], for calls
+/// that did not go through, e.g. because their payment failed.
+pub fn release_rate_limit(caller: Principal, counted_at: u64) {
+    GLOBAL_CALLS.with(|global| forget(&mut global.borrow_mut(), counted_at));
+    PRINCIPAL_CALLS.with(|principals| {
+        let mut principals = principals.borrow_mut();
+        if let Some(calls) = principals.get_mut(&caller) {
+            forget(calls, counted_at);
+            if calls.is_empty() {
+                principals.remove(&caller);
+            }
+        }
+    });
+}
+
+/// Drops a call made at `timestamp`, unless it has already been pruned.
+fn forget(calls: &mut VecDeque<u64>, timestamp: u64) {
+    if let Some(position) = calls.iter().rposition(|call| *call == timestamp) {
+        calls.remove(position);
+    }
+}
+
+/// Drops the calls out of `limit`'s window, and refuses a new call if the window is full.
+fn check(calls: &mut VecDeque<u64>, limit: RateLimit, now: u64) -> Result<(), CreateCanisterError> {
+    let window = limit.window_secs.saturating_mul(NANOS_PER_SEC);
+    prune(calls, now, window);
+
+    if calls.len() < usize::try_from(limit.max_calls).unwrap_or(usize::MAX) {
+        return Ok(());
+    }
+
+    // The window is full, so it has a call, and frees up when its oldest call leaves it.
+    let oldest = calls.front().copied().unwrap_or(now);
+    let retry_after_nanos = oldest.saturating_add(window).saturating_sub(now);
+
+    Err(CreateCanisterError::RateLimited {
+        retry_after: retry_after_nanos.div_ceil(NANOS_PER_SEC),
+    })
+}
+
+/// Drops the calls made `window` nanoseconds or longer before `now`.
+fn prune(calls: &mut VecDeque<u64>, now: u64, window: u64) {
+    while calls
+        .front()
+        .is_some_and(|timestamp| timestamp.saturating_add(window) <= now)
+    {
+        calls.pop_front();
+    }
+}
+
+/// Periodically drops the calls out of every window, and forgets the principals without any call
+/// left. Timers do not survive upgrades, so this runs on every install and upgrade.
+pub fn start_rate_limit_cleanup_timer() {
+    set_timer_interval(CLEANUP_INTERVAL, cleanup);
+}
+
+fn cleanup() {
+    let limits = rate_limits();
+    let now = time();
+    let window_of = |limit: Option<RateLimit>| {
+        limit.map_or(0, |limit| limit.window_secs.saturating_mul(NANOS_PER_SEC))
+    };
+
+    GLOBAL_CALLS.with(|global| prune(&mut global.borrow_mut(), now, window_of(limits.global)));
+    PRINCIPAL_CALLS.with(|principals| {
+        let window = window_of(limits.per_principal);
+        principals.borrow_mut().retain(|_, calls| {
+            prune(calls, now, window);
+            !calls.is_empty()
+        });
+    });
+}
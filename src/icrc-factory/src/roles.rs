@@ -0,0 +1,75 @@
+use candid::Principal;
+use ic_cdk::api::is_controller;
+
+use crate::{
+    events::record_event,
+    state::{mutate_state, read_state},
+    types::{
+        candid::Candid,
+        event::EventKind,
+        role::{Role, RoleAssignment},
+        stored_principal::StoredPrincipal,
+    },
+};
+
+/// Whether `principal` holds `role`. Controllers hold every role.
+#[must_use]
+pub fn has_role(principal: Principal, role: Role) -> bool {
+    is_controller(&principal) || roles_of(principal).contains(&role)
+}
+
+/// Returns the roles granted to `principal`, in declaration order.
+#[must_use]
+pub fn roles_of(principal: Principal) -> Vec<Role> {
+    read_state(|s| s.roles.get(&StoredPrincipal(principal)))
+        .map_or_else(Vec::new, |Candid(roles)| roles)
+}
+
+/// Grants `role` to `principal`; granting a role the principal already holds changes nothing.
+pub fn grant_role(principal: Principal, role: Role) {
+    let mut roles = roles_of(principal);
+    if roles.contains(&role) {
+        return;
+    }
+
+    roles.push(role);
+    roles.sort();
+    mutate_state(|s| {
+        s.roles.insert(StoredPrincipal(principal), Candid(roles));
+    });
+
+    record_event(EventKind::RoleGranted { principal, role });
+}
+
+/// Revokes `role` from `principal`; revoking a role the principal does not hold changes nothing.
+pub fn revoke_role(principal: Principal, role: Role) {
+    let mut roles = roles_of(principal);
+    if !roles.contains(&role) {
+        return;
+    }
+
+    roles.retain(|r| *r != role);
+    mutate_state(|s| {
+        if roles.is_empty() {
+            s.roles.remove(&StoredPrincipal(principal));
+        } else {
+            s.roles.insert(StoredPrincipal(principal), Candid(roles));
+        }
+    });
+
+    record_event(EventKind::RoleRevoked { principal, role });
+}
+
+/// Returns every principal holding a role, with its roles.
+#[must_use]
+pub fn list_roles() -> Vec<RoleAssignment> {
+    read_state(|s| {
+        s.roles
+            .keys()
+            .filter_map(|StoredPrincipal(principal)| {
+                let Candid(roles) = s.roles.get(&StoredPrincipal(principal))?;
+                Some(RoleAssignment { principal, roles })
+            })
+            .collect()
+    })
+}
@@ -0,0 +1,121 @@
+use candid::{Nat, Principal};
+use ic_cdk::api::management_canister::main::{CanisterSettings, LogVisibility};
+
+use crate::types::{
+    args::create_canister::CanisterSettingsArgs, results::create_canister::CreateCanisterError,
+};
+
+/// Default freezing threshold of the IC: 30 days.
+pub const DEFAULT_FREEZING_THRESHOLD: u64 = 30 * 24 * 60 * 60;
+
+/// Lowering the freezing threshold below the IC default only makes a ledger easier to lose.
+pub const MIN_FREEZING_THRESHOLD: u64 = DEFAULT_FREEZING_THRESHOLD;
+pub const MAX_FREEZING_THRESHOLD: u64 = 365 * 24 * 60 * 60;
+
+/// Ledger and index canisters keep their state in stable memory; a small heap is enough but a
+/// limit below this could trap them during upgrades.
+pub const MIN_WASM_MEMORY_LIMIT: u64 = 256 * 1024 * 1024;
+pub const MAX_WASM_MEMORY_LIMIT: u64 = 4 * 1024 * 1024 * 1024;
+
+pub const MAX_RESERVED_CYCLES_LIMIT: u64 = 50_000_000_000_000;
+
+/// Compute allocations quickly become expensive; larger ones are better set by the owner.
+pub const MAX_COMPUTE_ALLOCATION: u64 = 10;
+
+/// Maximum number of principals allowed to read the canister logs, as enforced by the IC.
+pub const MAX_ALLOWED_LOG_VIEWERS: usize = 10;
+
+/// Cost, in cycles, of 1% of compute allocation during one second on a 13-node subnet.
+pub const COMPUTE_ALLOCATION_FEE_PER_PERCENT_SECOND: u64 = 10_000_000;
+
+/// Checks that the requested settings are within the bounds the factory accepts.
+///
+/// # Errors
+/// - `InvalidCanisterSettings` describing the first setting out of bounds.
+pub fn validate_canister_settings(
+    settings: &CanisterSettingsArgs,
+) -> Result<(), CreateCanisterError> {
+    let invalid = |msg: String| Err(CreateCanisterError::InvalidCanisterSettings(msg));
+
+    if let Some(freezing_threshold) = settings.freezing_threshold {
+        if !(MIN_FREEZING_THRESHOLD..=MAX_FREEZING_THRESHOLD).contains(&freezing_threshold) {
+            return invalid(format!(
+                "freezing_threshold must be between {MIN_FREEZING_THRESHOLD} and {MAX_FREEZING_THRESHOLD} seconds"
+            ));
+        }
+    }
+
+    if let Some(wasm_memory_limit) = settings.wasm_memory_limit {
+        if !(MIN_WASM_MEMORY_LIMIT..=MAX_WASM_MEMORY_LIMIT).contains(&wasm_memory_limit) {
+            return invalid(format!(
+                "wasm_memory_limit must be between {MIN_WASM_MEMORY_LIMIT} and {MAX_WASM_MEMORY_LIMIT} bytes"
+            ));
+        }
+    }
+
+    if let Some(reserved_cycles_limit) = settings.reserved_cycles_limit {
+        if reserved_cycles_limit > MAX_RESERVED_CYCLES_LIMIT {
+            return invalid(format!(
+                "reserved_cycles_limit must not exceed {MAX_RESERVED_CYCLES_LIMIT} cycles"
+            ));
+        }
+    }
+
+    if let Some(compute_allocation) = settings.compute_allocation {
+        if compute_allocation > MAX_COMPUTE_ALLOCATION {
+            return invalid(format!(
+                "compute_allocation must not exceed {MAX_COMPUTE_ALLOCATION}%"
+            ));
+        }
+    }
+
+    if let Some(LogVisibility::AllowedViewers(viewers)) = &settings.log_visibility {
+        if viewers.len() > MAX_ALLOWED_LOG_VIEWERS {
+            return invalid(format!(
+                "log_visibility must not list more than {MAX_ALLOWED_LOG_VIEWERS} viewers"
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Builds the management canister settings of a new canister controlled by `controllers`.
+#[must_use]
+pub fn canister_settings(
+    controllers: Vec<Principal>,
+    settings: Option<CanisterSettingsArgs>,
+) -> CanisterSettings {
+    let settings = settings.unwrap_or_default();
+
+    CanisterSettings {
+        controllers: Some(controllers),
+        compute_allocation: settings.compute_allocation.map(Nat::from),
+        memory_allocation: None,
+        freezing_threshold: settings.freezing_threshold.map(Nat::from),
+        reserved_cycles_limit: settings.reserved_cycles_limit.map(Nat::from),
+        log_visibility: settings.log_visibility,
+        wasm_memory_limit: settings.wasm_memory_limit.map(Nat::from),
+    }
+}
+
+/// Cycles needed on top of the base fee to keep the requested allocations above the freezing
+/// threshold.
+///
+/// A canister with a compute allocation burns cycles for it even when idle, and is frozen unless
+/// its balance covers that burn for the whole freezing threshold.
+#[must_use]
+pub fn allocation_fee(settings: Option<&CanisterSettingsArgs>) -> u64 {
+    let Some(settings) = settings else {
+        return 0;
+    };
+
+    let compute_allocation = settings.compute_allocation.unwrap_or(0);
+    let freezing_threshold = settings
+        .freezing_threshold
+        .unwrap_or(DEFAULT_FREEZING_THRESHOLD);
+
+    compute_allocation
+        .saturating_mul(COMPUTE_ALLOCATION_FEE_PER_PERCENT_SECOND)
+        .saturating_mul(freezing_threshold)
+}
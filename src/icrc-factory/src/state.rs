@@ -4,19 +4,91 @@ use candid::Principal;
 use ic_papi_guard::guards::any::{PaymentGuard, VendorPaymentConfig};
 use ic_stable_structures::{
     memory_manager::{MemoryId, MemoryManager},
-    DefaultMemoryImpl,
+    DefaultMemoryImpl, Memory,
 };
 
-use crate::types::{
-    candid::Candid,
-    config::{Config, InitArgs},
-    memory::{ConfigCell, IcrcLedgerWasmCell, UserCanisterMap},
+use crate::{
+    certification::certify_config,
+    events::record_event,
+    payment::supported_payment_methods,
+    types::{
+        candid::Candid,
+        config::{Config, InitArgs, CMC_CANISTER_ID, ICP_LEDGER_CANISTER_ID},
+        event::EventKind,
+        memory::{
+            AccessListMap, AutoTopUpMap, BlockLog, CanisterOwnerMap, ConfigCell,
+            CreationOutcomeMap, CustomPricingMap, EventLog, FreeQuotaMap, IcrcLedgerWasmCell,
+            LogBuffer, PaymentTotalMap, PendingIcpTopUpMap, PrepaidBalanceMap, PrepaidJournal,
+            PricingCell, PromoCodeMap, PromoCodeRedemptionLog, RoleMap, TokenMetadataMap,
+            UserCanisterMap,
+        },
+    },
 };
 
 const CONFIG_MEMORY_ID: MemoryId = MemoryId::new(0);
 const ICRC_LEDGER_WASM_MEMORY_ID: MemoryId = MemoryId::new(2);
 const ICRC_INDEX_WASM_MEMORY_ID: MemoryId = MemoryId::new(3);
 const USER_CANISTER_MEMORY_ID: MemoryId = MemoryId::new(4);
+const EVENT_LOG_INDEX_MEMORY_ID: MemoryId = MemoryId::new(5);
+const EVENT_LOG_DATA_MEMORY_ID: MemoryId = MemoryId::new(6);
+const CANISTER_OWNER_MEMORY_ID: MemoryId = MemoryId::new(7);
+const AUTO_TOP_UP_MEMORY_ID: MemoryId = MemoryId::new(8);
+const PREPAID_BALANCE_MEMORY_ID: MemoryId = MemoryId::new(9);
+const PRICING_MEMORY_ID: MemoryId = MemoryId::new(10);
+const PREPAID_JOURNAL_INDEX_MEMORY_ID: MemoryId = MemoryId::new(11);
+const PREPAID_JOURNAL_DATA_MEMORY_ID: MemoryId = MemoryId::new(12);
+const FREE_QUOTA_MEMORY_ID: MemoryId = MemoryId::new(13);
+const PROMO_CODE_MEMORY_ID: MemoryId = MemoryId::new(14);
+const CUSTOM_PRICING_MEMORY_ID: MemoryId = MemoryId::new(15);
+const PROMO_CODE_REDEMPTION_INDEX_MEMORY_ID: MemoryId = MemoryId::new(16);
+const PROMO_CODE_REDEMPTION_DATA_MEMORY_ID: MemoryId = MemoryId::new(17);
+const BLOCK_LOG_INDEX_MEMORY_ID: MemoryId = MemoryId::new(18);
+const BLOCK_LOG_DATA_MEMORY_ID: MemoryId = MemoryId::new(19);
+const CREATION_OUTCOME_MEMORY_ID: MemoryId = MemoryId::new(20);
+const PAYMENT_TOTAL_MEMORY_ID: MemoryId = MemoryId::new(21);
+const TOKEN_METADATA_MEMORY_ID: MemoryId = MemoryId::new(22);
+const LOG_MEMORY_ID: MemoryId = MemoryId::new(23);
+const ROLE_MEMORY_ID: MemoryId = MemoryId::new(24);
+const CREATION_ALLOWLIST_MEMORY_ID: MemoryId = MemoryId::new(25);
+const CREATION_DENYLIST_MEMORY_ID: MemoryId = MemoryId::new(26);
+const PENDING_ICP_TOP_UP_MEMORY_ID: MemoryId = MemoryId::new(27);
+
+/// Every memory of the state, with the name its size is reported under in the metrics.
+const MEMORIES: [(&str, MemoryId); 27] = [
+    ("config", CONFIG_MEMORY_ID),
+    ("icrc_ledger_wasm", ICRC_LEDGER_WASM_MEMORY_ID),
+    ("icrc_index_wasm", ICRC_INDEX_WASM_MEMORY_ID),
+    ("user_canister", USER_CANISTER_MEMORY_ID),
+    ("event_log_index", EVENT_LOG_INDEX_MEMORY_ID),
+    ("event_log_data", EVENT_LOG_DATA_MEMORY_ID),
+    ("canister_owner", CANISTER_OWNER_MEMORY_ID),
+    ("auto_top_up", AUTO_TOP_UP_MEMORY_ID),
+    ("prepaid_balance", PREPAID_BALANCE_MEMORY_ID),
+    ("pricing", PRICING_MEMORY_ID),
+    ("prepaid_journal_index", PREPAID_JOURNAL_INDEX_MEMORY_ID),
+    ("prepaid_journal_data", PREPAID_JOURNAL_DATA_MEMORY_ID),
+    ("free_quota", FREE_QUOTA_MEMORY_ID),
+    ("promo_code", PROMO_CODE_MEMORY_ID),
+    ("custom_pricing", CUSTOM_PRICING_MEMORY_ID),
+    (
+        "promo_code_redemption_index",
+        PROMO_CODE_REDEMPTION_INDEX_MEMORY_ID,
+    ),
+    (
+        "promo_code_redemption_data",
+        PROMO_CODE_REDEMPTION_DATA_MEMORY_ID,
+    ),
+    ("block_log_index", BLOCK_LOG_INDEX_MEMORY_ID),
+    ("block_log_data", BLOCK_LOG_DATA_MEMORY_ID),
+    ("creation_outcome", CREATION_OUTCOME_MEMORY_ID),
+    ("payment_total", PAYMENT_TOTAL_MEMORY_ID),
+    ("token_metadata", TOKEN_METADATA_MEMORY_ID),
+    ("log", LOG_MEMORY_ID),
+    ("role", ROLE_MEMORY_ID),
+    ("creation_allowlist", CREATION_ALLOWLIST_MEMORY_ID),
+    ("creation_denylist", CREATION_DENYLIST_MEMORY_ID),
+    ("pending_icp_top_up", PENDING_ICP_TOP_UP_MEMORY_ID),
+];
 
 thread_local! {
     static MEMORY_MANAGER: RefCell<MemoryManager<DefaultMemoryImpl>> = RefCell::new(
@@ -29,6 +101,25 @@ thread_local! {
             icrc_ledger_wasm: IcrcLedgerWasmCell::init(mm.borrow().get(ICRC_LEDGER_WASM_MEMORY_ID), Vec::new()),
             icrc_index_wasm: IcrcLedgerWasmCell::init(mm.borrow().get(ICRC_INDEX_WASM_MEMORY_ID),Vec::new()),
             user_canister: UserCanisterMap::init(mm.borrow().get(USER_CANISTER_MEMORY_ID)),
+            events: EventLog::init(mm.borrow().get(EVENT_LOG_INDEX_MEMORY_ID), mm.borrow().get(EVENT_LOG_DATA_MEMORY_ID)),
+            canister_owners: CanisterOwnerMap::init(mm.borrow().get(CANISTER_OWNER_MEMORY_ID)),
+            auto_top_ups: AutoTopUpMap::init(mm.borrow().get(AUTO_TOP_UP_MEMORY_ID)),
+            prepaid_balances: PrepaidBalanceMap::init(mm.borrow().get(PREPAID_BALANCE_MEMORY_ID)),
+            pricing: PricingCell::init(mm.borrow().get(PRICING_MEMORY_ID), None),
+            prepaid_journal: PrepaidJournal::init(mm.borrow().get(PREPAID_JOURNAL_INDEX_MEMORY_ID), mm.borrow().get(PREPAID_JOURNAL_DATA_MEMORY_ID)),
+            free_quotas: FreeQuotaMap::init(mm.borrow().get(FREE_QUOTA_MEMORY_ID)),
+            promo_codes: PromoCodeMap::init(mm.borrow().get(PROMO_CODE_MEMORY_ID)),
+            custom_pricing: CustomPricingMap::init(mm.borrow().get(CUSTOM_PRICING_MEMORY_ID)),
+            promo_code_redemptions: PromoCodeRedemptionLog::init(mm.borrow().get(PROMO_CODE_REDEMPTION_INDEX_MEMORY_ID), mm.borrow().get(PROMO_CODE_REDEMPTION_DATA_MEMORY_ID)),
+            blocks: BlockLog::init(mm.borrow().get(BLOCK_LOG_INDEX_MEMORY_ID), mm.borrow().get(BLOCK_LOG_DATA_MEMORY_ID)),
+            creation_outcomes: CreationOutcomeMap::init(mm.borrow().get(CREATION_OUTCOME_MEMORY_ID)),
+            payment_totals: PaymentTotalMap::init(mm.borrow().get(PAYMENT_TOTAL_MEMORY_ID)),
+            token_metadata: TokenMetadataMap::init(mm.borrow().get(TOKEN_METADATA_MEMORY_ID)),
+            logs: LogBuffer::init(mm.borrow().get(LOG_MEMORY_ID)),
+            roles: RoleMap::init(mm.borrow().get(ROLE_MEMORY_ID)),
+            creation_allowlist: AccessListMap::init(mm.borrow().get(CREATION_ALLOWLIST_MEMORY_ID)),
+            creation_denylist: AccessListMap::init(mm.borrow().get(CREATION_DENYLIST_MEMORY_ID)),
+            pending_icp_top_ups: PendingIcpTopUpMap::init(mm.borrow().get(PENDING_ICP_TOP_UP_MEMORY_ID)),
         })
     );
 }
@@ -38,6 +129,36 @@ pub struct State {
     pub icrc_ledger_wasm: IcrcLedgerWasmCell,
     pub icrc_index_wasm: IcrcLedgerWasmCell,
     pub user_canister: UserCanisterMap,
+    pub events: EventLog,
+    pub canister_owners: CanisterOwnerMap,
+    pub auto_top_ups: AutoTopUpMap,
+    pub prepaid_balances: PrepaidBalanceMap,
+    pub pricing: PricingCell,
+    pub prepaid_journal: PrepaidJournal,
+    pub free_quotas: FreeQuotaMap,
+    pub promo_codes: PromoCodeMap,
+    pub custom_pricing: CustomPricingMap,
+    pub promo_code_redemptions: PromoCodeRedemptionLog,
+    pub blocks: BlockLog,
+    pub creation_outcomes: CreationOutcomeMap,
+    pub payment_totals: PaymentTotalMap,
+    pub token_metadata: TokenMetadataMap,
+    pub logs: LogBuffer,
+    pub roles: RoleMap,
+    pub creation_allowlist: AccessListMap,
+    pub creation_denylist: AccessListMap,
+    pub pending_icp_top_ups: PendingIcpTopUpMap,
+}
+
+/// Size, in WASM pages of 64 KiB, of every memory of the state, by name.
+pub fn memory_pages() -> Vec<(&'static str, u64)> {
+    MEMORY_MANAGER.with(|mm| {
+        let mm = mm.borrow();
+        MEMORIES
+            .iter()
+            .map(|(name, memory_id)| (*name, mm.get(*memory_id).size()))
+            .collect()
+    })
 }
 
 pub fn read_state<R>(f: impl FnOnce(&State) -> R) -> R {
@@ -62,28 +183,64 @@ pub fn read_config<R>(f: impl FnOnce(&Config) -> R) -> R {
     })
 }
 
+/// Stores the configuration, certifies it, and records the change. The pause flags, rate limits
+/// and creation access set by admins are kept.
 pub fn set_config(arg: InitArgs) {
     let config = Config::from(arg);
+    let stored = read_state(|state| {
+        state
+            .config
+            .get()
+            .as_ref()
+            .map(|Candid(config)| config.clone())
+    });
+
+    store_config(match stored {
+        Some(stored) => Config {
+            paused: stored.paused,
+            rate_limits: stored.rate_limits,
+            creation_access: stored.creation_access,
+            ..config
+        },
+        None => config,
+    });
+}
+
+/// Changes the stored configuration, certifies it, and records the change.
+///
+/// # Panics
+/// - If the `STATE.config` is not initialized.
+pub fn update_config(f: impl FnOnce(&mut Config)) {
+    let mut config = read_config(Clone::clone);
+    f(&mut config);
+
+    store_config(config);
+}
+
+fn store_config(config: Config) {
     mutate_state(|state| {
-        state.config.set(Some(Candid(config)));
+        state.config.set(Some(Candid(config.clone())));
     });
+    certify_config(&config);
+
+    record_event(EventKind::ConfigUpdated { config });
 }
 
 pub static PAYMENT_GUARD: LazyLock<PaymentGuard<5>> = LazyLock::new(|| PaymentGuard {
-    supported: [
-        VendorPaymentConfig::AttachedCycles,
-        VendorPaymentConfig::CallerPaysIcrc2Cycles,
-        VendorPaymentConfig::PatronPaysIcrc2Cycles,
-        VendorPaymentConfig::CallerPaysIcrc2Tokens {
-            ledger: payment_ledger(),
-        },
-        VendorPaymentConfig::PatronPaysIcrc2Tokens {
-            ledger: payment_ledger(),
-        },
-    ],
+    supported: supported_payment_methods().map(VendorPaymentConfig::from),
 });
 
 /// Provides the canister id of the ledger used for payments.
 pub fn payment_ledger() -> Principal {
     read_config(|config| config.cycles_ledger)
 }
+
+/// Provides the canister id of the ICP ledger used for ICP payments.
+pub fn icp_ledger() -> Principal {
+    read_config(|config| config.icp_ledger.unwrap_or(ICP_LEDGER_CANISTER_ID))
+}
+
+/// Provides the canister id of the CMC that converts ICP payments to cycles.
+pub fn cmc_canister() -> Principal {
+    read_config(|config| config.cmc.unwrap_or(CMC_CANISTER_ID))
+}
@@ -0,0 +1,78 @@
+use candid::Principal;
+use futures::future::join_all;
+use ic_cdk::caller;
+
+use crate::{
+    mgmt::get_canister_status,
+    state::read_state,
+    types::{
+        candid::Candid,
+        canister_status::CanisterStatus,
+        results::{
+            canister_status::{CanisterStatusEntry, CanisterStatusResult},
+            create_canister::CreateCanisterError,
+        },
+        stored_principal::StoredPrincipal,
+        user_canister::UserCanister,
+    },
+    user_canister::get_managed_user_canister,
+};
+
+/// Number of `canister_status` calls [`canister_statuses`] awaits together.
+const STATUS_BATCH_SIZE: usize = 50;
+
+pub async fn canister_status(canister_id: Principal) -> CanisterStatusResult {
+    if let Err(err) = read_state(|s| get_managed_user_canister(s, caller(), canister_id)) {
+        return CanisterStatusResult::Err(err);
+    }
+
+    fetch_status(canister_id).await
+}
+
+pub async fn canister_statuses() -> Vec<CanisterStatusEntry> {
+    let owner = caller();
+
+    let canisters = read_state(|s| {
+        s.user_canister
+            .get(&StoredPrincipal(owner))
+            .map(|Candid(canisters)| canisters)
+            .unwrap_or_default()
+    });
+
+    let mut entries = Vec::with_capacity(canisters.len());
+
+    // The calls of a batch are in flight together, so a refresh takes one round trip per batch
+    // rather than one per canister, without flooding the management canister.
+    for batch in canisters.chunks(STATUS_BATCH_SIZE) {
+        entries.extend(join_all(batch.iter().map(status_entry)).await);
+    }
+
+    entries
+}
+
+async fn status_entry(canister: &UserCanister) -> CanisterStatusEntry {
+    let canister_id = canister.canister_id;
+
+    // The factory can only read the status of canisters it still controls.
+    let result = if canister.is_deleted() {
+        CanisterStatusResult::Err(CreateCanisterError::Deleted)
+    } else if canister.is_immutable() {
+        CanisterStatusResult::Err(CreateCanisterError::Immutable)
+    } else if canister.is_sovereign() {
+        CanisterStatusResult::Err(CreateCanisterError::Sovereign)
+    } else {
+        fetch_status(canister_id).await
+    };
+
+    CanisterStatusEntry {
+        canister_id,
+        result,
+    }
+}
+
+async fn fetch_status(canister_id: Principal) -> CanisterStatusResult {
+    match get_canister_status(canister_id).await {
+        Ok(response) => CanisterStatusResult::Ok(CanisterStatus::new(canister_id, response)),
+        Err(err) => CanisterStatusResult::Err(CreateCanisterError::CanisterStatusFailed(err)),
+    }
+}
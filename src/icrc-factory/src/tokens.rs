@@ -0,0 +1,416 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::BTreeMap,
+    time::Duration,
+};
+
+use candid::Principal;
+use ic_cdk::api::time;
+use ic_cdk_timers::set_timer;
+use icrc_ledger_types::icrc::generic_metadata_value::MetadataValue;
+use serde::Serialize;
+
+use crate::{
+    certification::certify_http_responses,
+    state::{mutate_state, read_state, State},
+    types::{
+        candid::Candid,
+        results::create_canister::CreateCanisterError,
+        stored_principal::StoredPrincipal,
+        token::TokenMetadata,
+        user_canister::{UserCanister, UserCanisterKind},
+    },
+};
+
+/// Maximum number of tokens on a page of the token list.
+const TOKENS_PER_PAGE: usize = 100;
+
+/// Maximum size, in bytes, of the tokens on a page of the token list, well within the limit of
+/// query responses.
+const MAX_TOKEN_BYTES_PER_PAGE: usize = 1024 * 1024;
+
+/// Maximum length, in bytes, of a token logo.
+pub const MAX_LOGO_LENGTH: usize = 8 * 1024;
+
+/// Chain ID of the listed tokens. The IC has no EIP-155 chain ID, so the list uses 223, the
+/// SLIP-44 coin type of ICP.
+const IC_CHAIN_ID: u32 = 223;
+
+/// Maximum length of a token name, and the characters it may hold besides ASCII alphanumerics,
+/// per the token list schema.
+const MAX_TOKEN_NAME_LENGTH: usize = 40;
+const TOKEN_NAME_SYMBOLS: &str = " _.'+-%/:&[]()";
+
+/// Maximum length of a token symbol, per the token list schema.
+const MAX_TOKEN_SYMBOL_LENGTH: usize = 20;
+
+/// Path of the first page of the token list; page `n` is served at `/tokens/{n}.json`.
+pub const TOKEN_LIST_PATH: &str = "/tokens.json";
+
+/// Path of the index of the token list, which links every page.
+pub const TOKEN_LIST_INDEX_PATH: &str = "/tokens/index.json";
+
+const TOKEN_LIST_NAME: &str = "ICRC Factory";
+
+const TOKEN_LIST_VERSION: Version = Version {
+    major: 1,
+    minor: 0,
+    patch: 0,
+};
+
+thread_local! {
+    /// Pages of the token list, by path, as served and certified.
+    static PAGES: RefCell<BTreeMap<String, Vec<u8>>> = const { RefCell::new(BTreeMap::new()) };
+
+    /// Whether a refresh of the token list is already scheduled.
+    static REFRESH_SCHEDULED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// A page of the token list, following the schema of the token lists ingested by wallets, with the
+/// ledger ID as the token address.
+#[derive(Serialize)]
+struct TokenList<'a> {
+    name: &'static str,
+    timestamp: &'a str,
+    version: Version,
+    tokens: &'a [Token],
+}
+
+/// The index of the token list: the number of listed tokens and the path of every page, in order.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TokenListIndex<'a> {
+    name: &'static str,
+    timestamp: &'a str,
+    version: Version,
+    token_count: usize,
+    pages: Vec<String>,
+}
+
+#[derive(Serialize, Clone, Copy)]
+struct Version {
+    major: u32,
+    minor: u32,
+    patch: u32,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Token {
+    chain_id: u32,
+    address: String,
+    name: String,
+    symbol: String,
+    decimals: u8,
+    #[serde(rename = "logoURI", skip_serializing_if = "Option::is_none")]
+    logo_uri: Option<String>,
+    extensions: TokenExtensions,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TokenExtensions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    index_canister_id: Option<String>,
+    owner: String,
+}
+
+/// Stores the metadata of a ledger, and refreshes the token list.
+pub fn set_token_metadata(ledger_id: Principal, metadata: TokenMetadata) {
+    mutate_state(|s| {
+        s.token_metadata
+            .insert(StoredPrincipal(ledger_id), Candid(metadata));
+    });
+
+    schedule_token_list_refresh();
+}
+
+/// Updates the stored metadata of a ledger, if any, and refreshes the token list.
+pub fn update_token_metadata(ledger_id: Principal, f: impl FnOnce(&mut TokenMetadata)) {
+    let Some(Candid(mut metadata)) =
+        read_state(|s| s.token_metadata.get(&StoredPrincipal(ledger_id)))
+    else {
+        return;
+    };
+
+    f(&mut metadata);
+    set_token_metadata(ledger_id, metadata);
+}
+
+/// Returns the body of the token list page served at `path`, if any.
+#[must_use]
+pub fn token_list_page(path: &str) -> Option<Vec<u8>> {
+    PAGES.with(|pages| pages.borrow().get(path).cloned())
+}
+
+/// Refreshes the token list once the current message has committed its changes to the registry.
+///
+/// Registry entries are changed within `mutate_state`, where the state cannot be read again, so
+/// the refresh is deferred to a timer; changes made within the same round are refreshed once.
+pub fn schedule_token_list_refresh() {
+    if REFRESH_SCHEDULED.with(|scheduled| scheduled.replace(true)) {
+        return;
+    }
+
+    set_timer(Duration::ZERO, || {
+        REFRESH_SCHEDULED.with(|scheduled| scheduled.set(false));
+        refresh_token_list();
+    });
+}
+
+/// Builds every page of the token list, and its index, from the registry and the stored metadata,
+/// and certifies them.
+///
+/// The schema requires a list to hold a token, so no page is served while no token is listed.
+pub fn refresh_token_list() {
+    let pages = paginate(read_state(listed_tokens));
+    let timestamp = iso8601(time());
+
+    let mut bodies: BTreeMap<String, Vec<u8>> = pages
+        .iter()
+        .enumerate()
+        .map(|(page, tokens)| {
+            let list = TokenList {
+                name: TOKEN_LIST_NAME,
+                timestamp: &timestamp,
+                version: TOKEN_LIST_VERSION,
+                tokens,
+            };
+            (page_path(page), json(&list))
+        })
+        .collect();
+
+    let index = TokenListIndex {
+        name: TOKEN_LIST_NAME,
+        timestamp: &timestamp,
+        version: TOKEN_LIST_VERSION,
+        token_count: pages.iter().map(Vec::len).sum(),
+        pages: (0..pages.len()).map(page_path).collect(),
+    };
+    bodies.insert(TOKEN_LIST_INDEX_PATH.to_string(), json(&index));
+
+    certify_http_responses(
+        bodies
+            .iter()
+            .map(|(path, body)| (path.as_str(), body.as_slice())),
+    );
+    PAGES.with(|pages| *pages.borrow_mut() = bodies);
+}
+
+/// Splits `tokens` into pages of at most `TOKENS_PER_PAGE` tokens and `MAX_TOKEN_BYTES_PER_PAGE`
+/// bytes of tokens.
+fn paginate(tokens: Vec<Token>) -> Vec<Vec<Token>> {
+    let mut pages = Vec::new();
+    let mut page = Vec::new();
+    let mut page_bytes = 0;
+
+    for token in tokens {
+        // The token and the comma separating it from the next one.
+        let token_bytes = json(&token).len() + 1;
+
+        if page.len() == TOKENS_PER_PAGE
+            || (!page.is_empty() && page_bytes + token_bytes > MAX_TOKEN_BYTES_PER_PAGE)
+        {
+            pages.push(std::mem::take(&mut page));
+            page_bytes = 0;
+        }
+
+        page_bytes += token_bytes;
+        page.push(token);
+    }
+
+    if !page.is_empty() {
+        pages.push(page);
+    }
+
+    pages
+}
+
+fn json(value: &impl Serialize) -> Vec<u8> {
+    serde_json::to_vec(value).expect("encoding should always succeed")
+}
+
+/// Checks the logo of a new ledger: at most `MAX_LOGO_LENGTH` bytes, and a `data:` or `https:`
+/// URI, so that it can be listed as the `logoURI` of the token.
+///
+/// # Errors
+/// - `InvalidLogo` describing why the logo is refused.
+pub fn validate_logo(logo: &str) -> Result<(), CreateCanisterError> {
+    if logo.len() > MAX_LOGO_LENGTH {
+        return Err(CreateCanisterError::InvalidLogo(format!(
+            "The logo must be at most {MAX_LOGO_LENGTH} bytes long"
+        )));
+    }
+
+    if !is_logo_uri(logo) {
+        return Err(CreateCanisterError::InvalidLogo(
+            "The logo must be a data: or https: URI".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Whether `logo` is a `data:` or `https:` URI made only of the characters URIs may hold.
+fn is_logo_uri(logo: &str) -> bool {
+    (logo.starts_with("data:") || logo.starts_with("https://"))
+        && logo
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"-._~:/?#[]@!$&'()*+,;=%".contains(&b))
+}
+
+/// Whether the name and symbol of a token fit the token list schema.
+fn is_listable(metadata: &TokenMetadata) -> bool {
+    let name_fits = (1..=MAX_TOKEN_NAME_LENGTH).contains(&metadata.name.chars().count())
+        && metadata.name.chars().all(|c| {
+            c.is_ascii_alphanumeric()
+                || TOKEN_NAME_SYMBOLS.contains(c)
+                // Latin-1 letters, from À to ÿ but for × and ÷.
+                || (('\u{c0}'..='\u{ff}').contains(&c) && c != '\u{d7}' && c != '\u{f7}')
+        });
+    let symbol_fits = (1..=MAX_TOKEN_SYMBOL_LENGTH).contains(&metadata.symbol.chars().count())
+        && !metadata.symbol.chars().any(char::is_whitespace);
+
+    name_fits && symbol_fits
+}
+
+/// Reads the metadata of installed ledgers that have none stored, e.g. ledgers created before the
+/// token list existed, from the ledgers themselves.
+pub fn schedule_token_metadata_backfill() {
+    set_timer(Duration::ZERO, || ic_cdk::spawn(backfill_token_metadata()));
+}
+
+async fn backfill_token_metadata() {
+    let ledger_ids: Vec<Principal> = read_state(|s| {
+        registered_ledgers(s)
+            .into_iter()
+            .map(|(_, ledger)| ledger.canister_id)
+            .filter(|ledger_id| !s.token_metadata.contains_key(&StoredPrincipal(*ledger_id)))
+            .collect()
+    });
+
+    for ledger_id in ledger_ids {
+        // Ledgers that cannot be read are left out of the list until the next upgrade.
+        let Ok((metadata,)) =
+            ic_cdk::call::<(), (Vec<(String, MetadataValue)>,)>(ledger_id, "icrc1_metadata", ())
+                .await
+        else {
+            continue;
+        };
+
+        if let Some(metadata) = token_metadata(&metadata) {
+            set_token_metadata(ledger_id, metadata);
+        }
+    }
+}
+
+/// Token metadata from the `icrc1_metadata` of a ledger, or `None` if a required entry is missing.
+fn token_metadata(metadata: &[(String, MetadataValue)]) -> Option<TokenMetadata> {
+    let text = |key: &str| {
+        metadata.iter().find_map(|(k, v)| match v {
+            MetadataValue::Text(text) if k == key => Some(text.clone()),
+            _ => None,
+        })
+    };
+    let decimals = metadata.iter().find_map(|(k, v)| match v {
+        MetadataValue::Nat(decimals) if k == "icrc1:decimals" => {
+            u8::try_from(decimals.0.clone()).ok()
+        }
+        _ => None,
+    })?;
+
+    Some(TokenMetadata {
+        symbol: text("icrc1:symbol")?,
+        name: text("icrc1:name")?,
+        decimals,
+        logo: text("icrc1:logo"),
+        index_id: None,
+    })
+}
+
+/// Installed, not deleted ledgers of the registry, with their owner.
+fn registered_ledgers(s: &State) -> Vec<(Principal, UserCanister)> {
+    s.user_canister
+        .keys()
+        .filter_map(|owner| s.user_canister.get(&owner).map(|Candid(c)| (owner.0, c)))
+        .flat_map(|(owner, canisters)| canisters.into_iter().map(move |c| (owner, c)))
+        .filter(|(_, c)| c.kind == UserCanisterKind::IcrcLedger && c.installed && !c.is_deleted())
+        .collect()
+}
+
+/// Tokens of the registered ledgers with stored metadata, sorted by ledger ID.
+///
+/// Tokens whose name or symbol does not fit the token list schema are left out, and logos that do
+/// not, e.g. long logos read from ledgers created before logos were checked, are not listed.
+fn listed_tokens(s: &State) -> Vec<Token> {
+    let mut ledgers = registered_ledgers(s);
+    ledgers.sort_by_key(|(_, ledger)| ledger.canister_id);
+
+    ledgers
+        .into_iter()
+        .filter_map(|(owner, ledger)| {
+            let Candid(metadata) = s.token_metadata.get(&StoredPrincipal(ledger.canister_id))?;
+            if !is_listable(&metadata) {
+                return None;
+            }
+
+            Some(Token {
+                chain_id: IC_CHAIN_ID,
+                address: ledger.canister_id.to_text(),
+                name: metadata.name,
+                symbol: metadata.symbol,
+                decimals: metadata.decimals,
+                logo_uri: metadata
+                    .logo
+                    .filter(|logo| logo.len() <= MAX_LOGO_LENGTH && is_logo_uri(logo)),
+                extensions: TokenExtensions {
+                    index_canister_id: metadata.index_id.as_ref().map(Principal::to_text),
+                    owner: owner.to_text(),
+                },
+            })
+        })
+        .collect()
+}
+
+fn page_path(page: usize) -> String {
+    if page == 0 {
+        TOKEN_LIST_PATH.to_string()
+    } else {
+        format!("/tokens/{page}.json")
+    }
+}
+
+/// Formats a timestamp in nanoseconds since the epoch as an ISO 8601 UTC date and time.
+fn iso8601(nanos: u64) -> String {
+    let secs = nanos / 1_000_000_000;
+    let (days, secs) = (secs / 86_400, secs % 86_400);
+    let (year, month, day) = civil_from_days(days);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        secs / 3_600,
+        secs % 3_600 / 60,
+        secs % 60
+    )
+}
+
+/// Gregorian calendar date of a number of days since 1970-01-01.
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    // Days since 0000-03-01, so that leap days fall at the end of a year.
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = era * 400 + year_of_era + u64::from(month <= 2);
+
+    (year, month, day)
+}
@@ -0,0 +1,76 @@
+use ic_cdk::caller;
+
+use crate::{
+    events::record_paid_event,
+    mgmt::deposit_cycles_to,
+    prepaid::credit,
+    state::read_state,
+    types::{
+        args::top_up::TopUpCanisterArgs,
+        event::{EventKind, EventPayment},
+        prepaid::PrepaidReason,
+        results::create_canister::{CreateCanisterError, SetCanisterResult},
+    },
+    user_canister::find_user_canister,
+};
+
+/// Smallest top-up accepted, so that the service fee stays small in comparison.
+pub const MIN_TOP_UP_CYCLES: u64 = 100_000_000_000;
+
+/// Checks that a top-up can be performed before the caller is charged for it.
+///
+/// # Errors
+/// - `InvalidTopUpAmount` if fewer than [`MIN_TOP_UP_CYCLES`] cycles are requested.
+/// - `CanisterNotFound` if the canister is not in the factory registry.
+/// - `Deleted` if the canister has been deleted.
+pub fn validate_top_up(args: &TopUpCanisterArgs) -> Result<(), CreateCanisterError> {
+    if args.cycles < MIN_TOP_UP_CYCLES {
+        return Err(CreateCanisterError::InvalidTopUpAmount);
+    }
+
+    match read_state(|s| find_user_canister(s, args.canister_id)) {
+        None => Err(CreateCanisterError::CanisterNotFound),
+        Some((_, canister)) if canister.is_deleted() => Err(CreateCanisterError::Deleted),
+        Some(_) => Ok(()),
+    }
+}
+
+/// Deposits already paid-for cycles into a registered canister, and records the top-up with its
+/// payment and outcome.
+///
+/// If the deposit fails, the whole amount charged, service fee included, is credited to the
+/// caller's prepaid balance.
+pub async fn top_up_canister(args: TopUpCanisterArgs, payment: EventPayment) -> SetCanisterResult {
+    let TopUpCanisterArgs {
+        canister_id,
+        cycles,
+    } = args;
+
+    let result = match deposit_cycles_to(canister_id, cycles.into()).await {
+        Ok(()) => SetCanisterResult::Ok(),
+        Err(err) => {
+            credit(
+                caller(),
+                payment.cycles,
+                PrepaidReason::TopUpReverted { canister_id },
+            );
+            SetCanisterResult::Err(CreateCanisterError::TopUpFailed(err))
+        }
+    };
+
+    let error = match &result {
+        SetCanisterResult::Ok() => None,
+        SetCanisterResult::Err(err) => Some(err),
+    };
+
+    record_paid_event(
+        EventKind::CanisterToppedUp {
+            canister_id,
+            cycles,
+        },
+        payment,
+        error,
+    );
+
+    result
+}
@@ -0,0 +1,26 @@
+use candid::{CandidType, Deserialize, Principal};
+use serde::Serialize;
+
+/// Who may create canisters, besides the principals on the creation denylist who never may.
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum CreationAccess {
+    /// Every principal may create canisters.
+    #[default]
+    Open,
+    /// Only the principals on the creation allowlist may create canisters.
+    AllowlistOnly,
+}
+
+/// A list of principals controlling who may create canisters.
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AccessList {
+    Allowlist,
+    Denylist,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct AccessListEntry {
+    pub principal: Principal,
+    /// IC time, in nanoseconds since the epoch, at which the principal was listed.
+    pub listed_at: u64,
+}
@@ -0,0 +1,9 @@
+use candid::{CandidType, Deserialize, Principal};
+use serde::Serialize;
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Eq, PartialEq, Debug)]
+pub struct SetAccessListArgs {
+    pub principals: Vec<Principal>,
+    /// Whether the principals are added to the list or removed from it.
+    pub listed: bool,
+}
@@ -0,0 +1,26 @@
+use candid::{CandidType, Deserialize, Principal};
+use icrc_ledger_types::icrc1::account::Account;
+use serde::Serialize;
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Eq, PartialEq, Debug)]
+pub struct SetAutoTopUpArgs {
+    pub canister_id: Principal,
+    /// Balance, in cycles, below which the canister is topped up.
+    pub threshold: u64,
+    /// Cycles deposited on each top-up.
+    pub refill: u64,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Eq, PartialEq, Debug)]
+pub struct FundPrepaidBalanceArgs {
+    /// Cycles to add to the caller's prepaid balance.
+    pub cycles: u64,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Eq, PartialEq, Debug)]
+pub struct WithdrawPrepaidBalanceArgs {
+    /// Cycles to take from the caller's prepaid balance.
+    pub cycles: u64,
+    /// Cycles ledger account credited with the cycles.
+    pub to: Account,
+}
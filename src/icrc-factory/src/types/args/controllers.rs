@@ -0,0 +1,14 @@
+use candid::{CandidType, Deserialize, Principal};
+use serde::Serialize;
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Eq, PartialEq, Debug)]
+pub struct AddControllerArgs {
+    pub canister_id: Principal,
+    pub controller: Principal,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Eq, PartialEq, Debug)]
+pub struct RemoveControllerArgs {
+    pub canister_id: Principal,
+    pub controller: Principal,
+}
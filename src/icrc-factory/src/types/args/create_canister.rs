@@ -1,4 +1,5 @@
 use candid::{CandidType, Deserialize, Principal};
+use ic_cdk::api::management_canister::main::LogVisibility;
 use icrc_ledger_types::icrc1::account::Account;
 use serde::Serialize;
 
@@ -11,11 +12,32 @@ pub struct CreateIcrcLedgerArgs {
     pub transfer_fee: Option<u64>,
     pub decimals: Option<u8>,
     pub minting_account: Option<Account>,
+    pub settings: Option<CanisterSettingsArgs>,
+    /// Logo of the token, a `data:` or `https:` URI of at most 8 KiB, stored as the `icrc1:logo`
+    /// ledger metadata.
+    pub logo: Option<String>,
 }
 
 #[derive(CandidType, Serialize, Deserialize, Clone, Eq, PartialEq, Debug)]
 pub struct CreateIcrcIndexArgs {
     pub ledger_id: Principal,
+    pub settings: Option<CanisterSettingsArgs>,
+}
+
+/// Canister settings that callers may choose when creating a canister.
+///
+/// Omitted fields fall back to the IC defaults.
+#[derive(CandidType, Serialize, Deserialize, Clone, Eq, PartialEq, Debug, Default)]
+pub struct CanisterSettingsArgs {
+    /// Freezing threshold, in seconds.
+    pub freezing_threshold: Option<u64>,
+    /// Upper limit of the canister's Wasm heap memory, in bytes.
+    pub wasm_memory_limit: Option<u64>,
+    /// Upper limit of the cycles the canister may reserve for resource allocations.
+    pub reserved_cycles_limit: Option<u64>,
+    /// Compute allocation, as a percentage of an execution core.
+    pub compute_allocation: Option<u64>,
+    pub log_visibility: Option<LogVisibility>,
 }
 
 #[derive(CandidType, Serialize, Deserialize, Clone, Eq, PartialEq, Debug)]
@@ -0,0 +1,30 @@
+use candid::{CandidType, Deserialize, Principal};
+use serde::Serialize;
+
+use crate::types::args::pricing::SetPricingArgs;
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Eq, PartialEq, Debug)]
+pub struct SetFreeQuotaArgs {
+    pub principal: Principal,
+    /// Canister creations the principal may still make for free; 0 removes the quota.
+    pub creations: u32,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Eq, PartialEq, Debug)]
+pub struct SetPromoCodeArgs {
+    pub code: String,
+    /// Discount, in basis points, taken off the fee; 10 000 makes creations free.
+    pub discount_bps: u16,
+    /// IC time, in nanoseconds since the epoch, after which the code is refused.
+    pub expires_at: Option<u64>,
+    /// Number of redemptions allowed, including past ones.
+    pub max_uses: Option<u32>,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Eq, PartialEq, Debug)]
+pub struct SetCustomPricingArgs {
+    pub principal: Principal,
+    /// Fees of the principal; omitted fields take the factory's current value. `None` removes the
+    /// principal from the allowlist.
+    pub pricing: Option<SetPricingArgs>,
+}
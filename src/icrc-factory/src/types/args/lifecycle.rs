@@ -0,0 +1,11 @@
+use candid::{CandidType, Deserialize, Principal};
+use icrc_ledger_types::icrc1::account::Account;
+use serde::Serialize;
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Eq, PartialEq, Debug)]
+pub struct DeleteCanisterArgs {
+    pub canister_id: Principal,
+    /// Cycles ledger account credited with the recovered cycles. If not provided, they are kept by
+    /// the factory.
+    pub recover_cycles_to: Option<Account>,
+}
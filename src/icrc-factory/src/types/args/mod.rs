@@ -1 +1,11 @@
+pub mod access_list;
+pub mod auto_top_up;
+pub mod controllers;
 pub mod create_canister;
+pub mod discounts;
+pub mod lifecycle;
+pub mod ownership;
+pub mod pause;
+pub mod pricing;
+pub mod roles;
+pub mod top_up;
@@ -0,0 +1,23 @@
+use candid::{CandidType, Deserialize, Principal};
+use serde::Serialize;
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Eq, PartialEq, Debug)]
+pub struct ProposeOwnershipTransferArgs {
+    pub canister_id: Principal,
+    /// The proposed new owner, or `None` to cancel a pending proposal.
+    pub new_owner: Option<Principal>,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Eq, PartialEq, Debug)]
+pub struct AcceptOwnershipTransferArgs {
+    pub canister_id: Principal,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Eq, PartialEq, Debug)]
+pub struct MakeLedgerImmutableArgs {
+    pub ledger_id: Principal,
+    /// Must repeat `ledger_id`, guarding against making the wrong ledger immutable.
+    pub confirm_ledger_id: Principal,
+    /// Must be `true`, acknowledging that the ledger can never be upgraded again.
+    pub acknowledge_irreversible: bool,
+}
@@ -0,0 +1,10 @@
+use candid::{CandidType, Deserialize};
+use serde::Serialize;
+
+/// Pause flags to change; omitted flags keep their current value.
+#[derive(CandidType, Serialize, Deserialize, Clone, Default, Eq, PartialEq, Debug)]
+pub struct SetPausedArgs {
+    pub creation: Option<bool>,
+    pub upgrades: Option<bool>,
+    pub wasm_updates: Option<bool>,
+}
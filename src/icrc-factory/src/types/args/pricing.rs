@@ -0,0 +1,11 @@
+use candid::{CandidType, Deserialize};
+use serde::Serialize;
+
+/// Changes to the pricing table; omitted fields keep their current value.
+#[derive(CandidType, Serialize, Deserialize, Clone, Eq, PartialEq, Debug, Default)]
+pub struct SetPricingArgs {
+    pub create_icrc_ledger: Option<u64>,
+    pub create_icrc_index: Option<u64>,
+    pub top_up_canister: Option<u64>,
+    pub margin_bps: Option<u16>,
+}
@@ -0,0 +1,10 @@
+use candid::{CandidType, Deserialize, Principal};
+use serde::Serialize;
+
+use crate::types::role::Role;
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Eq, PartialEq, Debug)]
+pub struct RoleArgs {
+    pub principal: Principal,
+    pub role: Role,
+}
@@ -0,0 +1,9 @@
+use candid::{CandidType, Deserialize, Principal};
+use serde::Serialize;
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Eq, PartialEq, Debug)]
+pub struct TopUpCanisterArgs {
+    pub canister_id: Principal,
+    /// Cycles to deposit into the canister, on top of the service fee.
+    pub cycles: u64,
+}
@@ -0,0 +1,17 @@
+use candid::{CandidType, Deserialize, Principal};
+use serde::Serialize;
+
+/// An owner's request to keep a canister, and the archives of a ledger, above a cycles threshold.
+#[derive(CandidType, Serialize, Deserialize, Clone, Eq, PartialEq, Debug)]
+pub struct AutoTopUp {
+    pub canister_id: Principal,
+    /// Owner whose prepaid balance funds the top-ups.
+    pub owner: Principal,
+    /// Balance, in cycles, below which the canister is topped up.
+    pub threshold: u64,
+    /// Cycles deposited on each top-up.
+    pub refill: u64,
+    /// Set once the owner has been notified that their prepaid balance ran dry, so that the
+    /// notification is not repeated on every poll.
+    pub balance_exhausted: bool,
+}
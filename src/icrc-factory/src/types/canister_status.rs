@@ -0,0 +1,49 @@
+use candid::{CandidType, Deserialize, Nat, Principal};
+use ic_cdk::api::management_canister::main::{CanisterStatusResponse, CanisterStatusType};
+use serde::Serialize;
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Eq, PartialEq, Debug)]
+pub enum CanisterRunStatus {
+    Running,
+    Stopping,
+    Stopped,
+}
+
+/// The parts of the management canister's `canister_status` response that owners need to monitor
+/// their canisters.
+#[derive(CandidType, Serialize, Deserialize, Clone, Eq, PartialEq, Debug)]
+pub struct CanisterStatus {
+    pub canister_id: Principal,
+    pub status: CanisterRunStatus,
+    pub module_hash: Option<Vec<u8>>,
+    pub controllers: Vec<Principal>,
+    pub cycles: Nat,
+    pub reserved_cycles: Nat,
+    pub idle_cycles_burned_per_day: Nat,
+    pub freezing_threshold: Nat,
+    /// Memory used by the canister, in bytes.
+    pub memory_size: Nat,
+}
+
+impl CanisterStatus {
+    #[must_use]
+    pub fn new(canister_id: Principal, response: CanisterStatusResponse) -> Self {
+        let status = match response.status {
+            CanisterStatusType::Running => CanisterRunStatus::Running,
+            CanisterStatusType::Stopping => CanisterRunStatus::Stopping,
+            CanisterStatusType::Stopped => CanisterRunStatus::Stopped,
+        };
+
+        Self {
+            canister_id,
+            status,
+            module_hash: response.module_hash,
+            controllers: response.settings.controllers,
+            cycles: response.cycles,
+            reserved_cycles: response.reserved_cycles,
+            idle_cycles_burned_per_day: response.idle_cycles_burned_per_day,
+            freezing_threshold: response.settings.freezing_threshold,
+            memory_size: response.memory_size,
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use candid::{CandidType, Deserialize};
+use serde_bytes::ByteBuf;
+
+use crate::types::{config::Config, user_canister::UserCanister};
+
+/// Registry entries, with the certificate of the factory and a witness of its certified tree.
+#[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+pub struct CertifiedUserCanisters {
+    pub canisters: Vec<UserCanister>,
+    /// Certificate of the certified data of the factory, signed by the subnet.
+    pub certificate: ByteBuf,
+    /// CBOR-encoded hash tree revealing the SHA-256 hash of the Candid encoding of every entry, at
+    /// `registry/<owner>/<canister ID>`.
+    pub witness: ByteBuf,
+}
+
+/// The configuration, with the certificate of the factory and a witness of its certified tree.
+#[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+pub struct CertifiedConfig {
+    pub config: Config,
+    /// Certificate of the certified data of the factory, signed by the subnet.
+    pub certificate: ByteBuf,
+    /// CBOR-encoded hash tree revealing the SHA-256 hash of the Candid encoding of the
+    /// configuration, at `config`.
+    pub witness: ByteBuf,
+}
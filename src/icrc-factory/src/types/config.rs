@@ -1,9 +1,28 @@
 use candid::{CandidType, Deserialize, Principal};
+use serde::Serialize;
+
+use crate::types::{access_list::CreationAccess, rate_limit::RateLimits};
+
+/// The ICP ledger on mainnet, `ryjl3-tyaaa-aaaaa-aaaba-cai`.
+pub const ICP_LEDGER_CANISTER_ID: Principal =
+    Principal::from_slice(&[0, 0, 0, 0, 0, 0, 0, 2, 1, 1]);
+
+/// The cycles minting canister on mainnet, `rkp4c-7iaaa-aaaaa-aaaaq-cai`.
+pub const CMC_CANISTER_ID: Principal = Principal::from_slice(&[0, 0, 0, 0, 0, 0, 0, 4, 1, 1]);
 
 #[derive(CandidType, Deserialize)]
 pub struct InitArgs {
     /// Payment canister ID. If not provided, the default cycles ledger canister ID will be used.
     pub cycles_ledger: Option<Principal>,
+    /// Canister left as the sole controller of ledgers made immutable. If not provided, immutable
+    /// ledgers are left without any controller.
+    pub blackhole_canister: Option<Principal>,
+    /// ICP ledger that ICP payments are pulled from. If not provided, the mainnet ICP ledger is
+    /// used.
+    pub icp_ledger: Option<Principal>,
+    /// Cycles minting canister that converts ICP payments to cycles. If not provided, the mainnet
+    /// CMC is used.
+    pub cmc: Option<Principal>,
 }
 
 #[derive(CandidType, Deserialize)]
@@ -12,18 +31,59 @@ pub enum Args {
     Upgrade,
 }
 
-#[derive(CandidType, Deserialize, Clone, Debug, Eq, PartialEq)]
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
 pub struct Config {
     /// Payment canister ID.
     pub cycles_ledger: Principal,
+    /// Sole controller of ledgers made immutable, or `None` to leave them without controllers.
+    pub blackhole_canister: Option<Principal>,
+    /// ICP ledger that ICP payments are pulled from; `None` in configurations stored before it was
+    /// introduced, which use the mainnet ICP ledger.
+    pub icp_ledger: Option<Principal>,
+    /// Cycles minting canister; `None` in configurations stored before it was introduced, which
+    /// use the mainnet CMC.
+    pub cmc: Option<Principal>,
+    /// Operations paused by an admin; `None` in configurations stored before pausing was
+    /// introduced, in which nothing is paused.
+    pub paused: Option<PauseFlags>,
+    /// Rate limits of creations and ledger upgrades; `None` in configurations stored before rate
+    /// limiting was introduced, in which calls are not limited.
+    pub rate_limits: Option<RateLimits>,
+    /// Who may create canisters; `None` in configurations stored before access lists were
+    /// introduced, in which creations are open.
+    pub creation_access: Option<CreationAccess>,
+}
+
+/// Classes of operations that admins can pause in an emergency, e.g. while a WASM is found faulty.
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct PauseFlags {
+    /// `create_icrc_ledger` and `create_icrc_index`.
+    pub creation: bool,
+    /// Ledger upgrades: `set_symbol`, `set_name` and `set_index_canister`.
+    pub upgrades: bool,
+    /// Replacements of the stored ledger and index WASMs.
+    pub wasm_updates: bool,
 }
 
 impl From<InitArgs> for Config {
     /// Creates a new `Config` from the provided `InitArgs`.
     fn from(arg: InitArgs) -> Self {
-        let InitArgs { cycles_ledger } = arg;
+        let InitArgs {
+            cycles_ledger,
+            blackhole_canister,
+            icp_ledger,
+            cmc,
+        } = arg;
         let cycles_ledger =
             cycles_ledger.unwrap_or_else(ic_papi_api::cycles::cycles_ledger_canister_id);
-        Config { cycles_ledger }
+        Config {
+            cycles_ledger,
+            blackhole_canister,
+            icp_ledger: Some(icp_ledger.unwrap_or(ICP_LEDGER_CANISTER_ID)),
+            cmc: Some(cmc.unwrap_or(CMC_CANISTER_ID)),
+            paused: None,
+            rate_limits: None,
+            creation_access: None,
+        }
     }
 }
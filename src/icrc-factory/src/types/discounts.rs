@@ -0,0 +1,49 @@
+use candid::{CandidType, Deserialize, Principal};
+use serde::Serialize;
+
+use crate::{methods::SignerMethods, types::pricing::Pricing};
+
+/// A promo code discounting the fees of canister creations.
+#[derive(CandidType, Serialize, Deserialize, Clone, Eq, PartialEq, Debug)]
+pub struct PromoCode {
+    pub code: String,
+    /// Discount, in basis points, taken off the fee; 10 000 makes creations free.
+    pub discount_bps: u16,
+    /// IC time, in nanoseconds since the epoch, after which the code is refused; `None` if it
+    /// never expires.
+    pub expires_at: Option<u64>,
+    /// Number of redemptions allowed; `None` if uncapped.
+    pub max_uses: Option<u32>,
+    /// Number of redemptions so far.
+    pub uses: u32,
+}
+
+/// Pricing that applies to an allowlisted principal instead of the factory's pricing.
+#[derive(CandidType, Serialize, Deserialize, Clone, Eq, PartialEq, Debug)]
+pub struct CustomPricing {
+    pub principal: Principal,
+    pub pricing: Pricing,
+}
+
+/// A discount granted to a canister creation.
+#[derive(CandidType, Serialize, Deserialize, Clone, Eq, PartialEq, Debug)]
+pub enum Discount {
+    /// One of the caller's free creations.
+    FreeQuota,
+    PromoCode {
+        code: String,
+    },
+}
+
+/// Audit record of a promo code redemption.
+#[derive(CandidType, Serialize, Deserialize, Clone, Eq, PartialEq, Debug)]
+pub struct PromoCodeRedemption {
+    pub timestamp: u64,
+    pub code: String,
+    pub redeemer: Principal,
+    pub method: SignerMethods,
+    /// Fee, in cycles, before the discount.
+    pub fee: u64,
+    /// Fee, in cycles, actually charged.
+    pub charged: u64,
+}
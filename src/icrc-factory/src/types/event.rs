@@ -0,0 +1,390 @@
+use candid::{CandidType, Deserialize, Nat, Principal};
+use icrc_ledger_types::icrc1::account::Account;
+use serde::Serialize;
+
+use crate::types::{
+    access_list::AccessList,
+    config::Config,
+    discounts::{Discount, PromoCode},
+    pricing::Pricing,
+    quote::PaymentMethod,
+    role::Role,
+};
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Eq, PartialEq, Debug)]
+pub enum EventKind {
+    /// A ledger was created; `canister_id` is `None` if the creation failed after payment.
+    IcrcLedgerCreated {
+        canister_id: Option<Principal>,
+        symbol: Option<String>,
+        name: Option<String>,
+    },
+    /// An index was created; `canister_id` is `None` if the creation failed after payment.
+    IcrcIndexCreated {
+        canister_id: Option<Principal>,
+        ledger_id: Principal,
+    },
+    /// The ledger WASM was replaced; `module_hash` is its SHA-256 hash.
+    LedgerWasmSet {
+        module_hash: Vec<u8>,
+        url: Option<String>,
+    },
+    /// The index WASM was replaced; `module_hash` is its SHA-256 hash.
+    IndexWasmSet {
+        module_hash: Vec<u8>,
+        url: Option<String>,
+    },
+    SymbolSet {
+        ledger_id: Principal,
+        symbol: String,
+    },
+    NameSet {
+        ledger_id: Principal,
+        name: String,
+    },
+    IndexCanisterSet {
+        ledger_id: Principal,
+        index_id: Principal,
+    },
+    /// The configuration was set on install or on an upgrade with init arguments, operations were
+    /// paused or resumed, or the rate limits or the creation access changed.
+    ConfigUpdated {
+        config: Config,
+    },
+    OwnershipTransferred {
+        canister_id: Principal,
+        from: Principal,
+        to: Principal,
+    },
+    FactoryControlRenounced {
+        canister_id: Principal,
+    },
+    LedgerMadeImmutable {
+        canister_id: Principal,
+        module_hash: Vec<u8>,
+    },
+    ControllerAdded {
+        canister_id: Principal,
+        controller: Principal,
+    },
+    ControllerRemoved {
+        canister_id: Principal,
+        controller: Principal,
+    },
+    CanisterToppedUp {
+        canister_id: Principal,
+        cycles: u64,
+    },
+    PrepaidBalanceFunded {
+        owner: Principal,
+        cycles: u64,
+    },
+    AutoTopUpSet {
+        canister_id: Principal,
+        threshold: u64,
+        refill: u64,
+    },
+    AutoTopUpRemoved {
+        canister_id: Principal,
+    },
+    /// A canister, or one of a ledger's archives, was topped up from its owner's prepaid balance.
+    AutoToppedUp {
+        owner: Principal,
+        canister_id: Principal,
+        cycles: u64,
+    },
+    AutoTopUpFailed {
+        canister_id: Principal,
+        reason: String,
+    },
+    /// The owner's prepaid balance no longer covers the top-ups of their canisters.
+    PrepaidBalanceExhausted {
+        owner: Principal,
+        canister_id: Principal,
+        balance: u64,
+        required: u64,
+    },
+    CanisterStopped {
+        canister_id: Principal,
+    },
+    CanisterStarted {
+        canister_id: Principal,
+    },
+    /// A canister was deleted; for a ledger, its archives were deleted along with it.
+    CanisterDeleted {
+        canister_id: Principal,
+        recovered_cycles: Nat,
+        recovered_to: Option<Account>,
+    },
+    PricingUpdated {
+        pricing: Pricing,
+    },
+    /// Cycles attached to a paid call beyond its fee were credited to the caller's prepaid
+    /// balance.
+    ExcessCyclesCredited {
+        owner: Principal,
+        cycles: u64,
+    },
+    PrepaidBalanceWithdrawn {
+        owner: Principal,
+        cycles: u64,
+        to: Account,
+    },
+    FreeQuotaSet {
+        principal: Principal,
+        creations: u32,
+    },
+    PromoCodeSet {
+        promo_code: PromoCode,
+    },
+    PromoCodeRemoved {
+        code: String,
+    },
+    /// A principal was allowlisted with custom pricing, or removed from the allowlist.
+    CustomPricingSet {
+        principal: Principal,
+        pricing: Option<Pricing>,
+    },
+    RoleGranted {
+        principal: Principal,
+        role: Role,
+    },
+    RoleRevoked {
+        principal: Principal,
+        role: Role,
+    },
+    /// Principals were added to a creation access list, or removed from it.
+    CreationAccessListUpdated {
+        list: AccessList,
+        principals: Vec<Principal>,
+        listed: bool,
+    },
+}
+
+impl EventKind {
+    /// Every name returned by [`EventKind::event_type`], in declaration order.
+    pub const EVENT_TYPES: [&'static str; 33] = [
+        "IcrcLedgerCreated",
+        "IcrcIndexCreated",
+        "LedgerWasmSet",
+        "IndexWasmSet",
+        "SymbolSet",
+        "NameSet",
+        "IndexCanisterSet",
+        "ConfigUpdated",
+        "OwnershipTransferred",
+        "FactoryControlRenounced",
+        "LedgerMadeImmutable",
+        "ControllerAdded",
+        "ControllerRemoved",
+        "CanisterToppedUp",
+        "PrepaidBalanceFunded",
+        "AutoTopUpSet",
+        "AutoTopUpRemoved",
+        "AutoToppedUp",
+        "AutoTopUpFailed",
+        "PrepaidBalanceExhausted",
+        "CanisterStopped",
+        "CanisterStarted",
+        "CanisterDeleted",
+        "PricingUpdated",
+        "ExcessCyclesCredited",
+        "PrepaidBalanceWithdrawn",
+        "FreeQuotaSet",
+        "PromoCodeSet",
+        "PromoCodeRemoved",
+        "CustomPricingSet",
+        "RoleGranted",
+        "RoleRevoked",
+        "CreationAccessListUpdated",
+    ];
+
+    /// Name of the event type, as matched by [`EventFilter::event_type`].
+    #[must_use]
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            EventKind::IcrcLedgerCreated { .. } => "IcrcLedgerCreated",
+            EventKind::IcrcIndexCreated { .. } => "IcrcIndexCreated",
+            EventKind::LedgerWasmSet { .. } => "LedgerWasmSet",
+            EventKind::IndexWasmSet { .. } => "IndexWasmSet",
+            EventKind::SymbolSet { .. } => "SymbolSet",
+            EventKind::NameSet { .. } => "NameSet",
+            EventKind::IndexCanisterSet { .. } => "IndexCanisterSet",
+            EventKind::ConfigUpdated { .. } => "ConfigUpdated",
+            EventKind::OwnershipTransferred { .. } => "OwnershipTransferred",
+            EventKind::FactoryControlRenounced { .. } => "FactoryControlRenounced",
+            EventKind::LedgerMadeImmutable { .. } => "LedgerMadeImmutable",
+            EventKind::ControllerAdded { .. } => "ControllerAdded",
+            EventKind::ControllerRemoved { .. } => "ControllerRemoved",
+            EventKind::CanisterToppedUp { .. } => "CanisterToppedUp",
+            EventKind::PrepaidBalanceFunded { .. } => "PrepaidBalanceFunded",
+            EventKind::AutoTopUpSet { .. } => "AutoTopUpSet",
+            EventKind::AutoTopUpRemoved { .. } => "AutoTopUpRemoved",
+            EventKind::AutoToppedUp { .. } => "AutoToppedUp",
+            EventKind::AutoTopUpFailed { .. } => "AutoTopUpFailed",
+            EventKind::PrepaidBalanceExhausted { .. } => "PrepaidBalanceExhausted",
+            EventKind::CanisterStopped { .. } => "CanisterStopped",
+            EventKind::CanisterStarted { .. } => "CanisterStarted",
+            EventKind::CanisterDeleted { .. } => "CanisterDeleted",
+            EventKind::PricingUpdated { .. } => "PricingUpdated",
+            EventKind::ExcessCyclesCredited { .. } => "ExcessCyclesCredited",
+            EventKind::PrepaidBalanceWithdrawn { .. } => "PrepaidBalanceWithdrawn",
+            EventKind::FreeQuotaSet { .. } => "FreeQuotaSet",
+            EventKind::PromoCodeSet { .. } => "PromoCodeSet",
+            EventKind::PromoCodeRemoved { .. } => "PromoCodeRemoved",
+            EventKind::CustomPricingSet { .. } => "CustomPricingSet",
+            EventKind::RoleGranted { .. } => "RoleGranted",
+            EventKind::RoleRevoked { .. } => "RoleRevoked",
+            EventKind::CreationAccessListUpdated { .. } => "CreationAccessListUpdated",
+        }
+    }
+
+    /// Canisters the event is about: the canister acted on and, for an index, its ledger.
+    #[must_use]
+    pub fn canister_ids(&self) -> Vec<Principal> {
+        match self {
+            EventKind::IcrcLedgerCreated { canister_id, .. } => {
+                canister_id.iter().copied().collect()
+            }
+            EventKind::IcrcIndexCreated {
+                canister_id,
+                ledger_id,
+            } => canister_id.iter().copied().chain([*ledger_id]).collect(),
+            EventKind::SymbolSet { ledger_id, .. } | EventKind::NameSet { ledger_id, .. } => {
+                vec![*ledger_id]
+            }
+            EventKind::IndexCanisterSet {
+                ledger_id,
+                index_id,
+            } => vec![*ledger_id, *index_id],
+            EventKind::OwnershipTransferred { canister_id, .. }
+            | EventKind::FactoryControlRenounced { canister_id }
+            | EventKind::LedgerMadeImmutable { canister_id, .. }
+            | EventKind::ControllerAdded { canister_id, .. }
+            | EventKind::ControllerRemoved { canister_id, .. }
+            | EventKind::CanisterToppedUp { canister_id, .. }
+            | EventKind::AutoTopUpSet { canister_id, .. }
+            | EventKind::AutoTopUpRemoved { canister_id }
+            | EventKind::AutoToppedUp { canister_id, .. }
+            | EventKind::AutoTopUpFailed { canister_id, .. }
+            | EventKind::PrepaidBalanceExhausted { canister_id, .. }
+            | EventKind::CanisterStopped { canister_id }
+            | EventKind::CanisterStarted { canister_id }
+            | EventKind::CanisterDeleted { canister_id, .. } => vec![*canister_id],
+            EventKind::LedgerWasmSet { .. }
+            | EventKind::IndexWasmSet { .. }
+            | EventKind::ConfigUpdated { .. }
+            | EventKind::PrepaidBalanceFunded { .. }
+            | EventKind::PricingUpdated { .. }
+            | EventKind::ExcessCyclesCredited { .. }
+            | EventKind::PrepaidBalanceWithdrawn { .. }
+            | EventKind::FreeQuotaSet { .. }
+            | EventKind::PromoCodeSet { .. }
+            | EventKind::PromoCodeRemoved { .. }
+            | EventKind::CustomPricingSet { .. }
+            | EventKind::RoleGranted { .. }
+            | EventKind::RoleRevoked { .. }
+            | EventKind::CreationAccessListUpdated { .. } => Vec::new(),
+        }
+    }
+
+    /// Principals the event is about, other than its caller: owners, controllers and the
+    /// principals discounts, roles or access list entries are about.
+    #[must_use]
+    pub fn principals(&self) -> Vec<Principal> {
+        match self {
+            EventKind::OwnershipTransferred { from, to, .. } => vec![*from, *to],
+            EventKind::ControllerAdded { controller, .. }
+            | EventKind::ControllerRemoved { controller, .. } => vec![*controller],
+            EventKind::PrepaidBalanceFunded { owner, .. }
+            | EventKind::AutoToppedUp { owner, .. }
+            | EventKind::PrepaidBalanceExhausted { owner, .. }
+            | EventKind::ExcessCyclesCredited { owner, .. } => vec![*owner],
+            EventKind::PrepaidBalanceWithdrawn { owner, to, .. } => vec![*owner, to.owner],
+            EventKind::CanisterDeleted { recovered_to, .. } => {
+                recovered_to.iter().map(|account| account.owner).collect()
+            }
+            EventKind::FreeQuotaSet { principal, .. }
+            | EventKind::CustomPricingSet { principal, .. }
+            | EventKind::RoleGranted { principal, .. }
+            | EventKind::RoleRevoked { principal, .. } => vec![*principal],
+            EventKind::CreationAccessListUpdated { principals, .. } => principals.clone(),
+            EventKind::IcrcLedgerCreated { .. }
+            | EventKind::IcrcIndexCreated { .. }
+            | EventKind::LedgerWasmSet { .. }
+            | EventKind::IndexWasmSet { .. }
+            | EventKind::SymbolSet { .. }
+            | EventKind::NameSet { .. }
+            | EventKind::IndexCanisterSet { .. }
+            | EventKind::ConfigUpdated { .. }
+            | EventKind::FactoryControlRenounced { .. }
+            | EventKind::LedgerMadeImmutable { .. }
+            | EventKind::CanisterToppedUp { .. }
+            | EventKind::AutoTopUpSet { .. }
+            | EventKind::AutoTopUpRemoved { .. }
+            | EventKind::AutoTopUpFailed { .. }
+            | EventKind::CanisterStopped { .. }
+            | EventKind::CanisterStarted { .. }
+            | EventKind::PricingUpdated { .. }
+            | EventKind::PromoCodeSet { .. }
+            | EventKind::PromoCodeRemoved { .. } => Vec::new(),
+        }
+    }
+}
+
+/// How a paid action was paid for.
+#[derive(CandidType, Serialize, Deserialize, Clone, Eq, PartialEq, Debug)]
+pub struct EventPayment {
+    pub method: PaymentMethod,
+    /// Cycles charged, after discounts.
+    pub cycles: u64,
+    pub discount: Option<Discount>,
+}
+
+/// Outcome of a paid action.
+#[derive(CandidType, Serialize, Deserialize, Clone, Eq, PartialEq, Debug)]
+pub enum EventOutcome {
+    Succeeded,
+    /// The action failed after its payment was taken.
+    Failed {
+        reason: String,
+    },
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Eq, PartialEq, Debug)]
+pub struct Event {
+    /// IC time, in nanoseconds since the epoch, at which the event was recorded.
+    pub timestamp: u64,
+    pub caller: Principal,
+    pub kind: EventKind,
+    /// Payment of a paid action; `None` for other events.
+    pub payment: Option<EventPayment>,
+    /// Outcome of a paid action; `None` for other events, which record completed changes.
+    pub outcome: Option<EventOutcome>,
+}
+
+/// Criteria events must all meet to be listed; omitted criteria match every event.
+#[derive(CandidType, Serialize, Deserialize, Clone, Default, Eq, PartialEq, Debug)]
+pub struct EventFilter {
+    /// Matches events called by the principal, or about it, e.g. as owner or controller.
+    pub principal: Option<Principal>,
+    /// Matches events about the canister.
+    pub canister_id: Option<Principal>,
+    /// Matches events of the type, e.g. `"IcrcLedgerCreated"`.
+    pub event_type: Option<String>,
+}
+
+impl EventFilter {
+    #[must_use]
+    pub fn matches(&self, event: &Event) -> bool {
+        self.principal.is_none_or(|principal| {
+            event.caller == principal || event.kind.principals().contains(&principal)
+        }) && self
+            .canister_id
+            .is_none_or(|canister_id| event.kind.canister_ids().contains(&canister_id))
+            && self
+                .event_type
+                .as_ref()
+                .is_none_or(|event_type| event.kind.event_type() == event_type)
+    }
+}
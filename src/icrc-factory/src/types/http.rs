@@ -0,0 +1,20 @@
+use candid::{CandidType, Deserialize};
+use serde_bytes::ByteBuf;
+
+/// A request received through the HTTP gateway.
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct HttpGatewayRequest {
+    pub method: String,
+    /// Path of the request, with its query string.
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: ByteBuf,
+}
+
+/// A response returned through the HTTP gateway.
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct HttpGatewayResponse {
+    pub status_code: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: ByteBuf,
+}
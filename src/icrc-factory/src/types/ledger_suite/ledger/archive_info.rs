@@ -0,0 +1,10 @@
+use candid::{CandidType, Deserialize, Nat, Principal};
+use serde::Serialize;
+
+/// <https://github.com/dfinity/ic/blob/e446c64d99a97e38166be23ff2bfade997d15ff7/rs/ledger_suite/icrc1/ledger/ledger.did>
+#[derive(Clone, Eq, PartialEq, Debug, CandidType, Deserialize, Serialize)]
+pub struct ArchiveInfo {
+    pub canister_id: Principal,
+    pub block_range_start: Nat,
+    pub block_range_end: Nat,
+}
@@ -1,2 +1,3 @@
+pub mod archive_info;
 pub mod init_args;
 pub mod upgrade_args;
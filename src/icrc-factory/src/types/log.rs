@@ -0,0 +1,60 @@
+use candid::{CandidType, Deserialize, Principal};
+use serde::Serialize;
+
+/// Severity of a log entry, from the least to the most severe.
+#[derive(
+    CandidType, Serialize, Deserialize, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Debug,
+)]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+/// A step of a factory flow, such as a canister creation or upgrade.
+#[derive(CandidType, Serialize, Deserialize, Clone, Eq, PartialEq, Debug)]
+pub struct LogEntry {
+    /// Sequence number of the entry, increasing across upgrades.
+    pub id: u64,
+    /// Time of the entry, in nanoseconds since the epoch.
+    pub timestamp: u64,
+    pub level: LogLevel,
+    /// ID of the first entry of the flow, shared by every entry of the flow.
+    pub correlation_id: u64,
+    /// Principal whose call started the flow.
+    pub caller: Principal,
+    /// Canister the entry is about, once known.
+    pub canister_id: Option<Principal>,
+    pub message: String,
+}
+
+/// Criteria log entries must all meet to be listed; omitted criteria match every entry.
+#[derive(CandidType, Serialize, Deserialize, Clone, Default, Eq, PartialEq, Debug)]
+pub struct LogFilter {
+    /// Matches entries of the level or a more severe one.
+    pub min_level: Option<LogLevel>,
+    /// Matches entries recorded at or after the time, in nanoseconds since the epoch.
+    pub from: Option<u64>,
+    /// Matches entries recorded before the time, in nanoseconds since the epoch.
+    pub to: Option<u64>,
+    /// Matches entries about the canister.
+    pub canister_id: Option<Principal>,
+    /// Matches entries of the flow.
+    pub correlation_id: Option<u64>,
+}
+
+impl LogFilter {
+    #[must_use]
+    pub fn matches(&self, entry: &LogEntry) -> bool {
+        self.min_level.is_none_or(|level| entry.level >= level)
+            && self.from.is_none_or(|from| entry.timestamp >= from)
+            && self.to.is_none_or(|to| entry.timestamp < to)
+            && self
+                .canister_id
+                .is_none_or(|canister_id| entry.canister_id == Some(canister_id))
+            && self
+                .correlation_id
+                .is_none_or(|correlation_id| entry.correlation_id == correlation_id)
+    }
+}
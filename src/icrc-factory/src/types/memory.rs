@@ -1,9 +1,21 @@
 use ic_stable_structures::{
-    memory_manager::VirtualMemory, DefaultMemoryImpl, StableBTreeMap, StableCell,
+    memory_manager::VirtualMemory, DefaultMemoryImpl, Log, StableBTreeMap, StableCell,
 };
+use icrc_ledger_types::icrc::generic_value::ICRC3Value;
 
 use crate::types::{
-    candid::Candid, config::Config, stored_principal::StoredPrincipal, user_canister::UserCanister,
+    auto_top_up::AutoTopUp,
+    candid::Candid,
+    config::Config,
+    discounts::{PromoCode, PromoCodeRedemption},
+    event::Event,
+    log::LogEntry,
+    prepaid::PrepaidJournalEntry,
+    pricing::Pricing,
+    role::Role,
+    stored_principal::StoredPrincipal,
+    token::TokenMetadata,
+    user_canister::UserCanister,
 };
 
 pub type VMem = VirtualMemory<DefaultMemoryImpl>;
@@ -14,3 +26,56 @@ pub type IcrcLedgerWasmCell = StableCell<Vec<u8>, VMem>;
 pub type IcrcIndexWasmCell = StableCell<Vec<u8>, VMem>;
 
 pub type UserCanisterMap = StableBTreeMap<StoredPrincipal, Candid<Vec<UserCanister>>, VMem>;
+
+/// Owner of every canister in the registry, keyed by canister ID.
+pub type CanisterOwnerMap = StableBTreeMap<StoredPrincipal, StoredPrincipal, VMem>;
+
+pub type EventLog = Log<Candid<Event>, VMem, VMem>;
+
+/// ICRC-3 blocks of the event log, one per event, oldest first.
+pub type BlockLog = Log<Candid<ICRC3Value>, VMem, VMem>;
+
+/// Auto top-up subscriptions, keyed by canister ID.
+pub type AutoTopUpMap = StableBTreeMap<StoredPrincipal, Candid<AutoTopUp>, VMem>;
+
+/// Prepaid cycles balances, keyed by owner.
+pub type PrepaidBalanceMap = StableBTreeMap<StoredPrincipal, u64, VMem>;
+
+/// Every change to a prepaid balance, oldest first.
+pub type PrepaidJournal = Log<Candid<PrepaidJournalEntry>, VMem, VMem>;
+
+/// Pricing set by controllers, or `None` while the defaults apply.
+pub type PricingCell = StableCell<Option<Candid<Pricing>>, VMem>;
+
+/// Free canister creations left, keyed by principal.
+pub type FreeQuotaMap = StableBTreeMap<StoredPrincipal, u32, VMem>;
+
+/// Promo codes, keyed by code.
+pub type PromoCodeMap = StableBTreeMap<String, Candid<PromoCode>, VMem>;
+
+/// Pricing of allowlisted principals, keyed by principal.
+pub type CustomPricingMap = StableBTreeMap<StoredPrincipal, Candid<Pricing>, VMem>;
+
+/// Every promo code redemption, oldest first.
+pub type PromoCodeRedemptionLog = Log<Candid<PromoCodeRedemption>, VMem, VMem>;
+
+/// Creations by outcome, keyed by `Ok` or the name of the `CreateCanisterError`.
+pub type CreationOutcomeMap = StableBTreeMap<String, u64, VMem>;
+
+/// Cycles charged by paid actions, keyed by the name of the `PaymentMethod`.
+pub type PaymentTotalMap = StableBTreeMap<String, u64, VMem>;
+
+/// Metadata of the ledgers created by the factory, keyed by ledger ID.
+pub type TokenMetadataMap = StableBTreeMap<StoredPrincipal, Candid<TokenMetadata>, VMem>;
+
+/// Log entries, keyed by ID; the oldest are dropped once the buffer is full.
+pub type LogBuffer = StableBTreeMap<u64, Candid<LogEntry>, VMem>;
+
+/// Admin roles granted by controllers, keyed by principal.
+pub type RoleMap = StableBTreeMap<StoredPrincipal, Candid<Vec<Role>>, VMem>;
+
+/// Principals on a creation access list, with the time they were listed at.
+pub type AccessListMap = StableBTreeMap<StoredPrincipal, u64, VMem>;
+
+/// Payers of ICP transfers to the CMC that are not converted to cycles yet, keyed by block index.
+pub type PendingIcpTopUpMap = StableBTreeMap<u64, StoredPrincipal, VMem>;
@@ -1,8 +1,23 @@
+pub mod access_list;
 pub mod args;
+pub mod auto_top_up;
 pub mod candid;
+pub mod canister_status;
+pub mod certified;
 pub mod config;
+pub mod discounts;
+pub mod event;
+pub mod http;
 pub mod ledger_suite;
+pub mod log;
 pub mod memory;
+pub mod payment;
+pub mod prepaid;
+pub mod pricing;
+pub mod quote;
+pub mod rate_limit;
 pub mod results;
+pub mod role;
 pub mod stored_principal;
+pub mod token;
 pub mod user_canister;
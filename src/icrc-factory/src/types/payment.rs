@@ -0,0 +1,41 @@
+use candid::{CandidType, Deserialize};
+use ic_papi_api::{
+    caller::{CallerPaysIcrc2Tokens, PatronPaysIcrc2Tokens},
+    PaymentType,
+};
+use icrc_ledger_types::icrc1::account::Account;
+
+/// How a paid call is paid for: any `PaymentType` accepted by the payment guard, the caller's
+/// prepaid balance, or ICP converted to cycles through the CMC.
+///
+/// The variants shared with `PaymentType` carry the same names and payloads, so callers passing a
+/// `PaymentType` keep working unchanged.
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum FactoryPaymentType {
+    AttachedCycles,
+    CallerPaysIcrc2Cycles,
+    PatronPaysIcrc2Cycles(Account),
+    CallerPaysIcrc2Tokens(CallerPaysIcrc2Tokens),
+    PatronPaysIcrc2Tokens(PatronPaysIcrc2Tokens),
+    PrepaidBalance,
+    /// ICP pulled from the caller with ICRC-2 `transfer_from` on the ICP ledger.
+    CallerPaysIcp,
+}
+
+impl From<PaymentType> for FactoryPaymentType {
+    fn from(payment: PaymentType) -> Self {
+        match payment {
+            PaymentType::AttachedCycles => FactoryPaymentType::AttachedCycles,
+            PaymentType::CallerPaysIcrc2Cycles => FactoryPaymentType::CallerPaysIcrc2Cycles,
+            PaymentType::PatronPaysIcrc2Cycles(patron) => {
+                FactoryPaymentType::PatronPaysIcrc2Cycles(patron)
+            }
+            PaymentType::CallerPaysIcrc2Tokens(payment) => {
+                FactoryPaymentType::CallerPaysIcrc2Tokens(payment)
+            }
+            PaymentType::PatronPaysIcrc2Tokens(payment) => {
+                FactoryPaymentType::PatronPaysIcrc2Tokens(payment)
+            }
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use candid::{CandidType, Deserialize, Principal};
+use icrc_ledger_types::icrc1::account::Account;
+use serde::Serialize;
+
+/// Direction and amount, in cycles, of a prepaid balance change.
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Eq, PartialEq, Debug)]
+pub enum PrepaidChange {
+    Credit(u64),
+    Debit(u64),
+}
+
+/// Why a prepaid balance changed.
+#[derive(CandidType, Serialize, Deserialize, Clone, Eq, PartialEq, Debug)]
+pub enum PrepaidReason {
+    /// Paid for with `fund_prepaid_balance`.
+    Funded,
+    /// Cycles attached to a call beyond its fee.
+    ExcessAttachedCycles,
+    /// A paid call settled against the balance.
+    Payment,
+    /// Cycles recovered from a deleted canister that could not be sent to their destination.
+    CyclesRecovered {
+        canister_id: Principal,
+    },
+    Withdrawal {
+        to: Account,
+    },
+    /// A withdrawal whose deposit failed, handed back.
+    WithdrawalReverted,
+    AutoTopUp {
+        canister_id: Principal,
+    },
+    /// An auto top-up whose deposit failed, handed back.
+    AutoTopUpReverted {
+        canister_id: Principal,
+    },
+    /// A top-up whose deposit failed, handed back with its service fee.
+    TopUpReverted {
+        canister_id: Principal,
+    },
+    /// An ICP payment converted to cycles after the call it paid for had failed.
+    IcpPaymentRecovered {
+        block_index: u64,
+    },
+}
+
+/// An entry of the prepaid balance journal, which records every credit and debit.
+#[derive(CandidType, Serialize, Deserialize, Clone, Eq, PartialEq, Debug)]
+pub struct PrepaidJournalEntry {
+    pub timestamp: u64,
+    pub owner: Principal,
+    pub change: PrepaidChange,
+    /// Balance of `owner` once the change is applied.
+    pub balance: u64,
+    pub reason: PrepaidReason,
+}
@@ -0,0 +1,42 @@
+use candid::{CandidType, Deserialize};
+use serde::Serialize;
+
+use crate::generic::MIN_CYCLES_FOR_CANISTER_CREATION;
+
+/// Fees, in cycles, charged by the factory's paid methods.
+#[derive(CandidType, Serialize, Deserialize, Clone, Eq, PartialEq, Debug)]
+pub struct Pricing {
+    /// Base fee of `create_icrc_ledger`, which includes the cycles the new ledger starts with.
+    pub create_icrc_ledger: u64,
+    /// Base fee of `create_icrc_index`, which includes the cycles the new index starts with.
+    pub create_icrc_index: u64,
+    /// Service fee of `top_up_canister` and of each auto top-up, on top of the deposited cycles.
+    pub top_up_canister: u64,
+    /// Service margin, in basis points, added to every fee.
+    pub margin_bps: u16,
+    /// IC time, in nanoseconds since the epoch, of the last change, or `None` for the defaults.
+    pub updated_at: Option<u64>,
+}
+
+impl Default for Pricing {
+    /// The fees charged before pricing became configurable.
+    fn default() -> Self {
+        // Note: Fees are determined with the aid of scripts/check-pricing
+        Self {
+            create_icrc_ledger: MIN_CYCLES_FOR_CANISTER_CREATION + 400_000_000_000,
+            create_icrc_index: MIN_CYCLES_FOR_CANISTER_CREATION + 400_000_000_000,
+            top_up_canister: 10_000_000_000,
+            margin_bps: 0,
+            updated_at: None,
+        }
+    }
+}
+
+impl Pricing {
+    /// Adds the service margin to `fee`.
+    #[must_use]
+    pub fn with_margin(&self, fee: u64) -> u64 {
+        let margin = u128::from(fee) * u128::from(self.margin_bps) / 10_000;
+        fee.saturating_add(u64::try_from(margin).unwrap_or(u64::MAX))
+    }
+}
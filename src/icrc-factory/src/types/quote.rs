@@ -0,0 +1,49 @@
+use candid::{CandidType, Deserialize, Principal};
+use serde::Serialize;
+
+/// A payment method accepted by the factory, mirroring the variants of `FactoryPaymentType`.
+#[derive(CandidType, Serialize, Deserialize, Clone, Eq, PartialEq, Debug)]
+pub enum PaymentMethod {
+    AttachedCycles,
+    CallerPaysIcrc2Cycles,
+    PatronPaysIcrc2Cycles,
+    CallerPaysIcrc2Tokens { ledger: Principal },
+    PatronPaysIcrc2Tokens { ledger: Principal },
+    PrepaidBalance,
+    CallerPaysIcp { ledger: Principal },
+}
+
+impl PaymentMethod {
+    /// Name of the variant, as reported in the metrics.
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        match self {
+            PaymentMethod::AttachedCycles => "AttachedCycles",
+            PaymentMethod::CallerPaysIcrc2Cycles => "CallerPaysIcrc2Cycles",
+            PaymentMethod::PatronPaysIcrc2Cycles => "PatronPaysIcrc2Cycles",
+            PaymentMethod::CallerPaysIcrc2Tokens { .. } => "CallerPaysIcrc2Tokens",
+            PaymentMethod::PatronPaysIcrc2Tokens { .. } => "PatronPaysIcrc2Tokens",
+            PaymentMethod::PrepaidBalance => "PrepaidBalance",
+            PaymentMethod::CallerPaysIcp { .. } => "CallerPaysIcp",
+        }
+    }
+}
+
+/// What a call costs when paid with one payment method.
+#[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+pub struct PaymentQuote {
+    pub method: PaymentMethod,
+    /// Amount taken from the payer, in cycles, in tokens of the payment ledger or in e8s of ICP.
+    pub amount: u64,
+    /// Allowance the payer must grant the factory, ledger fee included; `None` for attached
+    /// cycles and the prepaid balance.
+    pub allowance: Option<u64>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+pub struct FeeQuote {
+    /// Fee of the call, in cycles.
+    pub cycles: u64,
+    /// The fee for every supported payment method.
+    pub payments: Vec<PaymentQuote>,
+}
@@ -0,0 +1,18 @@
+use candid::{CandidType, Deserialize};
+use serde::Serialize;
+
+/// A sliding-window rate limit: at most `max_calls` calls within any `window_secs` seconds.
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RateLimit {
+    pub max_calls: u32,
+    pub window_secs: u64,
+}
+
+/// Rate limits of creations and ledger upgrades; a `None` limit does not apply.
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct RateLimits {
+    /// Limit of the calls of each principal.
+    pub per_principal: Option<RateLimit>,
+    /// Limit of the calls of all principals together.
+    pub global: Option<RateLimit>,
+}
@@ -0,0 +1,18 @@
+use candid::{CandidType, Deserialize, Principal};
+
+use crate::types::{
+    canister_status::CanisterStatus, results::create_canister::CreateCanisterError,
+};
+
+#[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+pub enum CanisterStatusResult {
+    Ok(CanisterStatus),
+    Err(CreateCanisterError),
+}
+
+/// Status of one of the caller's canisters, as returned by the batch status query.
+#[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+pub struct CanisterStatusEntry {
+    pub canister_id: Principal,
+    pub result: CanisterStatusResult,
+}
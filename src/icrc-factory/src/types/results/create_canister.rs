@@ -7,6 +7,104 @@ pub enum CreateCanisterError {
     InitArgsEncodingFailed(String),
     WasmInstallationFailed(String),
     PaymentError(ic_papi_api::PaymentError),
+    CanisterNotFound,
+    NotOwner,
+    InvalidNewOwner,
+    NoPendingOwnershipTransfer,
+    CanisterStatusFailed(String),
+    UpdateSettingsFailed(String),
+    ArchiveListingFailed(String),
+    Sovereign,
+    ModuleHashMismatch,
+    Immutable,
+    NotALedger,
+    ImmutabilityNotConfirmed,
+    InvalidController,
+    LastController,
+    InvalidCanisterSettings(String),
+    InvalidTopUpAmount,
+    TopUpFailed(String),
+    InsufficientPrepaidBalance {
+        balance: u64,
+        required: u64,
+    },
+    NoAutoTopUp,
+    Deleted,
+    CanisterStopFailed(String),
+    CanisterStartFailed(String),
+    CanisterDeletionFailed(String),
+    CyclesRecoveryFailed(String),
+    InvalidPricing(String),
+    FeeExceedsMax {
+        fee: u64,
+        max_fee: u64,
+    },
+    WithdrawalFailed(String),
+    IcpPaymentFailed(String),
+    /// The ICP was taken but not yet converted to cycles. The factory keeps notifying the CMC, and
+    /// credits the cycles to the payer's prepaid balance once converted.
+    IcpPaymentPending {
+        block_index: u64,
+    },
+    InvalidPromoCode(String),
+    Paused,
+    RateLimited {
+        retry_after: u64,
+    },
+    InvalidRateLimit(String),
+    Denylisted,
+    NotAllowlisted,
+    InvalidLogo(String),
+}
+
+impl CreateCanisterError {
+    /// Name of the variant, as reported in the metrics.
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        match self {
+            CreateCanisterError::NoWasmStored => "NoWasmStored",
+            CreateCanisterError::CanisterCreationFailed(_) => "CanisterCreationFailed",
+            CreateCanisterError::InitArgsEncodingFailed(_) => "InitArgsEncodingFailed",
+            CreateCanisterError::WasmInstallationFailed(_) => "WasmInstallationFailed",
+            CreateCanisterError::PaymentError(_) => "PaymentError",
+            CreateCanisterError::CanisterNotFound => "CanisterNotFound",
+            CreateCanisterError::NotOwner => "NotOwner",
+            CreateCanisterError::InvalidNewOwner => "InvalidNewOwner",
+            CreateCanisterError::NoPendingOwnershipTransfer => "NoPendingOwnershipTransfer",
+            CreateCanisterError::CanisterStatusFailed(_) => "CanisterStatusFailed",
+            CreateCanisterError::UpdateSettingsFailed(_) => "UpdateSettingsFailed",
+            CreateCanisterError::ArchiveListingFailed(_) => "ArchiveListingFailed",
+            CreateCanisterError::Sovereign => "Sovereign",
+            CreateCanisterError::ModuleHashMismatch => "ModuleHashMismatch",
+            CreateCanisterError::Immutable => "Immutable",
+            CreateCanisterError::NotALedger => "NotALedger",
+            CreateCanisterError::ImmutabilityNotConfirmed => "ImmutabilityNotConfirmed",
+            CreateCanisterError::InvalidController => "InvalidController",
+            CreateCanisterError::LastController => "LastController",
+            CreateCanisterError::InvalidCanisterSettings(_) => "InvalidCanisterSettings",
+            CreateCanisterError::InvalidTopUpAmount => "InvalidTopUpAmount",
+            CreateCanisterError::TopUpFailed(_) => "TopUpFailed",
+            CreateCanisterError::InsufficientPrepaidBalance { .. } => "InsufficientPrepaidBalance",
+            CreateCanisterError::NoAutoTopUp => "NoAutoTopUp",
+            CreateCanisterError::Deleted => "Deleted",
+            CreateCanisterError::CanisterStopFailed(_) => "CanisterStopFailed",
+            CreateCanisterError::CanisterStartFailed(_) => "CanisterStartFailed",
+            CreateCanisterError::CanisterDeletionFailed(_) => "CanisterDeletionFailed",
+            CreateCanisterError::CyclesRecoveryFailed(_) => "CyclesRecoveryFailed",
+            CreateCanisterError::InvalidPricing(_) => "InvalidPricing",
+            CreateCanisterError::FeeExceedsMax { .. } => "FeeExceedsMax",
+            CreateCanisterError::WithdrawalFailed(_) => "WithdrawalFailed",
+            CreateCanisterError::IcpPaymentFailed(_) => "IcpPaymentFailed",
+            CreateCanisterError::IcpPaymentPending { .. } => "IcpPaymentPending",
+            CreateCanisterError::InvalidPromoCode(_) => "InvalidPromoCode",
+            CreateCanisterError::Paused => "Paused",
+            CreateCanisterError::RateLimited { .. } => "RateLimited",
+            CreateCanisterError::InvalidRateLimit(_) => "InvalidRateLimit",
+            CreateCanisterError::Denylisted => "Denylisted",
+            CreateCanisterError::NotAllowlisted => "NotAllowlisted",
+            CreateCanisterError::InvalidLogo(_) => "InvalidLogo",
+        }
+    }
 }
 
 #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
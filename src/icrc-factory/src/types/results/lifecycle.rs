@@ -0,0 +1,10 @@
+use candid::{CandidType, Deserialize, Nat};
+
+use crate::types::results::create_canister::CreateCanisterError;
+
+#[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+pub enum DeleteCanisterResult {
+    /// Cycles recovered from the deleted canister, and from its archives for a ledger.
+    Ok(Nat),
+    Err(CreateCanisterError),
+}
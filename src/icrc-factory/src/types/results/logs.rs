@@ -0,0 +1,9 @@
+use candid::{CandidType, Deserialize};
+
+use crate::types::{log::LogEntry, results::create_canister::CreateCanisterError};
+
+#[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+pub enum ListLogsResult {
+    Ok(Vec<LogEntry>),
+    Err(CreateCanisterError),
+}
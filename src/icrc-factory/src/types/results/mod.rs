@@ -1,2 +1,6 @@
+pub mod canister_status;
 pub mod create_canister;
+pub mod lifecycle;
+pub mod logs;
+pub mod quote;
 pub mod set_wasm;
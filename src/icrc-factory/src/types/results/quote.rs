@@ -0,0 +1,9 @@
+use candid::{CandidType, Deserialize};
+
+use crate::types::{quote::FeeQuote, results::create_canister::CreateCanisterError};
+
+#[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+pub enum QuoteResult {
+    Ok(FeeQuote),
+    Err(CreateCanisterError),
+}
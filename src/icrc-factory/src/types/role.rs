@@ -0,0 +1,25 @@
+use candid::{CandidType, Deserialize, Principal};
+use serde::Serialize;
+
+/// An admin role, granting access to a class of privileged endpoints without making its holder a
+/// controller of the factory.
+#[derive(
+    CandidType, Serialize, Deserialize, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Debug,
+)]
+pub enum Role {
+    /// Stores the ledger and index WASMs installed by the factory.
+    WasmManager,
+    /// Sets the pricing, free quotas, promo codes and custom pricing.
+    PricingManager,
+    /// Runs the factory day to day, e.g. pauses and resumes operations in an emergency.
+    Operator,
+    /// Reads the audit data: promo codes and their redemptions, custom pricing, logs and roles.
+    Auditor,
+}
+
+/// The roles granted to a principal.
+#[derive(CandidType, Serialize, Deserialize, Clone, Eq, PartialEq, Debug)]
+pub struct RoleAssignment {
+    pub principal: Principal,
+    pub roles: Vec<Role>,
+}
@@ -0,0 +1,14 @@
+use candid::{CandidType, Deserialize, Principal};
+use serde::Serialize;
+
+/// Metadata of a ledger created by the factory, as listed in the token list.
+#[derive(CandidType, Serialize, Deserialize, Clone, Eq, PartialEq, Debug)]
+pub struct TokenMetadata {
+    pub symbol: String,
+    pub name: String,
+    pub decimals: u8,
+    /// Logo of the token, usually a data URL, as stored in the `icrc1:logo` ledger metadata.
+    pub logo: Option<String>,
+    /// Index canister of the ledger, once one is created or set through the factory.
+    pub index_id: Option<Principal>,
+}
@@ -12,4 +12,58 @@ pub struct UserCanister {
     pub canister_id: Principal,
     pub kind: UserCanisterKind,
     pub installed: bool,
+    /// Principal that the current owner proposed as the new owner, if a transfer is pending.
+    pub pending_owner: Option<Principal>,
+    /// Set once the factory has removed itself from the canister's controllers.
+    pub sovereign: Option<bool>,
+    /// Module hash of a ledger made immutable; no further upgrade is possible once set.
+    pub final_module_hash: Option<Vec<u8>>,
+    /// Controllers of the canister other than the factory, as last set through the factory.
+    pub controllers: Option<Vec<Principal>>,
+    /// Set once the canister has been deleted through the factory; the entry is kept for history.
+    pub deleted: Option<bool>,
+    /// Principal that a ledger's archives were handed over to, set as soon as they are, so that an
+    /// interrupted renunciation can be resumed.
+    pub archive_controller: Option<Principal>,
+}
+
+impl UserCanister {
+    /// Creates a registry entry whose only controller besides the factory is `owner`.
+    #[must_use]
+    pub fn new(
+        canister_id: Principal,
+        kind: UserCanisterKind,
+        owner: Principal,
+        installed: bool,
+    ) -> Self {
+        Self {
+            canister_id,
+            kind,
+            installed,
+            pending_owner: None,
+            sovereign: None,
+            final_module_hash: None,
+            controllers: Some(vec![owner]),
+            deleted: None,
+            archive_controller: None,
+        }
+    }
+
+    /// Whether the factory has relinquished control of the canister.
+    #[must_use]
+    pub fn is_sovereign(&self) -> bool {
+        self.sovereign.unwrap_or(false)
+    }
+
+    /// Whether the canister has been deleted.
+    #[must_use]
+    pub fn is_deleted(&self) -> bool {
+        self.deleted.unwrap_or(false)
+    }
+
+    /// Whether the canister has been made immutable.
+    #[must_use]
+    pub fn is_immutable(&self) -> bool {
+        self.final_module_hash.is_some()
+    }
 }
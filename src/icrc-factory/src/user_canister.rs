@@ -1,16 +1,29 @@
-use crate::types::{
-    candid::Candid, memory::UserCanisterMap, stored_principal::StoredPrincipal,
-    user_canister::UserCanister,
+use candid::Principal;
+
+use crate::{
+    certification::{certify_registry, certify_user_canisters},
+    state::{mutate_state, read_state, State},
+    tokens::schedule_token_list_refresh,
+    types::{
+        candid::Candid, memory::UserCanisterMap, results::create_canister::CreateCanisterError,
+        stored_principal::StoredPrincipal, user_canister::UserCanister,
+    },
 };
 
 const MAX_USER_CANISTER_LIST_LENGTH: usize = 1000;
 
+/// Inserts or replaces a canister in an owner's list, indexes its owner, certifies the list, and
+/// refreshes the token list.
 pub fn upsert_user_canister(
     stored_principal: StoredPrincipal,
-    user_canister: &mut UserCanisterMap,
+    state: &mut State,
     new_entry: UserCanister,
 ) {
-    let Candid(mut canisters) = user_canister.get(&stored_principal).unwrap_or_default();
+    let Candid(mut canisters) = state
+        .user_canister
+        .get(&stored_principal)
+        .unwrap_or_default();
+    let canister_id = new_entry.canister_id;
 
     if let Some(existing) = canisters
         .iter_mut()
@@ -27,5 +40,166 @@ pub fn upsert_user_canister(
         canisters.push(new_entry);
     }
 
-    user_canister.insert(stored_principal, Candid(canisters));
+    certify_user_canisters(stored_principal.0, &canisters);
+    state
+        .user_canister
+        .insert(stored_principal, Candid(canisters));
+    state
+        .canister_owners
+        .insert(StoredPrincipal(canister_id), stored_principal);
+
+    schedule_token_list_refresh();
+}
+
+/// Removes a canister from an owner's list and from the owner index, returning the removed entry
+/// if it was present.
+pub fn remove_user_canister(
+    stored_principal: StoredPrincipal,
+    state: &mut State,
+    canister_id: Principal,
+) -> Option<UserCanister> {
+    let Candid(mut canisters) = state.user_canister.get(&stored_principal)?;
+
+    let position = canisters
+        .iter()
+        .position(|c| c.canister_id == canister_id)?;
+    let removed = canisters.remove(position);
+
+    certify_user_canisters(stored_principal.0, &canisters);
+    if canisters.is_empty() {
+        state.user_canister.remove(&stored_principal);
+    } else {
+        state
+            .user_canister
+            .insert(stored_principal, Candid(canisters));
+    }
+    state.canister_owners.remove(&StoredPrincipal(canister_id));
+
+    schedule_token_list_refresh();
+
+    Some(removed)
+}
+
+/// Certifies every registry entry again, as the certified tree is kept in heap memory and lost on
+/// upgrade.
+pub fn init_certified_registry() {
+    let registry: Vec<(Principal, Vec<UserCanister>)> = read_state(|s| {
+        s.user_canister
+            .keys()
+            .filter_map(|owner| {
+                s.user_canister
+                    .get(&owner)
+                    .map(|Candid(canisters)| (owner.0, canisters))
+            })
+            .collect()
+    });
+
+    certify_registry(registry);
+}
+
+/// Indexes the owner of every canister in the registry, for registries stored before the owner
+/// index existed.
+pub fn init_canister_owners() {
+    mutate_state(|s| {
+        if !s.canister_owners.is_empty() {
+            return;
+        }
+
+        let owners: Vec<(StoredPrincipal, StoredPrincipal)> = s
+            .user_canister
+            .keys()
+            .filter_map(|owner| s.user_canister.get(&owner).map(|Candid(c)| (owner, c)))
+            .flat_map(|(owner, canisters)| {
+                canisters
+                    .into_iter()
+                    .map(move |c| (StoredPrincipal(c.canister_id), owner))
+            })
+            .collect();
+
+        for (canister_id, owner) in owners {
+            s.canister_owners.insert(canister_id, owner);
+        }
+    });
+}
+
+/// Returns up to `limit` registry entries, with their owner, after skipping the first `offset`,
+/// ordered by owner and then by creation.
+pub fn list_canisters_page(
+    user_canister: &UserCanisterMap,
+    offset: u64,
+    limit: u64,
+) -> Vec<(Principal, UserCanister)> {
+    let offset = usize::try_from(offset).unwrap_or(usize::MAX);
+    let limit = usize::try_from(limit).unwrap_or(usize::MAX);
+
+    user_canister
+        .keys()
+        .filter_map(|owner| {
+            user_canister
+                .get(&owner)
+                .map(|Candid(canisters)| (owner.0, canisters))
+        })
+        .flat_map(|(owner, canisters)| canisters.into_iter().map(move |c| (owner, c)))
+        .skip(offset)
+        .take(limit)
+        .collect()
+}
+
+/// Looks up a canister through the owner index, returning its owner and registry entry.
+pub fn find_user_canister(
+    state: &State,
+    canister_id: Principal,
+) -> Option<(StoredPrincipal, UserCanister)> {
+    let owner = state.canister_owners.get(&StoredPrincipal(canister_id))?;
+    let Candid(canisters) = state.user_canister.get(&owner)?;
+
+    canisters
+        .into_iter()
+        .find(|c| c.canister_id == canister_id)
+        .map(|c| (owner, c))
+}
+
+/// Returns the registry entry of a canister owned by `owner`.
+///
+/// # Errors
+/// - `CanisterNotFound` if the canister is not in the registry.
+/// - `NotOwner` if the canister is registered under a different owner.
+/// - `Deleted` if the canister has been deleted.
+pub fn get_owned_user_canister(
+    state: &State,
+    owner: Principal,
+    canister_id: Principal,
+) -> Result<UserCanister, CreateCanisterError> {
+    match find_user_canister(state, canister_id) {
+        Some((StoredPrincipal(found_owner), _)) if found_owner != owner => {
+            Err(CreateCanisterError::NotOwner)
+        }
+        Some((_, canister)) if canister.is_deleted() => Err(CreateCanisterError::Deleted),
+        Some((_, canister)) => Ok(canister),
+        None => Err(CreateCanisterError::CanisterNotFound),
+    }
+}
+
+/// Returns the registry entry of a canister owned by `owner` that the factory still controls.
+///
+/// # Errors
+/// - Any error of [`get_owned_user_canister`].
+/// - `Immutable` if the canister has been made immutable.
+/// - `Sovereign` if the factory has relinquished control of the canister.
+pub fn get_managed_user_canister(
+    state: &State,
+    owner: Principal,
+    canister_id: Principal,
+) -> Result<UserCanister, CreateCanisterError> {
+    let canister = get_owned_user_canister(state, owner, canister_id)?;
+
+    if canister.is_immutable() {
+        return Err(CreateCanisterError::Immutable);
+    }
+
+    if canister.is_sovereign() {
+        return Err(CreateCanisterError::Sovereign);
+    }
+
+    Ok(canister)
 }
@@ -1,6 +1,8 @@
 use crate::{
+    events::record_event,
     state::{mutate_state, read_state},
-    wasm::utils::fetch_wasm_from_url,
+    types::event::EventKind,
+    wasm::utils::{fetch_wasm_from_url, module_hash},
 };
 
 pub fn get_stored_index_wasm() -> Vec<u8> {
@@ -8,14 +10,23 @@ pub fn get_stored_index_wasm() -> Vec<u8> {
 }
 
 pub fn set_index_wasm(wasm: Vec<u8>) {
-    mutate_state(|s| {
-        s.icrc_index_wasm.set(wasm);
-    });
+    store_index_wasm(wasm, None);
 }
 
 pub async fn set_index_wasm_from_url(url: String) -> Result<usize, String> {
-    let response = fetch_wasm_from_url(url).await?;
+    let response = fetch_wasm_from_url(url.clone()).await?;
     let len = response.body.len();
-    set_index_wasm(response.body);
+    store_index_wasm(response.body, Some(url));
     Ok(len)
 }
+
+/// Replaces the stored index WASM, and records the replacement with the hash of the new module.
+fn store_index_wasm(wasm: Vec<u8>, url: Option<String>) {
+    let module_hash = module_hash(&wasm);
+
+    mutate_state(|s| {
+        s.icrc_index_wasm.set(wasm);
+    });
+
+    record_event(EventKind::IndexWasmSet { module_hash, url });
+}
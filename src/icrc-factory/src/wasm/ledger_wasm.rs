@@ -1,6 +1,8 @@
 use crate::{
+    events::record_event,
     state::{mutate_state, read_state},
-    wasm::utils::fetch_wasm_from_url,
+    types::event::EventKind,
+    wasm::utils::{fetch_wasm_from_url, module_hash},
 };
 
 pub fn get_stored_ledger_wasm() -> Vec<u8> {
@@ -8,14 +10,23 @@ pub fn get_stored_ledger_wasm() -> Vec<u8> {
 }
 
 pub fn set_ledger_wasm(wasm: Vec<u8>) {
-    mutate_state(|s| {
-        s.icrc_ledger_wasm.set(wasm);
-    });
+    store_ledger_wasm(wasm, None);
 }
 
 pub async fn set_ledger_wasm_from_url(url: String) -> Result<usize, String> {
-    let response = fetch_wasm_from_url(url).await?;
+    let response = fetch_wasm_from_url(url.clone()).await?;
     let len = response.body.len();
-    set_ledger_wasm(response.body);
+    store_ledger_wasm(response.body, Some(url));
     Ok(len)
 }
+
+/// Replaces the stored ledger WASM, and records the replacement with the hash of the new module.
+fn store_ledger_wasm(wasm: Vec<u8>, url: Option<String>) {
+    let module_hash = module_hash(&wasm);
+
+    mutate_state(|s| {
+        s.icrc_ledger_wasm.set(wasm);
+    });
+
+    record_event(EventKind::LedgerWasmSet { module_hash, url });
+}
@@ -2,6 +2,7 @@ use ic_cdk::api::management_canister::http_request::{
     http_request, CanisterHttpRequestArgument, HttpHeader, HttpMethod, HttpResponse, TransformArgs,
     TransformContext,
 };
+use sha2::{Digest, Sha256};
 
 pub async fn fetch_wasm_from_url(url: String) -> Result<HttpResponse, String> {
     let request_headers = vec![HttpHeader {
@@ -44,3 +45,9 @@ pub fn transform_wasm_response(args: TransformArgs) -> HttpResponse {
     res.headers = vec![];
     res
 }
+
+/// SHA-256 hash of a WASM module, as reported in the `module_hash` of a canister running it.
+#[must_use]
+pub fn module_hash(wasm: &[u8]) -> Vec<u8> {
+    Sha256::digest(wasm).to_vec()
+}
@@ -0,0 +1,141 @@
+use candid::Principal;
+use icrc_factory::types::{
+    access_list::{AccessListEntry, CreationAccess},
+    args::{access_list::SetAccessListArgs, create_canister::CreateIcrcLedgerArgs},
+    results::create_canister::{CreateCanisterError, CreateCanisterResult, SetCanisterResult},
+};
+
+use crate::utils::{
+    ledger::{create_paid_ledger, create_paid_ledger_with_args, setup_paid_factory},
+    pocketic::{caller, controller, user_1, PicBackend, PicCanisterTrait},
+};
+
+fn set_list(
+    factory: &PicBackend,
+    method: &str,
+    principals: Vec<Principal>,
+    listed: bool,
+) -> SetCanisterResult {
+    factory
+        .update(
+            controller(),
+            method,
+            SetAccessListArgs { principals, listed },
+        )
+        .expect("Failed to update the access list")
+}
+
+fn list(factory: &PicBackend, method: &str) -> Vec<AccessListEntry> {
+    factory
+        .query_with_args(controller(), method, (None::<u64>, None::<u64>))
+        .expect("Failed to list the access list")
+}
+
+fn create_ledger(
+    factory: &PicBackend,
+    payment_ledger: Principal,
+    owner: Principal,
+) -> CreateCanisterResult {
+    let args = CreateIcrcLedgerArgs {
+        symbol: Some("TKN".to_string()),
+        name: Some("Token".to_string()),
+        transfer_fee: None,
+        decimals: None,
+        minting_account: None,
+        settings: None,
+        logo: None,
+    };
+
+    create_paid_ledger_with_args(factory, payment_ledger, owner, args)
+}
+
+#[test]
+fn test_denylisted_principals_cannot_create() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+
+    let result = set_list(&factory, "set_creation_denylist", vec![caller()], true);
+    assert_eq!(result, SetCanisterResult::Ok());
+
+    let denylist = list(&factory, "list_creation_denylist");
+    assert_eq!(denylist.len(), 1);
+    assert_eq!(denylist[0].principal, caller());
+
+    assert_eq!(
+        create_ledger(&factory, payment_ledger, caller()),
+        CreateCanisterResult::Err(CreateCanisterError::Denylisted)
+    );
+    create_paid_ledger(&factory, payment_ledger, user_1());
+
+    let result = set_list(&factory, "set_creation_denylist", vec![caller()], false);
+    assert_eq!(result, SetCanisterResult::Ok());
+    assert!(list(&factory, "list_creation_denylist").is_empty());
+
+    create_paid_ledger(&factory, payment_ledger, caller());
+}
+
+#[test]
+fn test_allowlist_only_admits_allowlisted_principals() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+
+    let result = set_list(&factory, "set_creation_allowlist", vec![caller()], true);
+    assert_eq!(result, SetCanisterResult::Ok());
+
+    // The allowlist does not apply while creations are open.
+    create_paid_ledger(&factory, payment_ledger, user_1());
+
+    let result: SetCanisterResult = factory
+        .update(
+            controller(),
+            "set_creation_access",
+            CreationAccess::AllowlistOnly,
+        )
+        .expect("Failed to call set_creation_access");
+    assert_eq!(result, SetCanisterResult::Ok());
+
+    assert_eq!(
+        create_ledger(&factory, payment_ledger, user_1()),
+        CreateCanisterResult::Err(CreateCanisterError::NotAllowlisted)
+    );
+    create_paid_ledger(&factory, payment_ledger, caller());
+
+    // The denylist takes precedence over the allowlist.
+    let result = set_list(&factory, "set_creation_denylist", vec![caller()], true);
+    assert_eq!(result, SetCanisterResult::Ok());
+    assert_eq!(
+        create_ledger(&factory, payment_ledger, caller()),
+        CreateCanisterResult::Err(CreateCanisterError::Denylisted)
+    );
+
+    let allowlist = list(&factory, "list_creation_allowlist");
+    assert_eq!(allowlist.len(), 1);
+    assert_eq!(allowlist[0].principal, caller());
+}
+
+#[test]
+fn test_only_controllers_manage_access_lists() {
+    let (factory, _) = setup_paid_factory(None);
+
+    let result: Result<SetCanisterResult, _> = factory.update(
+        caller(),
+        "set_creation_denylist",
+        SetAccessListArgs {
+            principals: vec![user_1()],
+            listed: true,
+        },
+    );
+    assert!(result.is_err());
+
+    let result: Result<SetCanisterResult, _> = factory.update(
+        caller(),
+        "set_creation_access",
+        CreationAccess::AllowlistOnly,
+    );
+    assert!(result.is_err());
+
+    let result: Result<Vec<AccessListEntry>, _> = factory.query_with_args(
+        caller(),
+        "list_creation_allowlist",
+        (None::<u64>, None::<u64>),
+    );
+    assert!(result.is_err());
+}
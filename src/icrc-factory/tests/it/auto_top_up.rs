@@ -0,0 +1,192 @@
+use std::time::Duration;
+
+use candid::Principal;
+use icrc_factory::types::{
+    args::auto_top_up::{SetAutoTopUpArgs, WithdrawPrepaidBalanceArgs},
+    auto_top_up::AutoTopUp,
+    event::{Event, EventKind},
+    results::create_canister::{CreateCanisterError, SetCanisterResult},
+};
+use icrc_ledger_types::icrc1::account::Account;
+
+use crate::utils::{
+    ledger::{create_paid_ledger, setup_paid_factory},
+    pocketic::{caller, user_1, PicBackend, PicCanisterTrait},
+    prepaid::{fund_prepaid_balance, prepaid_balance},
+};
+
+const REFILL: u64 = 200_000_000_000;
+const TOP_UP_SERVICE_FEE: u64 = 10_000_000_000;
+
+/// Far above the balance of a freshly created ledger, so that every poll tops it up.
+const HIGH_THRESHOLD: u64 = 100_000_000_000_000;
+
+fn withdraw_prepaid_balance(factory: &PicBackend, cycles: u64) -> SetCanisterResult {
+    let args = WithdrawPrepaidBalanceArgs {
+        cycles,
+        to: Account {
+            owner: caller(),
+            subaccount: None,
+        },
+    };
+
+    factory
+        .update(caller(), "withdraw_prepaid_balance", args)
+        .expect("Failed to call withdraw_prepaid_balance")
+}
+
+fn set_auto_top_up(
+    factory: &PicBackend,
+    caller: Principal,
+    canister_id: Principal,
+) -> SetCanisterResult {
+    factory
+        .update(
+            caller,
+            "set_auto_top_up",
+            SetAutoTopUpArgs {
+                canister_id,
+                threshold: HIGH_THRESHOLD,
+                refill: REFILL,
+            },
+        )
+        .expect("Failed to call set_auto_top_up")
+}
+
+fn list_events(factory: &PicBackend) -> Vec<Event> {
+    factory
+        .query_with_args(caller(), "list_events", (None::<u64>, Some(1_000u64)))
+        .expect("Failed to query list_events")
+}
+
+/// Lets the auto top-up timer fire until an event matching `predicate` is recorded.
+fn wait_for_event(factory: &PicBackend, predicate: impl Fn(&EventKind) -> bool) -> Event {
+    factory.pic.advance_time(Duration::from_secs(11 * 60));
+
+    for _ in 0..50 {
+        factory.pic.tick();
+
+        if let Some(event) = list_events(factory)
+            .into_iter()
+            .find(|event| predicate(&event.kind))
+        {
+            return event;
+        }
+    }
+
+    panic!("Expected event was not recorded");
+}
+
+#[test]
+fn test_fund_prepaid_balance() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+
+    fund_prepaid_balance(&factory, payment_ledger, 1_000_000_000_000);
+
+    assert_eq!(prepaid_balance(&factory), 1_000_000_000_000);
+}
+
+#[test]
+fn test_auto_top_up_tops_up_from_prepaid_balance() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+    let ledger_id = create_paid_ledger(&factory, payment_ledger, caller());
+
+    fund_prepaid_balance(&factory, payment_ledger, 1_000_000_000_000);
+
+    assert_eq!(
+        set_auto_top_up(&factory, caller(), ledger_id),
+        SetCanisterResult::Ok()
+    );
+
+    let subscriptions: Vec<AutoTopUp> = factory
+        .query(caller(), "list_auto_top_ups", ())
+        .expect("Failed to query list_auto_top_ups");
+    assert_eq!(subscriptions.len(), 1);
+    assert_eq!(subscriptions[0].canister_id, ledger_id);
+
+    let balance_before = factory.pic.cycle_balance(ledger_id);
+
+    wait_for_event(&factory, |kind| {
+        matches!(kind, EventKind::AutoToppedUp { canister_id, cycles, .. }
+            if *canister_id == ledger_id && *cycles == REFILL)
+    });
+
+    assert!(factory.pic.cycle_balance(ledger_id) > balance_before);
+    assert!(prepaid_balance(&factory) <= 1_000_000_000_000 - REFILL - TOP_UP_SERVICE_FEE);
+}
+
+#[test]
+fn test_exhausted_prepaid_balance_is_reported() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+    let ledger_id = create_paid_ledger(&factory, payment_ledger, caller());
+
+    // Less than a single refill.
+    fund_prepaid_balance(&factory, payment_ledger, 100_000_000_000);
+
+    assert_eq!(
+        set_auto_top_up(&factory, caller(), ledger_id),
+        SetCanisterResult::Ok()
+    );
+
+    let event = wait_for_event(&factory, |kind| {
+        matches!(kind, EventKind::PrepaidBalanceExhausted { .. })
+    });
+
+    assert_eq!(
+        event.kind,
+        EventKind::PrepaidBalanceExhausted {
+            owner: caller(),
+            canister_id: ledger_id,
+            balance: 100_000_000_000,
+            required: REFILL + TOP_UP_SERVICE_FEE,
+        }
+    );
+    assert_eq!(prepaid_balance(&factory), 100_000_000_000);
+}
+
+#[test]
+fn test_set_auto_top_up_by_non_owner_fails() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+    let ledger_id = create_paid_ledger(&factory, payment_ledger, caller());
+
+    assert_eq!(
+        set_auto_top_up(&factory, user_1(), ledger_id),
+        SetCanisterResult::Err(CreateCanisterError::NotOwner)
+    );
+}
+
+#[test]
+fn test_withdraw_more_than_prepaid_balance_fails() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+
+    fund_prepaid_balance(&factory, payment_ledger, 100_000_000_000);
+
+    assert_eq!(
+        withdraw_prepaid_balance(&factory, 200_000_000_000),
+        SetCanisterResult::Err(CreateCanisterError::InsufficientPrepaidBalance {
+            balance: 100_000_000_000,
+            required: 200_000_000_000,
+        })
+    );
+    assert_eq!(prepaid_balance(&factory), 100_000_000_000);
+}
+
+#[test]
+fn test_failed_withdrawal_restores_prepaid_balance() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+
+    fund_prepaid_balance(&factory, payment_ledger, 100_000_000_000);
+
+    // The factory's cycles ledger is a plain ICRC ledger in these tests, which cannot take
+    // deposits.
+    let result = withdraw_prepaid_balance(&factory, 50_000_000_000);
+
+    assert!(
+        matches!(
+            result,
+            SetCanisterResult::Err(CreateCanisterError::WithdrawalFailed(_))
+        ),
+        "Unexpected result: {result:?}"
+    );
+    assert_eq!(prepaid_balance(&factory), 100_000_000_000);
+}
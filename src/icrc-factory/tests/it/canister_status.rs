@@ -0,0 +1,90 @@
+use candid::Principal;
+use icrc_factory::types::{
+    canister_status::CanisterRunStatus,
+    results::{
+        canister_status::{CanisterStatusEntry, CanisterStatusResult},
+        create_canister::{CreateCanisterError, SetCanisterResult},
+    },
+};
+
+use crate::utils::{
+    ledger::{create_paid_ledger, setup_paid_factory},
+    pocketic::{caller, user_1, PicBackend, PicCanisterTrait},
+};
+
+fn canister_status(
+    factory: &PicBackend,
+    caller: Principal,
+    canister_id: Principal,
+) -> CanisterStatusResult {
+    factory
+        .update(caller, "canister_status", canister_id)
+        .expect("Failed to call canister_status")
+}
+
+#[test]
+fn test_owner_gets_canister_status() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+    let ledger_id = create_paid_ledger(&factory, payment_ledger, caller());
+
+    let CanisterStatusResult::Ok(status) = canister_status(&factory, caller(), ledger_id) else {
+        panic!("Expected the ledger status");
+    };
+
+    let expected = factory
+        .pic
+        .canister_status(ledger_id, Some(caller()))
+        .expect("The owner should be able to read the ledger status");
+
+    assert_eq!(status.canister_id, ledger_id);
+    assert_eq!(status.status, CanisterRunStatus::Running);
+    assert_eq!(status.module_hash, expected.module_hash);
+    assert_eq!(status.controllers.len(), 2);
+    assert!(status.controllers.contains(&caller()));
+    assert!(status.controllers.contains(&factory.canister_id));
+}
+
+#[test]
+fn test_canister_status_by_non_owner_fails() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+    let ledger_id = create_paid_ledger(&factory, payment_ledger, caller());
+
+    assert_eq!(
+        canister_status(&factory, user_1(), ledger_id),
+        CanisterStatusResult::Err(CreateCanisterError::NotOwner)
+    );
+}
+
+#[test]
+fn test_canister_statuses_covers_all_owned_canisters() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+    let managed_id = create_paid_ledger(&factory, payment_ledger, caller());
+    let sovereign_id = create_paid_ledger(&factory, payment_ledger, caller());
+    create_paid_ledger(&factory, payment_ledger, user_1());
+
+    let result: SetCanisterResult = factory
+        .update(caller(), "renounce_factory_control", sovereign_id)
+        .expect("Failed to call renounce_factory_control");
+    assert_eq!(result, SetCanisterResult::Ok());
+
+    let entries: Vec<CanisterStatusEntry> = factory
+        .update(caller(), "canister_statuses", ())
+        .expect("Failed to call canister_statuses");
+
+    assert_eq!(entries.len(), 2);
+
+    let managed = entries
+        .iter()
+        .find(|entry| entry.canister_id == managed_id)
+        .expect("The managed ledger should be reported");
+    assert!(matches!(managed.result, CanisterStatusResult::Ok(_)));
+
+    let sovereign = entries
+        .iter()
+        .find(|entry| entry.canister_id == sovereign_id)
+        .expect("The sovereign ledger should be reported");
+    assert_eq!(
+        sovereign.result,
+        CanisterStatusResult::Err(CreateCanisterError::Sovereign)
+    );
+}
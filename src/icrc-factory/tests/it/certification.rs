@@ -0,0 +1,79 @@
+use candid::Principal;
+use ic_certification::{HashTree, LookupResult};
+use icrc_factory::types::{
+    certified::{CertifiedConfig, CertifiedUserCanisters},
+    user_canister::UserCanister,
+};
+use sha2::{Digest, Sha256};
+
+use crate::utils::{
+    certification::verified_witness,
+    ledger::{create_paid_ledger, setup_paid_factory},
+    pocketic::{caller, PicCanisterTrait},
+};
+
+fn candid_hash<T: candid::CandidType>(value: &T) -> Vec<u8> {
+    Sha256::digest(candid::encode_one(value).expect("Candid encoding")).to_vec()
+}
+
+fn assert_entry_certified(witness: &HashTree, owner: Principal, canister: &UserCanister) {
+    assert_eq!(
+        witness.lookup_path([
+            b"registry".as_slice(),
+            owner.as_slice(),
+            canister.canister_id.as_slice(),
+        ]),
+        LookupResult::Found(candid_hash(canister).as_slice())
+    );
+}
+
+#[test]
+fn test_user_canisters_are_certified() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+    create_paid_ledger(&factory, payment_ledger, caller());
+
+    let response: CertifiedUserCanisters = factory
+        .query(caller(), "list_user_canisters_certified", ())
+        .expect("Failed to query list_user_canisters_certified");
+
+    let witness = verified_witness(&factory, &response.certificate, &response.witness);
+
+    assert_eq!(response.canisters.len(), 1);
+    assert_entry_certified(&witness, caller(), &response.canisters[0]);
+}
+
+#[test]
+fn test_canister_pages_are_certified() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+    create_paid_ledger(&factory, payment_ledger, caller());
+    create_paid_ledger(&factory, payment_ledger, caller());
+
+    let response: CertifiedUserCanisters = factory
+        .query_with_args(
+            caller(),
+            "list_all_canisters_paginated_certified",
+            (Some(1u64), Some(10u64)),
+        )
+        .expect("Failed to query list_all_canisters_paginated_certified");
+
+    let witness = verified_witness(&factory, &response.certificate, &response.witness);
+
+    assert_eq!(response.canisters.len(), 1);
+    assert_entry_certified(&witness, caller(), &response.canisters[0]);
+}
+
+#[test]
+fn test_config_is_certified() {
+    let (factory, _) = setup_paid_factory(None);
+
+    let response: CertifiedConfig = factory
+        .query(caller(), "config_certified", ())
+        .expect("Failed to query config_certified");
+
+    let witness = verified_witness(&factory, &response.certificate, &response.witness);
+
+    assert_eq!(
+        witness.lookup_path([b"config".as_slice()]),
+        LookupResult::Found(candid_hash(&response.config).as_slice())
+    );
+}
@@ -89,6 +89,9 @@ mod upgrade {
 
         let init_args = encode_one(Some(Args::Init(InitArgs {
             cycles_ledger: Some(new_ledger),
+            blackhole_canister: None,
+            icp_ledger: None,
+            cmc: None,
         })))
         .expect("encode Some(Args::Init)");
 
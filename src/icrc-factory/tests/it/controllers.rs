@@ -0,0 +1,160 @@
+use candid::Principal;
+use icrc_factory::types::{
+    args::controllers::{AddControllerArgs, RemoveControllerArgs},
+    results::create_canister::{CreateCanisterError, SetCanisterResult},
+    user_canister::UserCanister,
+};
+
+use crate::utils::{
+    ledger::{create_paid_ledger, setup_paid_factory},
+    pocketic::{caller, user_1, PicBackend, PicCanisterTrait},
+};
+
+fn add_controller(
+    factory: &PicBackend,
+    canister_id: Principal,
+    controller: Principal,
+) -> SetCanisterResult {
+    factory
+        .update(
+            caller(),
+            "add_controller",
+            AddControllerArgs {
+                canister_id,
+                controller,
+            },
+        )
+        .expect("Failed to call add_controller")
+}
+
+fn remove_controller(
+    factory: &PicBackend,
+    canister_id: Principal,
+    controller: Principal,
+) -> SetCanisterResult {
+    factory
+        .update(
+            caller(),
+            "remove_controller",
+            RemoveControllerArgs {
+                canister_id,
+                controller,
+            },
+        )
+        .expect("Failed to call remove_controller")
+}
+
+fn registered_controllers(factory: &PicBackend, canister_id: Principal) -> Option<Vec<Principal>> {
+    let canisters: Vec<UserCanister> = factory
+        .query(caller(), "list_user_canisters", ())
+        .expect("Failed to query list_user_canisters");
+
+    canisters
+        .into_iter()
+        .find(|c| c.canister_id == canister_id)
+        .expect("Canister should be registered")
+        .controllers
+}
+
+fn live_controllers(factory: &PicBackend, canister_id: Principal) -> Vec<Principal> {
+    factory
+        .pic
+        .canister_status(canister_id, Some(factory.canister_id))
+        .expect("The factory should be able to read the canister status")
+        .settings
+        .controllers
+}
+
+#[test]
+fn test_created_ledger_registers_owner_as_controller() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+    let ledger_id = create_paid_ledger(&factory, payment_ledger, caller());
+
+    assert_eq!(
+        registered_controllers(&factory, ledger_id),
+        Some(vec![caller()])
+    );
+}
+
+#[test]
+fn test_add_and_remove_controller() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+    let ledger_id = create_paid_ledger(&factory, payment_ledger, caller());
+
+    assert_eq!(
+        add_controller(&factory, ledger_id, user_1()),
+        SetCanisterResult::Ok()
+    );
+
+    assert!(live_controllers(&factory, ledger_id).contains(&user_1()));
+    assert!(factory
+        .pic
+        .canister_status(ledger_id, Some(user_1()))
+        .is_ok());
+
+    let registered =
+        registered_controllers(&factory, ledger_id).expect("Controllers should be registered");
+    assert_eq!(registered.len(), 2);
+    assert!(registered.contains(&caller()));
+    assert!(registered.contains(&user_1()));
+
+    assert_eq!(
+        remove_controller(&factory, ledger_id, user_1()),
+        SetCanisterResult::Ok()
+    );
+
+    assert!(!live_controllers(&factory, ledger_id).contains(&user_1()));
+    assert_eq!(
+        registered_controllers(&factory, ledger_id),
+        Some(vec![caller()])
+    );
+}
+
+#[test]
+fn test_remove_last_owner_side_controller_fails() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+    let ledger_id = create_paid_ledger(&factory, payment_ledger, caller());
+
+    assert_eq!(
+        remove_controller(&factory, ledger_id, caller()),
+        SetCanisterResult::Err(CreateCanisterError::LastController)
+    );
+    assert!(live_controllers(&factory, ledger_id).contains(&caller()));
+}
+
+#[test]
+fn test_factory_cannot_be_added_or_removed() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+    let ledger_id = create_paid_ledger(&factory, payment_ledger, caller());
+
+    assert_eq!(
+        remove_controller(&factory, ledger_id, factory.canister_id),
+        SetCanisterResult::Err(CreateCanisterError::InvalidController)
+    );
+    assert_eq!(
+        add_controller(&factory, ledger_id, Principal::anonymous()),
+        SetCanisterResult::Err(CreateCanisterError::InvalidController)
+    );
+}
+
+#[test]
+fn test_add_controller_by_non_owner_fails() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+    let ledger_id = create_paid_ledger(&factory, payment_ledger, caller());
+
+    let result: SetCanisterResult = factory
+        .update(
+            user_1(),
+            "add_controller",
+            AddControllerArgs {
+                canister_id: ledger_id,
+                controller: user_1(),
+            },
+        )
+        .expect("Failed to call add_controller");
+
+    assert_eq!(
+        result,
+        SetCanisterResult::Err(CreateCanisterError::NotOwner)
+    );
+}
@@ -0,0 +1,273 @@
+use candid::{Nat, Principal};
+use ic_papi_api::{caller::CallerPaysIcrc2Tokens, PaymentType};
+use icrc_factory::{
+    methods::SignerMethods,
+    types::{
+        args::{
+            create_canister::CreateIcrcLedgerArgs,
+            discounts::{SetCustomPricingArgs, SetFreeQuotaArgs, SetPromoCodeArgs},
+            pricing::SetPricingArgs,
+        },
+        discounts::{PromoCode, PromoCodeRedemption},
+        pricing::Pricing,
+        results::{
+            create_canister::{CreateCanisterError, CreateCanisterResult, SetCanisterResult},
+            quote::QuoteResult,
+        },
+    },
+};
+
+use crate::utils::{
+    ledger::{approve, balance_of, setup_paid_factory},
+    pocketic::{caller, controller, user_1, PicBackend, PicCanisterTrait},
+};
+
+const CREATION_ALLOWANCE: u64 = 2_000_000_000_000;
+
+/// Transfer fee of the payment ledger deployed by `setup_paid_factory`.
+const PAYMENT_LEDGER_FEE: u64 = 10_000;
+
+fn ledger_args() -> CreateIcrcLedgerArgs {
+    CreateIcrcLedgerArgs {
+        symbol: Some("TKN".to_string()),
+        name: Some("Token".to_string()),
+        transfer_fee: None,
+        decimals: None,
+        minting_account: None,
+        settings: None,
+        logo: None,
+    }
+}
+
+fn create_ledger(
+    factory: &PicBackend,
+    payment_ledger: Principal,
+    promo_code: Option<&str>,
+) -> CreateCanisterResult {
+    approve(
+        &factory.pic,
+        payment_ledger,
+        caller(),
+        factory.canister_id,
+        CREATION_ALLOWANCE,
+    );
+
+    let payment = PaymentType::CallerPaysIcrc2Tokens(CallerPaysIcrc2Tokens {
+        ledger: payment_ledger,
+    });
+
+    factory
+        .update_with_args(
+            caller(),
+            "create_icrc_ledger",
+            (
+                ledger_args(),
+                Some(payment),
+                None::<u64>,
+                promo_code.map(ToString::to_string),
+            ),
+        )
+        .expect("Failed to call create_icrc_ledger")
+}
+
+fn quoted_cycles(factory: &PicBackend, caller: Principal, promo_code: Option<&str>) -> u64 {
+    let result: QuoteResult = factory
+        .query_with_args(
+            caller,
+            "quote_create_icrc_ledger",
+            (ledger_args(), promo_code.map(ToString::to_string)),
+        )
+        .expect("Failed to query quote_create_icrc_ledger");
+
+    match result {
+        QuoteResult::Ok(quote) => quote.cycles,
+        QuoteResult::Err(err) => panic!("Unexpected quote error: {err:?}"),
+    }
+}
+
+fn set_promo_code(factory: &PicBackend, args: SetPromoCodeArgs) {
+    let result: SetCanisterResult = factory
+        .update(controller(), "set_promo_code", args)
+        .expect("Failed to call set_promo_code");
+
+    assert_eq!(result, SetCanisterResult::Ok());
+}
+
+#[test]
+fn test_free_quota_creates_without_payment() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+
+    let result: SetCanisterResult = factory
+        .update(
+            controller(),
+            "set_free_quota",
+            SetFreeQuotaArgs {
+                principal: caller(),
+                creations: 1,
+            },
+        )
+        .expect("Failed to call set_free_quota");
+    assert_eq!(result, SetCanisterResult::Ok());
+
+    assert_eq!(quoted_cycles(&factory, caller(), None), 0);
+
+    let balance_before = balance_of(&factory.pic, payment_ledger, caller());
+
+    let result = create_ledger(&factory, payment_ledger, None);
+    assert!(
+        matches!(result, CreateCanisterResult::Ok(_)),
+        "Unexpected result: {result:?}"
+    );
+
+    // Only the approval was paid for.
+    assert_eq!(
+        balance_of(&factory.pic, payment_ledger, caller()),
+        balance_before - Nat::from(PAYMENT_LEDGER_FEE)
+    );
+
+    let quota: u32 = factory
+        .query(caller(), "free_quota", ())
+        .expect("Failed to query free_quota");
+    assert_eq!(quota, 0);
+}
+
+#[test]
+fn test_promo_code_discounts_the_fee_and_is_audited() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+    let fee = Pricing::default().create_icrc_ledger;
+
+    set_promo_code(
+        &factory,
+        SetPromoCodeArgs {
+            code: "HALF".to_string(),
+            discount_bps: 5_000,
+            expires_at: None,
+            max_uses: Some(1),
+        },
+    );
+
+    assert_eq!(quoted_cycles(&factory, caller(), Some("HALF")), fee / 2);
+
+    let balance_before = balance_of(&factory.pic, payment_ledger, caller());
+
+    let result = create_ledger(&factory, payment_ledger, Some("HALF"));
+    assert!(
+        matches!(result, CreateCanisterResult::Ok(_)),
+        "Unexpected result: {result:?}"
+    );
+
+    // The discounted fee plus the approval and transfer fees of the payment ledger.
+    assert_eq!(
+        balance_of(&factory.pic, payment_ledger, caller()),
+        balance_before - Nat::from(fee / 2 + 2 * PAYMENT_LEDGER_FEE)
+    );
+
+    let redemptions: Vec<PromoCodeRedemption> = factory
+        .query_with_args(
+            controller(),
+            "list_promo_code_redemptions",
+            (None::<u64>, None::<u64>),
+        )
+        .expect("Failed to query list_promo_code_redemptions");
+
+    assert_eq!(redemptions.len(), 1);
+    assert_eq!(redemptions[0].code, "HALF");
+    assert_eq!(redemptions[0].redeemer, caller());
+    assert_eq!(redemptions[0].method, SignerMethods::CreateIcrcLedger);
+    assert_eq!(redemptions[0].fee, fee);
+    assert_eq!(redemptions[0].charged, fee / 2);
+
+    let codes: Vec<PromoCode> = factory
+        .query(controller(), "list_promo_codes", ())
+        .expect("Failed to query list_promo_codes");
+    assert_eq!(codes[0].uses, 1);
+
+    // The code was capped at a single use.
+    let result = create_ledger(&factory, payment_ledger, Some("HALF"));
+    assert!(matches!(
+        result,
+        CreateCanisterResult::Err(CreateCanisterError::InvalidPromoCode(_))
+    ));
+}
+
+#[test]
+fn test_expired_promo_code_is_refused() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+
+    set_promo_code(
+        &factory,
+        SetPromoCodeArgs {
+            code: "EXPIRED".to_string(),
+            discount_bps: 5_000,
+            expires_at: Some(1),
+            max_uses: None,
+        },
+    );
+
+    let balance_before = balance_of(&factory.pic, payment_ledger, caller());
+
+    let result = create_ledger(&factory, payment_ledger, Some("EXPIRED"));
+    assert!(matches!(
+        result,
+        CreateCanisterResult::Err(CreateCanisterError::InvalidPromoCode(_))
+    ));
+
+    // Only the approval was paid for.
+    assert_eq!(
+        balance_of(&factory.pic, payment_ledger, caller()),
+        balance_before - Nat::from(PAYMENT_LEDGER_FEE)
+    );
+}
+
+#[test]
+fn test_custom_pricing_applies_to_allowlisted_principal_only() {
+    let (factory, _) = setup_paid_factory(None);
+
+    let result: SetCanisterResult = factory
+        .update(
+            controller(),
+            "set_custom_pricing",
+            SetCustomPricingArgs {
+                principal: caller(),
+                pricing: Some(SetPricingArgs {
+                    create_icrc_ledger: Some(600_000_000_000),
+                    ..Default::default()
+                }),
+            },
+        )
+        .expect("Failed to call set_custom_pricing");
+    assert_eq!(result, SetCanisterResult::Ok());
+
+    assert_eq!(quoted_cycles(&factory, caller(), None), 600_000_000_000);
+    assert_eq!(
+        quoted_cycles(&factory, user_1(), None),
+        Pricing::default().create_icrc_ledger
+    );
+}
+
+#[test]
+fn test_discounts_by_non_controller_fail() {
+    let (factory, _) = setup_paid_factory(None);
+
+    let result: Result<SetCanisterResult, _> = factory.update(
+        caller(),
+        "set_promo_code",
+        SetPromoCodeArgs {
+            code: "FREE".to_string(),
+            discount_bps: 10_000,
+            expires_at: None,
+            max_uses: None,
+        },
+    );
+    assert!(result.is_err());
+
+    let result: Result<SetCanisterResult, _> = factory.update(
+        caller(),
+        "set_free_quota",
+        SetFreeQuotaArgs {
+            principal: caller(),
+            creations: 10,
+        },
+    );
+    assert!(result.is_err());
+}
@@ -0,0 +1,110 @@
+use icrc_factory::types::{
+    event::{Event, EventFilter, EventKind, EventOutcome},
+    pricing::Pricing,
+    quote::PaymentMethod,
+};
+
+use crate::utils::{
+    ledger::{create_paid_ledger, setup_paid_factory},
+    pocketic::{caller, controller, user_1, PicBackend, PicCanisterTrait},
+};
+
+fn list_events(factory: &PicBackend, filter: EventFilter) -> Vec<Event> {
+    factory
+        .query_with_args(
+            caller(),
+            "list_events",
+            (None::<u64>, Some(1_000u64), Some(filter)),
+        )
+        .expect("Failed to query list_events")
+}
+
+#[test]
+fn test_creation_is_recorded_with_payment_and_outcome() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+
+    let ledger_id = create_paid_ledger(&factory, payment_ledger, caller());
+
+    let events = list_events(
+        &factory,
+        EventFilter {
+            canister_id: Some(ledger_id),
+            ..Default::default()
+        },
+    );
+
+    assert_eq!(events.len(), 1);
+
+    let event = &events[0];
+    assert_eq!(event.caller, caller());
+    assert_eq!(
+        event.kind,
+        EventKind::IcrcLedgerCreated {
+            canister_id: Some(ledger_id),
+            symbol: Some("TKN".to_string()),
+            name: Some("Token".to_string()),
+        }
+    );
+    assert_eq!(event.outcome, Some(EventOutcome::Succeeded));
+
+    let payment = event
+        .payment
+        .as_ref()
+        .expect("The payment should be recorded");
+    assert_eq!(
+        payment.method,
+        PaymentMethod::CallerPaysIcrc2Tokens {
+            ledger: payment_ledger
+        }
+    );
+    assert_eq!(payment.cycles, Pricing::default().create_icrc_ledger);
+    assert_eq!(payment.discount, None);
+}
+
+#[test]
+fn test_wasm_replacement_is_recorded() {
+    let (factory, _) = setup_paid_factory(None);
+
+    let events = list_events(
+        &factory,
+        EventFilter {
+            event_type: Some("LedgerWasmSet".to_string()),
+            ..Default::default()
+        },
+    );
+
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].caller, controller());
+    assert!(matches!(
+        &events[0].kind,
+        EventKind::LedgerWasmSet { module_hash, url: None } if module_hash.len() == 32
+    ));
+    assert_eq!(events[0].payment, None);
+    assert_eq!(events[0].outcome, None);
+}
+
+#[test]
+fn test_events_are_filtered_by_principal() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+
+    create_paid_ledger(&factory, payment_ledger, caller());
+
+    let by_caller = list_events(
+        &factory,
+        EventFilter {
+            principal: Some(caller()),
+            ..Default::default()
+        },
+    );
+    assert!(!by_caller.is_empty());
+    assert!(by_caller.iter().all(|event| event.caller == caller()));
+
+    let by_user_1 = list_events(
+        &factory,
+        EventFilter {
+            principal: Some(user_1()),
+            ..Default::default()
+        },
+    );
+    assert!(by_user_1.is_empty());
+}
@@ -0,0 +1,171 @@
+use std::time::Duration;
+
+use candid::Nat;
+use icrc_factory::types::{
+    args::create_canister::CreateIcrcLedgerArgs,
+    payment::FactoryPaymentType,
+    prepaid::PrepaidReason,
+    pricing::Pricing,
+    quote::PaymentMethod,
+    results::{
+        create_canister::{CreateCanisterError, CreateCanisterResult},
+        quote::QuoteResult,
+    },
+};
+
+use crate::utils::{
+    icp::{
+        cycles_to_e8s, set_cmc_notify_rejected, setup_icp_factory, ICP_LEDGER_FEE,
+        XDR_PERMYRIAD_PER_ICP,
+    },
+    ledger::{approve, balance_of, PAYMENT_LEDGER_INITIAL_BALANCE},
+    pocketic::{caller, PicBackend, PicCanisterTrait},
+    prepaid::{prepaid_balance, prepaid_journal},
+};
+
+fn ledger_args() -> CreateIcrcLedgerArgs {
+    CreateIcrcLedgerArgs {
+        symbol: Some("TKN".to_string()),
+        name: Some("Token".to_string()),
+        transfer_fee: None,
+        decimals: None,
+        minting_account: None,
+        settings: None,
+        logo: None,
+    }
+}
+
+fn create_ledger_paid_with_icp(factory: &PicBackend) -> CreateCanisterResult {
+    factory
+        .update_with_args(
+            caller(),
+            "create_icrc_ledger",
+            (
+                ledger_args(),
+                Some(FactoryPaymentType::CallerPaysIcp),
+                None::<u64>,
+            ),
+        )
+        .expect("Failed to call create_icrc_ledger")
+}
+
+#[test]
+fn test_create_ledger_paid_with_icp() {
+    let (factory, icp_ledger) = setup_icp_factory();
+    let price = cycles_to_e8s(Pricing::default().create_icrc_ledger);
+
+    approve(
+        &factory.pic,
+        icp_ledger,
+        caller(),
+        factory.canister_id,
+        price + ICP_LEDGER_FEE,
+    );
+
+    let result = create_ledger_paid_with_icp(&factory);
+
+    assert!(
+        matches!(result, CreateCanisterResult::Ok(_)),
+        "Unexpected result: {result:?}"
+    );
+
+    // The approval and the transfer each cost a ledger fee.
+    assert_eq!(
+        balance_of(&factory.pic, icp_ledger, caller()),
+        Nat::from(PAYMENT_LEDGER_INITIAL_BALANCE - price - 2 * ICP_LEDGER_FEE)
+    );
+}
+
+#[test]
+fn test_unconverted_icp_payment_is_credited_once_converted() {
+    let (factory, icp_ledger) = setup_icp_factory();
+    let price = cycles_to_e8s(Pricing::default().create_icrc_ledger);
+
+    approve(
+        &factory.pic,
+        icp_ledger,
+        caller(),
+        factory.canister_id,
+        price + ICP_LEDGER_FEE,
+    );
+    set_cmc_notify_rejected(&factory, true);
+
+    let result = create_ledger_paid_with_icp(&factory);
+
+    let CreateCanisterResult::Err(CreateCanisterError::IcpPaymentPending { block_index }) = result
+    else {
+        panic!("Unexpected result: {result:?}");
+    };
+    assert_eq!(
+        balance_of(&factory.pic, icp_ledger, caller()),
+        Nat::from(PAYMENT_LEDGER_INITIAL_BALANCE - price - 2 * ICP_LEDGER_FEE)
+    );
+    assert_eq!(prepaid_balance(&factory), 0);
+
+    // The factory keeps notifying the CMC, and credits the ICP once it is converted.
+    set_cmc_notify_rejected(&factory, false);
+    factory.pic.advance_time(Duration::from_secs(6 * 60));
+    for _ in 0..10 {
+        factory.pic.tick();
+    }
+
+    assert_eq!(prepaid_balance(&factory), price * XDR_PERMYRIAD_PER_ICP);
+    let journal = prepaid_journal(&factory, caller());
+    assert_eq!(
+        journal.last().map(|entry| &entry.reason),
+        Some(&PrepaidReason::IcpPaymentRecovered { block_index })
+    );
+
+    // The payment is credited once, however often the timer fires again.
+    factory.pic.advance_time(Duration::from_secs(6 * 60));
+    for _ in 0..10 {
+        factory.pic.tick();
+    }
+    assert_eq!(prepaid_balance(&factory), price * XDR_PERMYRIAD_PER_ICP);
+}
+
+#[test]
+fn test_icp_payment_without_allowance_fails() {
+    let (factory, icp_ledger) = setup_icp_factory();
+
+    let result = create_ledger_paid_with_icp(&factory);
+
+    assert!(
+        matches!(
+            result,
+            CreateCanisterResult::Err(CreateCanisterError::IcpPaymentFailed(_))
+        ),
+        "Unexpected result: {result:?}"
+    );
+    assert_eq!(
+        balance_of(&factory.pic, icp_ledger, caller()),
+        Nat::from(PAYMENT_LEDGER_INITIAL_BALANCE)
+    );
+}
+
+#[test]
+fn test_quote_prices_icp_at_the_cmc_rate() {
+    let (factory, icp_ledger) = setup_icp_factory();
+
+    // Lets the factory read the conversion rate from the CMC.
+    for _ in 0..5 {
+        factory.pic.tick();
+    }
+
+    let QuoteResult::Ok(quote) = factory
+        .query(caller(), "quote_create_icrc_ledger", ledger_args())
+        .expect("Failed to query quote_create_icrc_ledger")
+    else {
+        panic!("Expected a quote");
+    };
+
+    let icp = quote
+        .payments
+        .iter()
+        .find(|p| p.method == PaymentMethod::CallerPaysIcp { ledger: icp_ledger })
+        .expect("ICP should be quoted");
+
+    let price = cycles_to_e8s(Pricing::default().create_icrc_ledger);
+    assert_eq!(icp.amount, price);
+    assert_eq!(icp.allowance, Some(price + ICP_LEDGER_FEE));
+}
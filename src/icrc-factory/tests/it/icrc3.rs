@@ -0,0 +1,191 @@
+use candid::Nat;
+use ic_certification::LookupResult;
+use icrc_factory::types::pricing::Pricing;
+use icrc_ledger_types::{
+    icrc::generic_value::ICRC3Value,
+    icrc3::blocks::{GetBlocksRequest, GetBlocksResult, ICRC3DataCertificate, SupportedBlockType},
+};
+use serde_bytes::ByteBuf;
+
+use crate::utils::{
+    certification::verified_witness,
+    ledger::{create_paid_ledger, setup_paid_factory},
+    pocketic::{caller, PicBackend, PicCanisterTrait},
+};
+
+fn get_blocks(factory: &PicBackend, start: u64, length: u64) -> GetBlocksResult {
+    factory
+        .query(
+            caller(),
+            "icrc3_get_blocks",
+            vec![GetBlocksRequest {
+                start: Nat::from(start),
+                length: Nat::from(length),
+            }],
+        )
+        .expect("Failed to query icrc3_get_blocks")
+}
+
+fn field<'a>(block: &'a ICRC3Value, name: &str) -> Option<&'a ICRC3Value> {
+    match block {
+        ICRC3Value::Map(fields) => fields.get(name),
+        _ => panic!("Blocks are maps: {block:?}"),
+    }
+}
+
+#[test]
+fn test_events_are_chained_blocks() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+
+    create_paid_ledger(&factory, payment_ledger, caller());
+
+    let result = get_blocks(&factory, 0, 1_000);
+
+    assert!(result.archived_blocks.is_empty());
+    assert_eq!(result.log_length, Nat::from(result.blocks.len()));
+
+    // The configuration set on install is the first block, and has no parent.
+    let first = &result.blocks[0].block;
+    assert_eq!(
+        field(first, "btype"),
+        Some(&ICRC3Value::Text("factory_config_updated".to_string()))
+    );
+    assert_eq!(field(first, "phash"), None);
+
+    for pair in result.blocks.windows(2) {
+        assert_eq!(
+            field(&pair[1].block, "phash"),
+            Some(&ICRC3Value::Blob(ByteBuf::from(
+                pair[0].block.clone().hash().to_vec()
+            )))
+        );
+    }
+
+    let created = result
+        .blocks
+        .iter()
+        .map(|block| &block.block)
+        .find(|block| {
+            field(block, "btype")
+                == Some(&ICRC3Value::Text("factory_icrc_ledger_created".to_string()))
+        })
+        .expect("The creation was recorded as a block");
+    assert!(field(created, "ts").is_some());
+    assert!(field(created, "tx").is_some());
+}
+
+#[test]
+fn test_block_transactions_are_icrc3_values() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+
+    let ledger_id = create_paid_ledger(&factory, payment_ledger, caller());
+
+    let created = get_blocks(&factory, 0, 1_000)
+        .blocks
+        .into_iter()
+        .map(|block| block.block)
+        .find(|block| {
+            field(block, "btype")
+                == Some(&ICRC3Value::Text("factory_icrc_ledger_created".to_string()))
+        })
+        .expect("The creation was recorded as a block");
+    let tx = field(&created, "tx").expect("Blocks have a tx");
+
+    assert_eq!(
+        field(tx, "caller"),
+        Some(&ICRC3Value::Blob(ByteBuf::from(caller().as_slice())))
+    );
+    assert_eq!(
+        field(tx, "canister_id"),
+        Some(&ICRC3Value::Blob(ByteBuf::from(ledger_id.as_slice())))
+    );
+    assert_eq!(
+        field(tx, "symbol"),
+        Some(&ICRC3Value::Text("TKN".to_string()))
+    );
+
+    let payment = field(tx, "payment").expect("The creation was paid");
+    assert_eq!(
+        field(payment, "cycles"),
+        Some(&ICRC3Value::Nat(Nat::from(
+            Pricing::default().create_icrc_ledger
+        )))
+    );
+    let method = field(payment, "method").expect("Payments have a method");
+    assert_eq!(
+        field(method, "type"),
+        Some(&ICRC3Value::Text("CallerPaysIcrc2Tokens".to_string()))
+    );
+    assert_eq!(
+        field(method, "ledger"),
+        Some(&ICRC3Value::Blob(ByteBuf::from(payment_ledger.as_slice())))
+    );
+
+    let outcome = field(tx, "outcome").expect("Paid actions have an outcome");
+    assert_eq!(
+        field(outcome, "type"),
+        Some(&ICRC3Value::Text("Succeeded".to_string()))
+    );
+}
+
+#[test]
+fn test_blocks_are_paginated() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+
+    create_paid_ledger(&factory, payment_ledger, caller());
+
+    let all = get_blocks(&factory, 0, 1_000);
+    let page = get_blocks(&factory, 1, 1);
+
+    assert_eq!(page.log_length, all.log_length);
+    assert_eq!(page.blocks.len(), 1);
+    assert_eq!(page.blocks[0].id, Nat::from(1u8));
+    assert_eq!(page.blocks[0].block, all.blocks[1].block);
+}
+
+#[test]
+fn test_tip_is_certified() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+
+    create_paid_ledger(&factory, payment_ledger, caller());
+
+    let certificate: Option<ICRC3DataCertificate> = factory
+        .query(caller(), "icrc3_get_tip_certificate", ())
+        .expect("Failed to query icrc3_get_tip_certificate");
+    let certificate = certificate.expect("The install recorded a block");
+
+    let witness = verified_witness(&factory, &certificate.certificate, &certificate.hash_tree);
+
+    let blocks = get_blocks(&factory, 0, 1_000);
+    let tip = blocks.blocks.last().expect("The log has blocks");
+
+    let mut index = Vec::new();
+    tip.id.encode(&mut index).expect("LEB128 encoding");
+
+    assert_eq!(
+        witness.lookup_path([b"last_block_index".as_slice()]),
+        LookupResult::Found(index.as_slice())
+    );
+    assert_eq!(
+        witness.lookup_path([b"last_block_hash".as_slice()]),
+        LookupResult::Found(tip.block.clone().hash().as_slice())
+    );
+}
+
+#[test]
+fn test_every_event_type_has_a_block_type() {
+    let (factory, _) = setup_paid_factory(None);
+
+    let block_types: Vec<SupportedBlockType> = factory
+        .query(caller(), "icrc3_supported_block_types", ())
+        .expect("Failed to query icrc3_supported_block_types");
+
+    let block_types: Vec<String> = block_types
+        .into_iter()
+        .map(|block_type| block_type.block_type)
+        .collect();
+
+    assert!(block_types.contains(&"factory_icrc_ledger_created".to_string()));
+    assert!(block_types.contains(&"factory_canister_topped_up".to_string()));
+    assert!(block_types.contains(&"factory_custom_pricing_set".to_string()));
+}
@@ -0,0 +1,224 @@
+use std::fs::read;
+
+use candid::Principal;
+use icrc_factory::types::{
+    args::{create_canister::SetSymbolArgs, ownership::MakeLedgerImmutableArgs},
+    results::create_canister::{CreateCanisterError, SetCanisterResult},
+    user_canister::UserCanister,
+};
+
+use crate::utils::{
+    ledger::{create_paid_ledger, setup_paid_factory, spawn_archive},
+    mock::BLACKHOLE_CANISTER_ID,
+    pocketic::{caller, controller, BackendBuilder, PicBackend, PicCanisterTrait},
+};
+
+fn blackhole() -> Principal {
+    Principal::from_text(BLACKHOLE_CANISTER_ID)
+        .expect("Test setup error: Failed to parse blackhole principal")
+}
+
+fn confirmed_args(ledger_id: Principal) -> MakeLedgerImmutableArgs {
+    MakeLedgerImmutableArgs {
+        ledger_id,
+        confirm_ledger_id: ledger_id,
+        acknowledge_irreversible: true,
+    }
+}
+
+fn make_ledger_immutable(factory: &PicBackend, args: MakeLedgerImmutableArgs) -> SetCanisterResult {
+    factory
+        .update(caller(), "make_ledger_immutable", args)
+        .expect("Failed to call make_ledger_immutable")
+}
+
+fn registry_entry(factory: &PicBackend, ledger_id: Principal) -> UserCanister {
+    let canisters: Vec<UserCanister> = factory
+        .query(caller(), "list_user_canisters", ())
+        .expect("Failed to query list_user_canisters");
+
+    canisters
+        .into_iter()
+        .find(|c| c.canister_id == ledger_id)
+        .expect("Ledger should be registered")
+}
+
+#[test]
+fn test_make_ledger_immutable_hands_ledger_to_blackhole() {
+    let (factory, payment_ledger) = setup_paid_factory(Some(blackhole()));
+    let ledger_id = create_paid_ledger(&factory, payment_ledger, caller());
+
+    let result = make_ledger_immutable(&factory, confirmed_args(ledger_id));
+
+    assert_eq!(result, SetCanisterResult::Ok());
+
+    let status = factory
+        .pic
+        .canister_status(ledger_id, Some(blackhole()))
+        .expect("The blackhole should be able to read the ledger status");
+
+    assert_eq!(status.settings.controllers, vec![blackhole()]);
+
+    let entry = registry_entry(&factory, ledger_id);
+
+    assert_eq!(entry.final_module_hash, status.module_hash);
+    assert!(entry.is_sovereign());
+}
+
+#[test]
+fn test_make_ledger_immutable_keeps_ledger_code() {
+    let (factory, payment_ledger) = setup_paid_factory(Some(blackhole()));
+    let ledger_id = create_paid_ledger(&factory, payment_ledger, caller());
+    let archive_id = spawn_archive(&factory.pic, ledger_id, caller());
+
+    let module_hash_before = factory
+        .pic
+        .canister_status(ledger_id, Some(caller()))
+        .expect("The owner should be able to read the ledger status")
+        .module_hash;
+
+    let result = make_ledger_immutable(&factory, confirmed_args(ledger_id));
+
+    assert_eq!(result, SetCanisterResult::Ok());
+
+    let module_hash_after = factory
+        .pic
+        .canister_status(ledger_id, Some(blackhole()))
+        .expect("The blackhole should be able to read the ledger status")
+        .module_hash;
+
+    assert_eq!(module_hash_after, module_hash_before);
+    assert_eq!(
+        registry_entry(&factory, ledger_id).final_module_hash,
+        module_hash_before
+    );
+
+    let archive_controllers = factory
+        .pic
+        .canister_status(archive_id, Some(blackhole()))
+        .expect("The blackhole should be able to read the archive status")
+        .settings
+        .controllers;
+    assert!(archive_controllers.contains(&blackhole()));
+    assert!(!archive_controllers.contains(&factory.canister_id));
+}
+
+#[test]
+fn test_make_ledger_immutable_refuses_ledger_running_another_wasm() {
+    let (factory, payment_ledger) = setup_paid_factory(Some(blackhole()));
+    let ledger_id = create_paid_ledger(&factory, payment_ledger, caller());
+
+    // Any module other than the ledger's does; the factory's own is at hand.
+    let other_wasm =
+        read(BackendBuilder::default_wasm_path()).expect("Could not read the backend wasm");
+    factory
+        .update::<()>(controller(), "set_ledger_wasm", other_wasm)
+        .expect("Failed to call set_ledger_wasm");
+
+    assert_eq!(
+        make_ledger_immutable(&factory, confirmed_args(ledger_id)),
+        SetCanisterResult::Err(CreateCanisterError::ModuleHashMismatch)
+    );
+
+    assert!(!registry_entry(&factory, ledger_id).is_immutable());
+    assert!(factory
+        .pic
+        .canister_status(ledger_id, Some(caller()))
+        .expect("The owner should still control the ledger")
+        .settings
+        .controllers
+        .contains(&factory.canister_id));
+}
+
+#[test]
+fn test_make_ledger_immutable_without_blackhole_removes_all_controllers() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+    let ledger_id = create_paid_ledger(&factory, payment_ledger, caller());
+
+    let result = make_ledger_immutable(&factory, confirmed_args(ledger_id));
+
+    assert_eq!(result, SetCanisterResult::Ok());
+
+    assert!(factory
+        .pic
+        .canister_status(ledger_id, Some(caller()))
+        .is_err());
+    assert!(factory
+        .pic
+        .canister_status(ledger_id, Some(factory.canister_id))
+        .is_err());
+    assert!(registry_entry(&factory, ledger_id).is_immutable());
+}
+
+#[test]
+fn test_immutable_ledger_refuses_upgrades() {
+    let (factory, payment_ledger) = setup_paid_factory(Some(blackhole()));
+    let ledger_id = create_paid_ledger(&factory, payment_ledger, caller());
+
+    make_ledger_immutable(&factory, confirmed_args(ledger_id));
+
+    let result: SetCanisterResult = factory
+        .update(
+            caller(),
+            "set_symbol",
+            SetSymbolArgs {
+                ledger_id,
+                symbol: "NEW".to_string(),
+            },
+        )
+        .expect("Failed to call set_symbol");
+
+    assert_eq!(
+        result,
+        SetCanisterResult::Err(CreateCanisterError::Immutable)
+    );
+}
+
+#[test]
+fn test_make_ledger_immutable_requires_confirmation() {
+    let (factory, payment_ledger) = setup_paid_factory(Some(blackhole()));
+    let ledger_id = create_paid_ledger(&factory, payment_ledger, caller());
+
+    let unacknowledged = MakeLedgerImmutableArgs {
+        acknowledge_irreversible: false,
+        ..confirmed_args(ledger_id)
+    };
+    let mismatched = MakeLedgerImmutableArgs {
+        confirm_ledger_id: payment_ledger,
+        ..confirmed_args(ledger_id)
+    };
+
+    for args in [unacknowledged, mismatched] {
+        assert_eq!(
+            make_ledger_immutable(&factory, args),
+            SetCanisterResult::Err(CreateCanisterError::ImmutabilityNotConfirmed)
+        );
+    }
+
+    let status = factory
+        .pic
+        .canister_status(ledger_id, Some(caller()))
+        .expect("The owner should still control the ledger");
+
+    assert!(status.settings.controllers.contains(&factory.canister_id));
+    assert!(!registry_entry(&factory, ledger_id).is_immutable());
+}
+
+#[test]
+fn test_make_ledger_immutable_by_non_owner_fails() {
+    let (factory, payment_ledger) = setup_paid_factory(Some(blackhole()));
+    let ledger_id = create_paid_ledger(&factory, payment_ledger, caller());
+
+    let result: SetCanisterResult = factory
+        .update(
+            controller(),
+            "make_ledger_immutable",
+            confirmed_args(ledger_id),
+        )
+        .expect("Failed to call make_ledger_immutable");
+
+    assert_eq!(
+        result,
+        SetCanisterResult::Err(CreateCanisterError::NotOwner)
+    );
+}
@@ -0,0 +1,192 @@
+use candid::{Nat, Principal};
+use icrc_factory::types::{
+    args::{
+        lifecycle::DeleteCanisterArgs,
+        ownership::{AcceptOwnershipTransferArgs, ProposeOwnershipTransferArgs},
+    },
+    canister_status::CanisterRunStatus,
+    results::{
+        canister_status::CanisterStatusResult,
+        create_canister::{CreateCanisterError, SetCanisterResult},
+        lifecycle::DeleteCanisterResult,
+    },
+    user_canister::UserCanister,
+};
+
+use crate::utils::{
+    ledger::{create_paid_ledger, setup_paid_factory},
+    pocketic::{caller, user_1, PicBackend, PicCanisterTrait},
+};
+
+fn run_status(factory: &PicBackend, canister_id: Principal) -> CanisterRunStatus {
+    let result: CanisterStatusResult = factory
+        .update(caller(), "canister_status", canister_id)
+        .expect("Failed to call canister_status");
+
+    let CanisterStatusResult::Ok(status) = result else {
+        panic!("Expected the canister status");
+    };
+
+    status.status
+}
+
+fn delete_canister(
+    factory: &PicBackend,
+    caller: Principal,
+    canister_id: Principal,
+) -> DeleteCanisterResult {
+    factory
+        .update(
+            caller,
+            "delete_canister",
+            DeleteCanisterArgs {
+                canister_id,
+                recover_cycles_to: None,
+            },
+        )
+        .expect("Failed to call delete_canister")
+}
+
+fn registry_entry(factory: &PicBackend, canister_id: Principal) -> UserCanister {
+    let canisters: Vec<UserCanister> = factory
+        .query(caller(), "list_user_canisters", ())
+        .expect("Failed to query list_user_canisters");
+
+    canisters
+        .into_iter()
+        .find(|c| c.canister_id == canister_id)
+        .expect("Canister should be registered")
+}
+
+#[test]
+fn test_owner_stops_and_starts_canister() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+    let ledger_id = create_paid_ledger(&factory, payment_ledger, caller());
+
+    let result: SetCanisterResult = factory
+        .update(caller(), "stop_canister", ledger_id)
+        .expect("Failed to call stop_canister");
+
+    assert_eq!(result, SetCanisterResult::Ok());
+    assert_eq!(run_status(&factory, ledger_id), CanisterRunStatus::Stopped);
+
+    let result: SetCanisterResult = factory
+        .update(caller(), "start_canister", ledger_id)
+        .expect("Failed to call start_canister");
+
+    assert_eq!(result, SetCanisterResult::Ok());
+    assert_eq!(run_status(&factory, ledger_id), CanisterRunStatus::Running);
+}
+
+#[test]
+fn test_stop_canister_by_non_owner_fails() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+    let ledger_id = create_paid_ledger(&factory, payment_ledger, caller());
+
+    let result: SetCanisterResult = factory
+        .update(user_1(), "stop_canister", ledger_id)
+        .expect("Failed to call stop_canister");
+
+    assert_eq!(
+        result,
+        SetCanisterResult::Err(CreateCanisterError::NotOwner)
+    );
+    assert_eq!(run_status(&factory, ledger_id), CanisterRunStatus::Running);
+}
+
+#[test]
+fn test_delete_canister_returns_cycles_to_factory() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+    let ledger_id = create_paid_ledger(&factory, payment_ledger, caller());
+
+    let ledger_cycles = factory.pic.cycle_balance(ledger_id);
+    let factory_cycles = factory.pic.cycle_balance(factory.canister_id);
+
+    let DeleteCanisterResult::Ok(recovered) = delete_canister(&factory, caller(), ledger_id) else {
+        panic!("Expected the ledger to be deleted");
+    };
+
+    // The drainer keeps a small reserve to pay for the transfer.
+    assert!(recovered > Nat::from(ledger_cycles * 9 / 10));
+    assert!(
+        factory.pic.cycle_balance(factory.canister_id) > factory_cycles + ledger_cycles * 9 / 10
+    );
+
+    assert!(factory
+        .pic
+        .canister_status(ledger_id, Some(caller()))
+        .is_err());
+    assert!(registry_entry(&factory, ledger_id).is_deleted());
+}
+
+#[test]
+fn test_deleted_canister_can_no_longer_be_managed() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+    let ledger_id = create_paid_ledger(&factory, payment_ledger, caller());
+
+    assert!(matches!(
+        delete_canister(&factory, caller(), ledger_id),
+        DeleteCanisterResult::Ok(_)
+    ));
+
+    assert_eq!(
+        delete_canister(&factory, caller(), ledger_id),
+        DeleteCanisterResult::Err(CreateCanisterError::Deleted)
+    );
+
+    let result: SetCanisterResult = factory
+        .update(caller(), "start_canister", ledger_id)
+        .expect("Failed to call start_canister");
+
+    assert_eq!(result, SetCanisterResult::Err(CreateCanisterError::Deleted));
+}
+
+#[test]
+fn test_delete_canister_by_non_owner_fails() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+    let ledger_id = create_paid_ledger(&factory, payment_ledger, caller());
+
+    assert_eq!(
+        delete_canister(&factory, user_1(), ledger_id),
+        DeleteCanisterResult::Err(CreateCanisterError::NotOwner)
+    );
+    assert!(!registry_entry(&factory, ledger_id).is_deleted());
+}
+
+#[test]
+fn test_deleting_withdraws_pending_transfer() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+    let ledger_id = create_paid_ledger(&factory, payment_ledger, caller());
+
+    let result: SetCanisterResult = factory
+        .update(
+            caller(),
+            "propose_ownership_transfer",
+            ProposeOwnershipTransferArgs {
+                canister_id: ledger_id,
+                new_owner: Some(user_1()),
+            },
+        )
+        .expect("Failed to call propose_ownership_transfer");
+    assert_eq!(result, SetCanisterResult::Ok());
+
+    assert!(matches!(
+        delete_canister(&factory, caller(), ledger_id),
+        DeleteCanisterResult::Ok(_)
+    ));
+    assert_eq!(registry_entry(&factory, ledger_id).pending_owner, None);
+
+    let result: SetCanisterResult = factory
+        .update(
+            user_1(),
+            "accept_ownership_transfer",
+            AcceptOwnershipTransferArgs {
+                canister_id: ledger_id,
+            },
+        )
+        .expect("Failed to call accept_ownership_transfer");
+    assert_eq!(
+        result,
+        SetCanisterResult::Err(CreateCanisterError::NoPendingOwnershipTransfer)
+    );
+}
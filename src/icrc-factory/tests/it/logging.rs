@@ -0,0 +1,103 @@
+use icrc_factory::types::{
+    log::{LogEntry, LogFilter, LogLevel},
+    results::{create_canister::CreateCanisterError, logs::ListLogsResult},
+};
+
+use crate::utils::{
+    ledger::{create_paid_ledger, setup_paid_factory},
+    pocketic::{caller, controller, user_1, PicCanisterTrait},
+};
+
+#[test]
+fn test_creation_steps_share_a_correlation_id() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+    let ledger_id = create_paid_ledger(&factory, payment_ledger, caller());
+
+    let entries: Vec<LogEntry> = factory
+        .query_with_args(
+            controller(),
+            "list_logs",
+            (None::<u64>, None::<u64>, None::<LogFilter>),
+        )
+        .expect("Failed to query list_logs");
+
+    let installed = entries
+        .iter()
+        .find(|entry| entry.message == "Ledger installed")
+        .expect("The installation was logged");
+    assert_eq!(installed.level, LogLevel::Info);
+    assert_eq!(installed.canister_id, Some(ledger_id));
+    assert_eq!(installed.caller, caller());
+
+    let flow: Vec<&LogEntry> = entries
+        .iter()
+        .filter(|entry| entry.correlation_id == installed.correlation_id)
+        .collect();
+    assert_eq!(flow[0].id, installed.correlation_id);
+    assert!(flow[0].message.starts_with("Creating ICRC ledger"));
+    assert!(flow.len() >= 3);
+}
+
+#[test]
+fn test_logs_are_filtered_by_level() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+    create_paid_ledger(&factory, payment_ledger, caller());
+
+    let filter = LogFilter {
+        min_level: Some(LogLevel::Info),
+        ..LogFilter::default()
+    };
+    let entries: Vec<LogEntry> = factory
+        .query_with_args(
+            controller(),
+            "list_logs",
+            (None::<u64>, None::<u64>, Some(filter)),
+        )
+        .expect("Failed to query list_logs");
+
+    assert!(!entries.is_empty());
+    assert!(entries.iter().all(|entry| entry.level >= LogLevel::Info));
+}
+
+#[test]
+fn test_owners_read_the_logs_of_their_canisters() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+    let ledger_id = create_paid_ledger(&factory, payment_ledger, caller());
+
+    let result: ListLogsResult = factory
+        .query_with_args(
+            caller(),
+            "list_canister_logs",
+            (ledger_id, None::<u64>, None::<u64>, None::<LogFilter>),
+        )
+        .expect("Failed to query list_canister_logs");
+    let ListLogsResult::Ok(entries) = result else {
+        panic!("The owner can read the logs: {result:?}");
+    };
+    assert!(!entries.is_empty());
+    assert!(entries
+        .iter()
+        .all(|entry| entry.canister_id == Some(ledger_id)));
+
+    let result: ListLogsResult = factory
+        .query_with_args(
+            user_1(),
+            "list_canister_logs",
+            (ledger_id, None::<u64>, None::<u64>, None::<LogFilter>),
+        )
+        .expect("Failed to query list_canister_logs");
+    assert_eq!(result, ListLogsResult::Err(CreateCanisterError::NotOwner));
+}
+
+#[test]
+fn test_only_controllers_read_all_logs() {
+    let (factory, _) = setup_paid_factory(None);
+
+    let result: Result<Vec<LogEntry>, String> = factory.query_with_args(
+        caller(),
+        "list_logs",
+        (None::<u64>, None::<u64>, None::<LogFilter>),
+    );
+
+    assert!(result.is_err());
+}
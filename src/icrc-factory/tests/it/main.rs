@@ -1,2 +1,26 @@
+mod access_list;
+mod auto_top_up;
+mod canister_status;
+mod certification;
 mod config;
+mod controllers;
+mod discounts;
+mod events;
+mod icp;
+mod icrc3;
+mod immutable;
+mod lifecycle;
+mod logging;
+mod metrics;
+mod ownership;
+mod pause;
+mod prepaid;
+mod pricing;
+mod quote;
+mod rate_limit;
+mod renounce;
+mod roles;
+mod settings;
+mod tokens;
+mod top_up;
 mod utils;
@@ -0,0 +1,48 @@
+use icrc_factory::types::http::{HttpGatewayRequest, HttpGatewayResponse};
+use serde_bytes::ByteBuf;
+
+use crate::utils::{
+    ledger::{create_paid_ledger, setup_paid_factory},
+    pocketic::{caller, PicBackend, PicCanisterTrait},
+};
+
+fn get(factory: &PicBackend, url: &str) -> HttpGatewayResponse {
+    factory
+        .query(
+            caller(),
+            "http_request",
+            HttpGatewayRequest {
+                method: "GET".to_string(),
+                url: url.to_string(),
+                headers: vec![],
+                body: ByteBuf::new(),
+            },
+        )
+        .expect("Failed to query http_request")
+}
+
+#[test]
+fn test_metrics_report_creations_and_payments() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+
+    create_paid_ledger(&factory, payment_ledger, caller());
+
+    let response = get(&factory, "/metrics");
+    assert_eq!(response.status_code, 200);
+
+    let body = String::from_utf8(response.body.into_vec()).expect("Metrics are text");
+
+    assert!(body.contains("icrc_factory_cycles_balance "));
+    assert!(body.contains(r#"icrc_factory_canisters{kind="IcrcLedger",status="installed"} 1 "#));
+    assert!(body.contains(r#"icrc_factory_creations_total{outcome="Ok"} 1 "#));
+    assert!(body.contains(r#"icrc_factory_payments_cycles_total{method="CallerPaysIcrc2Tokens"}"#));
+    assert!(body.contains(r#"icrc_factory_memory_pages{memory="event_log_data"}"#));
+    assert!(body.contains(r#"icrc_factory_wasm_size_bytes{wasm="ledger",module_hash=""#));
+}
+
+#[test]
+fn test_unknown_path_is_not_found() {
+    let (factory, _) = setup_paid_factory(None);
+
+    assert_eq!(get(&factory, "/unknown").status_code, 404);
+}
@@ -0,0 +1,168 @@
+use candid::Principal;
+use icrc_factory::types::{
+    args::{
+        create_canister::SetSymbolArgs,
+        ownership::{AcceptOwnershipTransferArgs, ProposeOwnershipTransferArgs},
+    },
+    results::create_canister::{CreateCanisterError, SetCanisterResult},
+    user_canister::UserCanister,
+};
+
+use crate::utils::{
+    ledger::{create_paid_index, create_paid_ledger, setup_paid_factory},
+    pocketic::{caller, controller, user_1, PicBackend, PicCanisterTrait},
+};
+
+/// Accepts any install argument, so it stands in for an index WASM.
+const CYCLES_DRAINER_WASM: &[u8] = include_bytes!("../../wasm/cycles-drainer.wasm");
+
+fn propose(
+    factory: &PicBackend,
+    canister_id: Principal,
+    new_owner: Option<Principal>,
+) -> SetCanisterResult {
+    factory
+        .update(
+            caller(),
+            "propose_ownership_transfer",
+            ProposeOwnershipTransferArgs {
+                canister_id,
+                new_owner,
+            },
+        )
+        .expect("Failed to call propose_ownership_transfer")
+}
+
+fn accept(factory: &PicBackend, acceptor: Principal, canister_id: Principal) -> SetCanisterResult {
+    factory
+        .update(
+            acceptor,
+            "accept_ownership_transfer",
+            AcceptOwnershipTransferArgs { canister_id },
+        )
+        .expect("Failed to call accept_ownership_transfer")
+}
+
+fn user_canisters(factory: &PicBackend, owner: Principal) -> Vec<UserCanister> {
+    factory
+        .query(owner, "list_user_canisters", ())
+        .expect("Failed to query list_user_canisters")
+}
+
+fn live_controllers(factory: &PicBackend, canister_id: Principal) -> Vec<Principal> {
+    factory
+        .pic
+        .canister_status(canister_id, Some(factory.canister_id))
+        .expect("The factory should be able to read the canister status")
+        .settings
+        .controllers
+}
+
+#[test]
+fn test_accepted_transfer_moves_entry_and_controllers() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+    let ledger_id = create_paid_ledger(&factory, payment_ledger, caller());
+
+    assert_eq!(
+        propose(&factory, ledger_id, Some(user_1())),
+        SetCanisterResult::Ok()
+    );
+    assert_eq!(
+        user_canisters(&factory, caller())[0].pending_owner,
+        Some(user_1())
+    );
+
+    assert_eq!(
+        accept(&factory, user_1(), ledger_id),
+        SetCanisterResult::Ok()
+    );
+
+    assert!(user_canisters(&factory, caller()).is_empty());
+
+    let canisters = user_canisters(&factory, user_1());
+    assert_eq!(canisters.len(), 1);
+    assert_eq!(canisters[0].canister_id, ledger_id);
+    assert_eq!(canisters[0].pending_owner, None);
+    assert_eq!(canisters[0].controllers, Some(vec![user_1()]));
+
+    let controllers = live_controllers(&factory, ledger_id);
+    assert!(controllers.contains(&user_1()));
+    assert!(controllers.contains(&factory.canister_id));
+    assert!(!controllers.contains(&caller()));
+
+    // The previous owner no longer manages the ledger.
+    assert_eq!(
+        propose(&factory, ledger_id, Some(controller())),
+        SetCanisterResult::Err(CreateCanisterError::NotOwner)
+    );
+}
+
+#[test]
+fn test_only_proposed_owner_can_accept() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+    let ledger_id = create_paid_ledger(&factory, payment_ledger, caller());
+
+    assert_eq!(
+        propose(&factory, ledger_id, Some(user_1())),
+        SetCanisterResult::Ok()
+    );
+
+    for acceptor in [caller(), controller()] {
+        assert_eq!(
+            accept(&factory, acceptor, ledger_id),
+            SetCanisterResult::Err(CreateCanisterError::NoPendingOwnershipTransfer)
+        );
+    }
+
+    assert_eq!(user_canisters(&factory, caller()).len(), 1);
+    assert!(live_controllers(&factory, ledger_id).contains(&caller()));
+}
+
+#[test]
+fn test_cancelled_transfer_cannot_be_accepted() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+    let ledger_id = create_paid_ledger(&factory, payment_ledger, caller());
+
+    assert_eq!(
+        propose(&factory, ledger_id, Some(user_1())),
+        SetCanisterResult::Ok()
+    );
+    assert_eq!(propose(&factory, ledger_id, None), SetCanisterResult::Ok());
+
+    assert_eq!(
+        accept(&factory, user_1(), ledger_id),
+        SetCanisterResult::Err(CreateCanisterError::NoPendingOwnershipTransfer)
+    );
+
+    assert!(user_canisters(&factory, user_1()).is_empty());
+    let controllers = live_controllers(&factory, ledger_id);
+    assert!(controllers.contains(&caller()));
+    assert!(!controllers.contains(&user_1()));
+}
+
+#[test]
+fn test_ledger_updates_refuse_owned_index() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+    let ledger_id = create_paid_ledger(&factory, payment_ledger, caller());
+
+    factory
+        .update::<()>(controller(), "set_index_wasm", CYCLES_DRAINER_WASM.to_vec())
+        .expect("Failed to call set_index_wasm");
+    let index_id = create_paid_index(&factory, payment_ledger, caller(), ledger_id);
+
+    let result: SetCanisterResult = factory
+        .update(
+            caller(),
+            "set_symbol",
+            SetSymbolArgs {
+                ledger_id: index_id,
+                symbol: "NEW".to_string(),
+            },
+        )
+        .expect("Failed to call set_symbol");
+
+    assert_eq!(
+        result,
+        SetCanisterResult::Err(CreateCanisterError::NotALedger)
+    );
+}
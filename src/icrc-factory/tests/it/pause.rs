@@ -0,0 +1,145 @@
+use icrc_factory::types::{
+    args::{
+        create_canister::{CreateIcrcLedgerArgs, SetSymbolArgs},
+        pause::SetPausedArgs,
+        roles::RoleArgs,
+    },
+    config::{Config, PauseFlags},
+    results::create_canister::{CreateCanisterError, CreateCanisterResult, SetCanisterResult},
+    role::Role,
+};
+
+use crate::utils::{
+    ledger::{create_paid_ledger, create_paid_ledger_with_args, ledger_wasm, setup_paid_factory},
+    pocketic::{caller, controller, user_1, PicBackend, PicCanisterTrait},
+};
+
+fn set_paused(factory: &PicBackend, args: SetPausedArgs) {
+    let result: SetCanisterResult = factory
+        .update(controller(), "set_paused", args)
+        .expect("Failed to call set_paused");
+
+    assert_eq!(result, SetCanisterResult::Ok());
+}
+
+#[test]
+fn test_paused_creation_is_refused_until_resumed() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+
+    set_paused(
+        &factory,
+        SetPausedArgs {
+            creation: Some(true),
+            ..Default::default()
+        },
+    );
+
+    let args = CreateIcrcLedgerArgs {
+        symbol: Some("TKN".to_string()),
+        name: Some("Token".to_string()),
+        transfer_fee: None,
+        decimals: None,
+        minting_account: None,
+        settings: None,
+        logo: None,
+    };
+    let result = create_paid_ledger_with_args(&factory, payment_ledger, caller(), args);
+    assert_eq!(
+        result,
+        CreateCanisterResult::Err(CreateCanisterError::Paused)
+    );
+
+    set_paused(
+        &factory,
+        SetPausedArgs {
+            creation: Some(false),
+            ..Default::default()
+        },
+    );
+
+    create_paid_ledger(&factory, payment_ledger, caller());
+}
+
+#[test]
+fn test_paused_upgrades_and_wasm_updates_are_refused() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+    let ledger_id = create_paid_ledger(&factory, payment_ledger, caller());
+
+    set_paused(
+        &factory,
+        SetPausedArgs {
+            upgrades: Some(true),
+            wasm_updates: Some(true),
+            ..Default::default()
+        },
+    );
+
+    let result: SetCanisterResult = factory
+        .update(
+            caller(),
+            "set_symbol",
+            SetSymbolArgs {
+                ledger_id,
+                symbol: "NEW".to_string(),
+            },
+        )
+        .expect("Failed to call set_symbol");
+    assert_eq!(result, SetCanisterResult::Err(CreateCanisterError::Paused));
+
+    let result: Result<(), _> = factory.update(controller(), "set_ledger_wasm", ledger_wasm());
+    assert!(result.is_err());
+
+    // Creations are not paused.
+    create_paid_ledger(&factory, payment_ledger, caller());
+}
+
+#[test]
+fn test_operator_pauses_and_flags_are_in_the_config() {
+    let (factory, _) = setup_paid_factory(None);
+
+    let result: Result<SetCanisterResult, _> = factory.update(
+        user_1(),
+        "set_paused",
+        SetPausedArgs {
+            creation: Some(true),
+            ..Default::default()
+        },
+    );
+    assert!(result.is_err());
+
+    let result: SetCanisterResult = factory
+        .update(
+            controller(),
+            "grant_role",
+            RoleArgs {
+                principal: user_1(),
+                role: Role::Operator,
+            },
+        )
+        .expect("Failed to call grant_role");
+    assert_eq!(result, SetCanisterResult::Ok());
+
+    let result: SetCanisterResult = factory
+        .update(
+            user_1(),
+            "set_paused",
+            SetPausedArgs {
+                creation: Some(true),
+                ..Default::default()
+            },
+        )
+        .expect("Failed to call set_paused");
+    assert_eq!(result, SetCanisterResult::Ok());
+
+    let config: Config = factory
+        .query(caller(), "config", ())
+        .expect("Failed to query config");
+    assert_eq!(
+        config.paused,
+        Some(PauseFlags {
+            creation: true,
+            upgrades: false,
+            wasm_updates: false,
+        })
+    );
+}
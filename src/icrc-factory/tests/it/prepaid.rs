@@ -0,0 +1,102 @@
+use icrc_factory::types::{
+    args::create_canister::CreateIcrcLedgerArgs,
+    payment::FactoryPaymentType,
+    prepaid::{PrepaidChange, PrepaidReason},
+    pricing::Pricing,
+    results::create_canister::{CreateCanisterError, CreateCanisterResult},
+};
+
+use crate::utils::{
+    ledger::setup_paid_factory,
+    pocketic::{caller, user_1, PicBackend, PicCanisterTrait},
+    prepaid::{fund_prepaid_balance, prepaid_balance, prepaid_journal},
+};
+
+const FUNDING: u64 = 1_000_000_000_000;
+
+fn ledger_args() -> CreateIcrcLedgerArgs {
+    CreateIcrcLedgerArgs {
+        symbol: Some("TKN".to_string()),
+        name: Some("Token".to_string()),
+        transfer_fee: None,
+        decimals: None,
+        minting_account: None,
+        settings: None,
+        logo: None,
+    }
+}
+
+fn create_ledger_from_prepaid_balance(factory: &PicBackend) -> CreateCanisterResult {
+    factory
+        .update_with_args(
+            caller(),
+            "create_icrc_ledger",
+            (
+                ledger_args(),
+                Some(FactoryPaymentType::PrepaidBalance),
+                None::<u64>,
+            ),
+        )
+        .expect("Failed to call create_icrc_ledger")
+}
+
+#[test]
+fn test_create_ledger_paid_from_prepaid_balance() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+    let fee = Pricing::default().create_icrc_ledger;
+
+    fund_prepaid_balance(&factory, payment_ledger, FUNDING);
+
+    let result = create_ledger_from_prepaid_balance(&factory);
+
+    assert!(
+        matches!(result, CreateCanisterResult::Ok(_)),
+        "Unexpected result: {result:?}"
+    );
+    assert_eq!(prepaid_balance(&factory), FUNDING - fee);
+}
+
+#[test]
+fn test_prepaid_payment_beyond_balance_fails() {
+    let (factory, _) = setup_paid_factory(None);
+    let fee = Pricing::default().create_icrc_ledger;
+
+    assert_eq!(
+        create_ledger_from_prepaid_balance(&factory),
+        CreateCanisterResult::Err(CreateCanisterError::InsufficientPrepaidBalance {
+            balance: 0,
+            required: fee,
+        })
+    );
+}
+
+#[test]
+fn test_prepaid_journal_records_credits_and_debits() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+    let fee = Pricing::default().create_icrc_ledger;
+
+    fund_prepaid_balance(&factory, payment_ledger, FUNDING);
+    create_ledger_from_prepaid_balance(&factory);
+
+    let journal: Vec<_> = prepaid_journal(&factory, caller())
+        .into_iter()
+        .map(|entry| (entry.change, entry.balance, entry.reason))
+        .collect();
+
+    assert_eq!(
+        journal,
+        vec![
+            (
+                PrepaidChange::Credit(FUNDING),
+                FUNDING,
+                PrepaidReason::Funded
+            ),
+            (
+                PrepaidChange::Debit(fee),
+                FUNDING - fee,
+                PrepaidReason::Payment
+            ),
+        ]
+    );
+    assert!(prepaid_journal(&factory, user_1()).is_empty());
+}
@@ -0,0 +1,208 @@
+use candid::{Nat, Principal};
+use ic_papi_api::{caller::CallerPaysIcrc2Tokens, PaymentType};
+use icrc_factory::types::{
+    args::{create_canister::CreateIcrcLedgerArgs, pricing::SetPricingArgs},
+    pricing::Pricing,
+    results::create_canister::{CreateCanisterError, CreateCanisterResult, SetCanisterResult},
+};
+
+use crate::utils::{
+    ledger::{approve, balance_of, create_paid_ledger, setup_paid_factory},
+    pocketic::{caller, controller, PicBackend, PicCanisterTrait},
+};
+
+const CREATION_ALLOWANCE: u64 = 2_000_000_000_000;
+
+fn get_pricing(factory: &PicBackend) -> Pricing {
+    factory
+        .query(caller(), "get_pricing", ())
+        .expect("Failed to query get_pricing")
+}
+
+fn set_pricing(factory: &PicBackend, args: SetPricingArgs) -> SetCanisterResult {
+    factory
+        .update(controller(), "set_pricing", args)
+        .expect("Failed to call set_pricing")
+}
+
+fn create_ledger_with_max_fee(
+    factory: &PicBackend,
+    payment_ledger: Principal,
+    max_fee: u64,
+) -> CreateCanisterResult {
+    approve(
+        &factory.pic,
+        payment_ledger,
+        caller(),
+        factory.canister_id,
+        CREATION_ALLOWANCE,
+    );
+
+    let args = CreateIcrcLedgerArgs {
+        symbol: Some("TKN".to_string()),
+        name: Some("Token".to_string()),
+        transfer_fee: None,
+        decimals: None,
+        minting_account: None,
+        settings: None,
+        logo: None,
+    };
+    let payment = PaymentType::CallerPaysIcrc2Tokens(CallerPaysIcrc2Tokens {
+        ledger: payment_ledger,
+    });
+
+    factory
+        .update_with_args(
+            caller(),
+            "create_icrc_ledger",
+            (args, Some(payment), Some(max_fee)),
+        )
+        .expect("Failed to call create_icrc_ledger")
+}
+
+#[test]
+fn test_default_pricing() {
+    let (factory, _) = setup_paid_factory(None);
+
+    assert_eq!(get_pricing(&factory), Pricing::default());
+}
+
+#[test]
+fn test_controller_updates_pricing() {
+    let (factory, _) = setup_paid_factory(None);
+
+    let result = set_pricing(
+        &factory,
+        SetPricingArgs {
+            create_icrc_ledger: Some(1_000_000_000_000),
+            margin_bps: Some(500),
+            ..Default::default()
+        },
+    );
+
+    assert_eq!(result, SetCanisterResult::Ok());
+
+    let pricing = get_pricing(&factory);
+
+    assert_eq!(pricing.create_icrc_ledger, 1_000_000_000_000);
+    assert_eq!(pricing.margin_bps, 500);
+    assert_eq!(
+        pricing.create_icrc_index,
+        Pricing::default().create_icrc_index
+    );
+    assert!(pricing.updated_at.is_some());
+}
+
+#[test]
+fn test_set_pricing_by_non_controller_fails() {
+    let (factory, _) = setup_paid_factory(None);
+
+    let result: Result<SetCanisterResult, _> =
+        factory.update(caller(), "set_pricing", SetPricingArgs::default());
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_creation_fee_below_canister_cycles_is_refused() {
+    let (factory, _) = setup_paid_factory(None);
+
+    let result = set_pricing(
+        &factory,
+        SetPricingArgs {
+            create_icrc_ledger: Some(1_000),
+            ..Default::default()
+        },
+    );
+
+    assert!(matches!(
+        result,
+        SetCanisterResult::Err(CreateCanisterError::InvalidPricing(_))
+    ));
+    assert_eq!(get_pricing(&factory), Pricing::default());
+}
+
+#[test]
+fn test_creation_charges_the_configured_fee() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+
+    let result = set_pricing(
+        &factory,
+        SetPricingArgs {
+            create_icrc_ledger: Some(600_000_000_000),
+            margin_bps: Some(1_000),
+            ..Default::default()
+        },
+    );
+    assert_eq!(result, SetCanisterResult::Ok());
+
+    let balance_before = balance_of(&factory.pic, payment_ledger, caller());
+
+    create_paid_ledger(&factory, payment_ledger, caller());
+
+    // The fee plus the approval and transfer fees of the payment ledger.
+    let expected_cost = Nat::from(660_000_000_000u64 + 2 * 10_000);
+    assert_eq!(
+        balance_of(&factory.pic, payment_ledger, caller()),
+        balance_before - expected_cost
+    );
+}
+
+#[test]
+fn test_creation_keeps_the_margin_in_the_factory() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+
+    let result = set_pricing(
+        &factory,
+        SetPricingArgs {
+            margin_bps: Some(1_000),
+            ..Default::default()
+        },
+    );
+    assert_eq!(result, SetCanisterResult::Ok());
+
+    let base_fee = u128::from(Pricing::default().create_icrc_ledger);
+    let balance_before = factory.pic.cycle_balance(factory.canister_id);
+
+    create_paid_ledger(&factory, payment_ledger, caller());
+
+    // The new ledger is funded with the base fee only; the factory also burns a little for the
+    // calls it makes, well below the 10% margin.
+    let spent = balance_before - factory.pic.cycle_balance(factory.canister_id);
+    assert!(spent >= base_fee);
+    assert!(spent < base_fee + base_fee / 20);
+}
+
+#[test]
+fn test_fee_above_max_fee_is_refused_before_payment() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+
+    let fee = Pricing::default().create_icrc_ledger;
+
+    let result = set_pricing(
+        &factory,
+        SetPricingArgs {
+            margin_bps: Some(1_000),
+            ..Default::default()
+        },
+    );
+    assert_eq!(result, SetCanisterResult::Ok());
+
+    let balance_before = balance_of(&factory.pic, payment_ledger, caller());
+
+    let result = create_ledger_with_max_fee(&factory, payment_ledger, fee);
+
+    assert_eq!(
+        result,
+        CreateCanisterResult::Err(CreateCanisterError::FeeExceedsMax {
+            fee: fee + fee / 10,
+            max_fee: fee,
+        })
+    );
+
+    // Only the approval was paid for.
+    assert_eq!(
+        balance_of(&factory.pic, payment_ledger, caller()),
+        balance_before - Nat::from(10_000u64)
+    );
+}
@@ -0,0 +1,157 @@
+use candid::{Nat, Principal};
+use ic_papi_api::{caller::CallerPaysIcrc2Tokens, PaymentType};
+use icrc_factory::types::{
+    args::create_canister::{CanisterSettingsArgs, CreateIcrcLedgerArgs},
+    pricing::Pricing,
+    quote::{FeeQuote, PaymentMethod},
+    results::{
+        create_canister::{CreateCanisterError, CreateCanisterResult},
+        quote::QuoteResult,
+    },
+};
+
+use crate::utils::{
+    ledger::{approve, balance_of, setup_paid_factory},
+    pocketic::{caller, PicBackend, PicCanisterTrait},
+};
+
+/// Transfer fee of the payment ledger deployed by `setup_paid_factory`.
+const PAYMENT_LEDGER_FEE: u64 = 10_000;
+
+const THIRTY_DAYS: u64 = 30 * 24 * 60 * 60;
+
+fn ledger_args(settings: Option<CanisterSettingsArgs>) -> CreateIcrcLedgerArgs {
+    CreateIcrcLedgerArgs {
+        symbol: Some("TKN".to_string()),
+        name: Some("Token".to_string()),
+        transfer_fee: None,
+        decimals: None,
+        minting_account: None,
+        settings,
+        logo: None,
+    }
+}
+
+fn quote(factory: &PicBackend, args: CreateIcrcLedgerArgs) -> QuoteResult {
+    factory
+        .query(caller(), "quote_create_icrc_ledger", args)
+        .expect("Failed to query quote_create_icrc_ledger")
+}
+
+fn token_allowance(quote: &FeeQuote, payment_ledger: Principal) -> u64 {
+    quote
+        .payments
+        .iter()
+        .find(|p| {
+            p.method
+                == PaymentMethod::CallerPaysIcrc2Tokens {
+                    ledger: payment_ledger,
+                }
+        })
+        .and_then(|p| p.allowance)
+        .expect("The payment ledger should be quoted")
+}
+
+#[test]
+fn test_quote_covers_every_payment_method() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+
+    let QuoteResult::Ok(quote) = quote(&factory, ledger_args(None)) else {
+        panic!("Expected a quote");
+    };
+
+    let fee = Pricing::default().create_icrc_ledger;
+
+    assert_eq!(quote.cycles, fee);
+    assert_eq!(quote.payments.len(), 6);
+    assert!(quote.payments.iter().all(|p| p.amount == fee));
+
+    for method in [PaymentMethod::AttachedCycles, PaymentMethod::PrepaidBalance] {
+        let payment = quote
+            .payments
+            .iter()
+            .find(|p| p.method == method)
+            .expect("Method should be quoted");
+        assert_eq!(payment.allowance, None);
+    }
+
+    assert_eq!(
+        token_allowance(&quote, payment_ledger),
+        fee + PAYMENT_LEDGER_FEE
+    );
+}
+
+#[test]
+fn test_quote_includes_requested_allocations() {
+    let (factory, _) = setup_paid_factory(None);
+
+    let settings = CanisterSettingsArgs {
+        freezing_threshold: Some(THIRTY_DAYS),
+        compute_allocation: Some(1),
+        ..Default::default()
+    };
+
+    let QuoteResult::Ok(quote) = quote(&factory, ledger_args(Some(settings))) else {
+        panic!("Expected a quote");
+    };
+
+    assert_eq!(
+        quote.cycles,
+        Pricing::default().create_icrc_ledger + 10_000_000 * THIRTY_DAYS
+    );
+}
+
+#[test]
+fn test_quote_refuses_out_of_bounds_settings() {
+    let (factory, _) = setup_paid_factory(None);
+
+    let settings = CanisterSettingsArgs {
+        freezing_threshold: Some(60),
+        ..Default::default()
+    };
+
+    assert!(matches!(
+        quote(&factory, ledger_args(Some(settings))),
+        QuoteResult::Err(CreateCanisterError::InvalidCanisterSettings(_))
+    ));
+}
+
+#[test]
+fn test_quoted_allowance_is_enough_to_create_a_ledger() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+
+    let QuoteResult::Ok(quote) = quote(&factory, ledger_args(None)) else {
+        panic!("Expected a quote");
+    };
+    let allowance = token_allowance(&quote, payment_ledger);
+
+    let balance_before = balance_of(&factory.pic, payment_ledger, caller());
+
+    approve(
+        &factory.pic,
+        payment_ledger,
+        caller(),
+        factory.canister_id,
+        allowance,
+    );
+
+    let payment = PaymentType::CallerPaysIcrc2Tokens(CallerPaysIcrc2Tokens {
+        ledger: payment_ledger,
+    });
+
+    let result: CreateCanisterResult = factory
+        .update_with_args(
+            caller(),
+            "create_icrc_ledger",
+            (ledger_args(None), Some(payment), Some(quote.cycles)),
+        )
+        .expect("Failed to call create_icrc_ledger");
+
+    assert!(matches!(result, CreateCanisterResult::Ok(_)));
+
+    // The allowance and the approval fee were spent.
+    assert_eq!(
+        balance_of(&factory.pic, payment_ledger, caller()),
+        balance_before - Nat::from(allowance + PAYMENT_LEDGER_FEE)
+    );
+}
@@ -0,0 +1,207 @@
+use std::time::Duration;
+
+use candid::Principal;
+use ic_papi_api::{caller::CallerPaysIcrc2Tokens, PaymentType};
+use icrc_factory::types::{
+    args::create_canister::{CreateIcrcLedgerArgs, SetNameArgs, SetSymbolArgs},
+    rate_limit::{RateLimit, RateLimits},
+    results::create_canister::{CreateCanisterError, CreateCanisterResult, SetCanisterResult},
+};
+
+use crate::utils::{
+    ledger::{create_paid_ledger, create_paid_ledger_with_args, setup_paid_factory},
+    pocketic::{caller, controller, user_1, PicBackend, PicCanisterTrait},
+};
+
+const HOUR_SECS: u64 = 3_600;
+
+fn set_rate_limits(factory: &PicBackend, limits: RateLimits) -> SetCanisterResult {
+    factory
+        .update(controller(), "set_rate_limits", limits)
+        .expect("Failed to call set_rate_limits")
+}
+
+fn ledger_args() -> CreateIcrcLedgerArgs {
+    CreateIcrcLedgerArgs {
+        symbol: Some("TKN".to_string()),
+        name: Some("Token".to_string()),
+        transfer_fee: None,
+        decimals: None,
+        minting_account: None,
+        settings: None,
+        logo: None,
+    }
+}
+
+fn create_ledger(
+    factory: &PicBackend,
+    payment_ledger: Principal,
+    owner: Principal,
+) -> CreateCanisterResult {
+    create_paid_ledger_with_args(factory, payment_ledger, owner, ledger_args())
+}
+
+fn set_symbol(factory: &PicBackend, sender: Principal, ledger_id: Principal) -> SetCanisterResult {
+    factory
+        .update(
+            sender,
+            "set_symbol",
+            SetSymbolArgs {
+                ledger_id,
+                symbol: "NEW".to_string(),
+            },
+        )
+        .expect("Failed to call set_symbol")
+}
+
+fn assert_rate_limited(error: &CreateCanisterError, window_secs: u64) {
+    match error {
+        CreateCanisterError::RateLimited { retry_after } => {
+            assert!(*retry_after > 0 && *retry_after <= window_secs);
+        }
+        err => panic!("Unexpected error: {err:?}"),
+    }
+}
+
+#[test]
+fn test_principal_rate_limit_covers_creations_and_upgrades() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+
+    let result = set_rate_limits(
+        &factory,
+        RateLimits {
+            per_principal: Some(RateLimit {
+                max_calls: 2,
+                window_secs: HOUR_SECS,
+            }),
+            global: None,
+        },
+    );
+    assert_eq!(result, SetCanisterResult::Ok());
+
+    let ledger_id = create_paid_ledger(&factory, payment_ledger, caller());
+
+    assert_eq!(
+        set_symbol(&factory, caller(), ledger_id),
+        SetCanisterResult::Ok()
+    );
+
+    let result: SetCanisterResult = factory
+        .update(
+            caller(),
+            "set_name",
+            SetNameArgs {
+                ledger_id,
+                name: "New Token".to_string(),
+            },
+        )
+        .expect("Failed to call set_name");
+    match result {
+        SetCanisterResult::Err(err) => assert_rate_limited(&err, HOUR_SECS),
+        SetCanisterResult::Ok() => panic!("The upgrade was not rate limited"),
+    }
+
+    match create_ledger(&factory, payment_ledger, caller()) {
+        CreateCanisterResult::Err(err) => assert_rate_limited(&err, HOUR_SECS),
+        CreateCanisterResult::Ok(_) => panic!("The creation was not rate limited"),
+    }
+
+    // Other principals have their own window.
+    create_paid_ledger(&factory, payment_ledger, user_1());
+
+    factory.pic.advance_time(Duration::from_secs(HOUR_SECS));
+    create_paid_ledger(&factory, payment_ledger, caller());
+}
+
+#[test]
+fn test_failed_calls_are_not_counted() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+
+    let result = set_rate_limits(
+        &factory,
+        RateLimits {
+            per_principal: Some(RateLimit {
+                max_calls: 1,
+                window_secs: HOUR_SECS,
+            }),
+            global: None,
+        },
+    );
+    assert_eq!(result, SetCanisterResult::Ok());
+
+    let ledger_id = create_paid_ledger(&factory, payment_ledger, caller());
+
+    // Without an allowance, the payment fails.
+    let payment = PaymentType::CallerPaysIcrc2Tokens(CallerPaysIcrc2Tokens {
+        ledger: payment_ledger,
+    });
+    let result: CreateCanisterResult = factory
+        .update_with_args(
+            user_1(),
+            "create_icrc_ledger",
+            (ledger_args(), Some(payment)),
+        )
+        .expect("Failed to call create_icrc_ledger");
+    assert!(matches!(
+        result,
+        CreateCanisterResult::Err(CreateCanisterError::PaymentError(_))
+    ));
+
+    assert_eq!(
+        set_symbol(&factory, user_1(), ledger_id),
+        SetCanisterResult::Err(CreateCanisterError::NotOwner)
+    );
+
+    let own_ledger_id = create_paid_ledger(&factory, payment_ledger, user_1());
+
+    match set_symbol(&factory, user_1(), own_ledger_id) {
+        SetCanisterResult::Err(err) => assert_rate_limited(&err, HOUR_SECS),
+        SetCanisterResult::Ok() => panic!("The upgrade was not rate limited"),
+    }
+}
+
+#[test]
+fn test_global_rate_limit_applies_across_principals() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+
+    let result = set_rate_limits(
+        &factory,
+        RateLimits {
+            per_principal: None,
+            global: Some(RateLimit {
+                max_calls: 1,
+                window_secs: HOUR_SECS,
+            }),
+        },
+    );
+    assert_eq!(result, SetCanisterResult::Ok());
+
+    create_paid_ledger(&factory, payment_ledger, caller());
+
+    match create_ledger(&factory, payment_ledger, user_1()) {
+        CreateCanisterResult::Err(err) => assert_rate_limited(&err, HOUR_SECS),
+        CreateCanisterResult::Ok(_) => panic!("The creation was not rate limited"),
+    }
+}
+
+#[test]
+fn test_invalid_rate_limits_are_refused() {
+    let (factory, _) = setup_paid_factory(None);
+
+    let limits = RateLimits {
+        per_principal: Some(RateLimit {
+            max_calls: 0,
+            window_secs: HOUR_SECS,
+        }),
+        global: None,
+    };
+
+    assert!(matches!(
+        set_rate_limits(&factory, limits),
+        SetCanisterResult::Err(CreateCanisterError::InvalidRateLimit(_))
+    ));
+
+    let result: Result<SetCanisterResult, _> =
+        factory.update(caller(), "set_rate_limits", RateLimits::default());
+    assert!(result.is_err());
+}
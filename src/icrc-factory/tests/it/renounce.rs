@@ -0,0 +1,156 @@
+use std::fs::read;
+
+use candid::Principal;
+use icrc_factory::types::{
+    args::{
+        controllers::AddControllerArgs, create_canister::SetSymbolArgs,
+        ownership::ProposeOwnershipTransferArgs,
+    },
+    results::create_canister::{CreateCanisterError, SetCanisterResult},
+    user_canister::UserCanister,
+};
+
+use crate::utils::{
+    ledger::{create_paid_ledger, setup_paid_factory, spawn_archive},
+    pocketic::{caller, controller, user_1, BackendBuilder, PicBackend, PicCanisterTrait},
+};
+
+fn renounce(factory: &PicBackend, canister_id: Principal) -> SetCanisterResult {
+    factory
+        .update(caller(), "renounce_factory_control", canister_id)
+        .expect("Failed to call renounce_factory_control")
+}
+
+fn registry_entry(factory: &PicBackend, canister_id: Principal) -> UserCanister {
+    let canisters: Vec<UserCanister> = factory
+        .query(caller(), "list_user_canisters", ())
+        .expect("Failed to query list_user_canisters");
+
+    canisters
+        .into_iter()
+        .find(|c| c.canister_id == canister_id)
+        .expect("Canister should be registered")
+}
+
+fn controllers(factory: &PicBackend, canister_id: Principal, reader: Principal) -> Vec<Principal> {
+    factory
+        .pic
+        .canister_status(canister_id, Some(reader))
+        .expect("The reader should control the canister")
+        .settings
+        .controllers
+}
+
+fn module_hash(factory: &PicBackend, canister_id: Principal) -> Option<Vec<u8>> {
+    factory
+        .pic
+        .canister_status(canister_id, Some(caller()))
+        .expect("The owner should control the canister")
+        .module_hash
+}
+
+#[test]
+fn test_renounce_hands_ledger_and_archives_to_owner() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+    let ledger_id = create_paid_ledger(&factory, payment_ledger, caller());
+    let archive_id = spawn_archive(&factory.pic, ledger_id, caller());
+
+    assert!(controllers(&factory, archive_id, ledger_id).contains(&factory.canister_id));
+
+    assert_eq!(renounce(&factory, ledger_id), SetCanisterResult::Ok());
+
+    assert_eq!(controllers(&factory, ledger_id, caller()), vec![caller()]);
+
+    let archive_controllers = controllers(&factory, archive_id, caller());
+    assert!(archive_controllers.contains(&caller()));
+    assert!(archive_controllers.contains(&ledger_id));
+    assert!(!archive_controllers.contains(&factory.canister_id));
+
+    let entry = registry_entry(&factory, ledger_id);
+    assert!(entry.is_sovereign());
+    assert_eq!(entry.controllers, Some(vec![caller()]));
+    assert_eq!(entry.archive_controller, Some(caller()));
+}
+
+#[test]
+fn test_renounce_keeps_ledger_code() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+    let ledger_id = create_paid_ledger(&factory, payment_ledger, caller());
+    let module_hash_before = module_hash(&factory, ledger_id);
+
+    assert_eq!(renounce(&factory, ledger_id), SetCanisterResult::Ok());
+
+    assert_eq!(module_hash(&factory, ledger_id), module_hash_before);
+}
+
+#[test]
+fn test_renounce_refuses_ledger_running_another_wasm() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+    let ledger_id = create_paid_ledger(&factory, payment_ledger, caller());
+
+    // Any module other than the ledger's does; the factory's own is at hand.
+    let other_wasm =
+        read(BackendBuilder::default_wasm_path()).expect("Could not read the backend wasm");
+    factory
+        .update::<()>(controller(), "set_ledger_wasm", other_wasm)
+        .expect("Failed to call set_ledger_wasm");
+
+    assert_eq!(
+        renounce(&factory, ledger_id),
+        SetCanisterResult::Err(CreateCanisterError::ModuleHashMismatch)
+    );
+
+    let entry = registry_entry(&factory, ledger_id);
+    assert!(!entry.is_sovereign());
+    assert_eq!(entry.archive_controller, None);
+    assert!(controllers(&factory, ledger_id, caller()).contains(&factory.canister_id));
+}
+
+#[test]
+fn test_sovereign_canister_refuses_management_calls() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+    let ledger_id = create_paid_ledger(&factory, payment_ledger, caller());
+
+    assert_eq!(renounce(&factory, ledger_id), SetCanisterResult::Ok());
+
+    let results: Vec<SetCanisterResult> = vec![
+        factory
+            .update(
+                caller(),
+                "set_symbol",
+                SetSymbolArgs {
+                    ledger_id,
+                    symbol: "NEW".to_string(),
+                },
+            )
+            .expect("Failed to call set_symbol"),
+        factory
+            .update(
+                caller(),
+                "add_controller",
+                AddControllerArgs {
+                    canister_id: ledger_id,
+                    controller: user_1(),
+                },
+            )
+            .expect("Failed to call add_controller"),
+        factory
+            .update(
+                caller(),
+                "propose_ownership_transfer",
+                ProposeOwnershipTransferArgs {
+                    canister_id: ledger_id,
+                    new_owner: Some(user_1()),
+                },
+            )
+            .expect("Failed to call propose_ownership_transfer"),
+        renounce(&factory, ledger_id),
+    ];
+
+    for result in results {
+        assert_eq!(
+            result,
+            SetCanisterResult::Err(CreateCanisterError::Sovereign)
+        );
+    }
+}
@@ -0,0 +1,128 @@
+use candid::Principal;
+use icrc_factory::types::{
+    args::{discounts::SetFreeQuotaArgs, roles::RoleArgs},
+    event::{Event, EventFilter, EventKind},
+    log::{LogEntry, LogFilter},
+    results::create_canister::SetCanisterResult,
+    role::{Role, RoleAssignment},
+};
+
+use crate::utils::pocketic::{caller, controller, setup, user_1, PicBackend, PicCanisterTrait};
+
+fn set_role(factory: &PicBackend, method: &str, principal: Principal, role: Role) {
+    let result: SetCanisterResult = factory
+        .update(controller(), method, RoleArgs { principal, role })
+        .expect("Failed to set role");
+
+    assert_eq!(result, SetCanisterResult::Ok());
+}
+
+fn list_logs(factory: &PicBackend, principal: Principal) -> Result<Vec<LogEntry>, String> {
+    factory.query_with_args(
+        principal,
+        "list_logs",
+        (None::<u64>, None::<u64>, None::<LogFilter>),
+    )
+}
+
+#[test]
+fn test_role_grants_access_to_its_endpoints_only() {
+    let factory = setup();
+
+    set_role(&factory, "grant_role", user_1(), Role::PricingManager);
+
+    let result: SetCanisterResult = factory
+        .update(
+            user_1(),
+            "set_free_quota",
+            SetFreeQuotaArgs {
+                principal: caller(),
+                creations: 1,
+            },
+        )
+        .expect("Failed to call set_free_quota");
+    assert_eq!(result, SetCanisterResult::Ok());
+
+    let result: Result<(), _> = factory.update(user_1(), "set_ledger_wasm", Vec::<u8>::new());
+    assert!(result.is_err());
+
+    assert!(list_logs(&factory, user_1()).is_err());
+
+    let roles: Vec<Role> = factory
+        .query(user_1(), "my_roles", ())
+        .expect("Failed to query my_roles");
+    assert_eq!(roles, vec![Role::PricingManager]);
+}
+
+#[test]
+fn test_revoked_role_is_refused_and_changes_are_audited() {
+    let factory = setup();
+
+    set_role(&factory, "grant_role", user_1(), Role::Auditor);
+    set_role(&factory, "grant_role", user_1(), Role::WasmManager);
+
+    assert!(list_logs(&factory, user_1()).is_ok());
+
+    let roles: Vec<RoleAssignment> = factory
+        .query(user_1(), "list_roles", ())
+        .expect("Failed to query list_roles");
+    assert_eq!(
+        roles,
+        vec![RoleAssignment {
+            principal: user_1(),
+            roles: vec![Role::WasmManager, Role::Auditor],
+        }]
+    );
+
+    set_role(&factory, "revoke_role", user_1(), Role::Auditor);
+
+    assert!(list_logs(&factory, user_1()).is_err());
+
+    let events: Vec<Event> = factory
+        .query_with_args(
+            caller(),
+            "list_events",
+            (
+                None::<u64>,
+                None::<u64>,
+                Some(EventFilter {
+                    principal: Some(user_1()),
+                    ..Default::default()
+                }),
+            ),
+        )
+        .expect("Failed to query list_events");
+    let kinds: Vec<EventKind> = events.into_iter().map(|event| event.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            EventKind::RoleGranted {
+                principal: user_1(),
+                role: Role::Auditor,
+            },
+            EventKind::RoleGranted {
+                principal: user_1(),
+                role: Role::WasmManager,
+            },
+            EventKind::RoleRevoked {
+                principal: user_1(),
+                role: Role::Auditor,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_roles_granted_by_non_controller_fail() {
+    let factory = setup();
+
+    let result: Result<SetCanisterResult, _> = factory.update(
+        user_1(),
+        "grant_role",
+        RoleArgs {
+            principal: user_1(),
+            role: Role::PricingManager,
+        },
+    );
+    assert!(result.is_err());
+}
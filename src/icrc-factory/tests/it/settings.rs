@@ -0,0 +1,111 @@
+use candid::{Nat, Principal};
+use ic_cdk::api::management_canister::main::LogVisibility;
+use icrc_factory::types::{
+    args::create_canister::{CanisterSettingsArgs, CreateIcrcLedgerArgs},
+    results::create_canister::{CreateCanisterError, CreateCanisterResult},
+};
+use pocket_ic::management_canister::LogVisibility as PicLogVisibility;
+
+use crate::utils::{
+    ledger::{balance_of, create_paid_ledger_with_args, setup_paid_factory},
+    pocketic::caller,
+};
+
+const NINETY_DAYS: u64 = 90 * 24 * 60 * 60;
+
+fn ledger_args(settings: CanisterSettingsArgs) -> CreateIcrcLedgerArgs {
+    CreateIcrcLedgerArgs {
+        symbol: None,
+        name: None,
+        transfer_fee: None,
+        decimals: None,
+        minting_account: None,
+        settings: Some(settings),
+        logo: None,
+    }
+}
+
+#[test]
+fn test_create_ledger_applies_settings() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+
+    let settings = CanisterSettingsArgs {
+        freezing_threshold: Some(NINETY_DAYS),
+        wasm_memory_limit: Some(1024 * 1024 * 1024),
+        reserved_cycles_limit: Some(1_000_000_000_000),
+        compute_allocation: None,
+        log_visibility: Some(LogVisibility::Public),
+    };
+
+    let ledger_id = match create_paid_ledger_with_args(
+        &factory,
+        payment_ledger,
+        caller(),
+        ledger_args(settings),
+    ) {
+        CreateCanisterResult::Ok(ledger_id) => ledger_id,
+        CreateCanisterResult::Err(err) => panic!("Ledger creation failed: {err:?}"),
+    };
+
+    let status = factory
+        .pic
+        .canister_status(ledger_id, Some(caller()))
+        .expect("The owner should be able to read the ledger status");
+
+    assert_eq!(status.settings.freezing_threshold, NINETY_DAYS.into());
+    assert_eq!(
+        status.settings.wasm_memory_limit,
+        (1024u64 * 1024 * 1024).into()
+    );
+    assert_eq!(
+        status.settings.reserved_cycles_limit,
+        1_000_000_000_000u64.into()
+    );
+    assert_eq!(status.settings.log_visibility, PicLogVisibility::Public);
+}
+
+#[test]
+fn test_out_of_bounds_settings_are_refused_before_payment() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+
+    let balance_before = balance_of(&factory.pic, payment_ledger, caller());
+
+    let invalid_settings = [
+        CanisterSettingsArgs {
+            freezing_threshold: Some(60),
+            ..Default::default()
+        },
+        CanisterSettingsArgs {
+            wasm_memory_limit: Some(1024),
+            ..Default::default()
+        },
+        CanisterSettingsArgs {
+            compute_allocation: Some(100),
+            ..Default::default()
+        },
+        CanisterSettingsArgs {
+            log_visibility: Some(LogVisibility::AllowedViewers(vec![
+                Principal::anonymous();
+                11
+            ])),
+            ..Default::default()
+        },
+    ];
+
+    for settings in invalid_settings {
+        let result =
+            create_paid_ledger_with_args(&factory, payment_ledger, caller(), ledger_args(settings));
+
+        assert!(matches!(
+            result,
+            CreateCanisterResult::Err(CreateCanisterError::InvalidCanisterSettings(_))
+        ));
+    }
+
+    // Only the approvals were paid for.
+    let approval_fees = Nat::from(4 * 10_000u64);
+    assert_eq!(
+        balance_of(&factory.pic, payment_ledger, caller()),
+        balance_before - approval_fees
+    );
+}
@@ -0,0 +1,182 @@
+use icrc_factory::types::{
+    args::create_canister::{CreateIcrcLedgerArgs, SetSymbolArgs},
+    http::{HttpGatewayRequest, HttpGatewayResponse},
+    results::create_canister::{CreateCanisterError, CreateCanisterResult, SetCanisterResult},
+};
+use serde_bytes::ByteBuf;
+use serde_json::Value;
+
+use crate::utils::{
+    ledger::{create_paid_ledger_with_args, setup_paid_factory},
+    pocketic::{caller, PicBackend, PicCanisterTrait},
+};
+
+const LOGO: &str = "data:image/svg+xml;base64,PHN2Zy8+";
+
+/// The token list schema, adapted to IC addresses.
+const TOKEN_LIST_SCHEMA: &str = include_str!("tokenlist.schema.json");
+
+fn ledger_args() -> CreateIcrcLedgerArgs {
+    CreateIcrcLedgerArgs {
+        symbol: Some("TKN".to_string()),
+        name: Some("Token".to_string()),
+        transfer_fee: None,
+        decimals: Some(6),
+        minting_account: None,
+        settings: None,
+        logo: Some(LOGO.to_string()),
+    }
+}
+
+fn get(factory: &PicBackend, url: &str) -> HttpGatewayResponse {
+    factory
+        .query(
+            caller(),
+            "http_request",
+            HttpGatewayRequest {
+                method: "GET".to_string(),
+                url: url.to_string(),
+                headers: vec![],
+                body: ByteBuf::new(),
+            },
+        )
+        .expect("Failed to query http_request")
+}
+
+/// Returns the JSON document served at `url`, once the pending refreshes have run.
+fn get_json(factory: &PicBackend, url: &str) -> Value {
+    factory.pic.tick();
+
+    let response = get(factory, url);
+    assert_eq!(response.status_code, 200);
+
+    serde_json::from_slice(&response.body).expect("The response is JSON")
+}
+
+/// Returns the token list page served at `url`, checked against the token list schema.
+fn token_list(factory: &PicBackend, url: &str) -> Value {
+    let list = get_json(factory, url);
+
+    let schema = serde_json::from_str(TOKEN_LIST_SCHEMA).expect("The schema is JSON");
+    let validator = jsonschema::validator_for(&schema).expect("The schema is valid");
+    let errors: Vec<String> = validator
+        .iter_errors(&list)
+        .map(|error| error.to_string())
+        .collect();
+    assert!(errors.is_empty(), "The token list is not valid: {errors:?}");
+
+    list
+}
+
+#[test]
+fn test_token_list_lists_created_ledgers() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+
+    let CreateCanisterResult::Ok(ledger_id) =
+        create_paid_ledger_with_args(&factory, payment_ledger, caller(), ledger_args())
+    else {
+        panic!("Ledger creation failed");
+    };
+
+    let list = token_list(&factory, "/tokens.json");
+
+    let tokens = list["tokens"].as_array().expect("Tokens are an array");
+    assert_eq!(tokens.len(), 1);
+    assert_eq!(tokens[0]["chainId"], 223);
+    assert_eq!(tokens[0]["address"], ledger_id.to_text());
+    assert_eq!(tokens[0]["symbol"], "TKN");
+    assert_eq!(tokens[0]["name"], "Token");
+    assert_eq!(tokens[0]["decimals"], 6);
+    assert_eq!(tokens[0]["logoURI"], LOGO);
+    assert_eq!(tokens[0]["extensions"]["owner"], caller().to_text());
+}
+
+#[test]
+fn test_token_list_index_links_every_page() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+
+    let index = get_json(&factory, "/tokens/index.json");
+    assert_eq!(index["tokenCount"], 0);
+    assert_eq!(index["pages"], serde_json::json!([]));
+
+    let CreateCanisterResult::Ok(_) =
+        create_paid_ledger_with_args(&factory, payment_ledger, caller(), ledger_args())
+    else {
+        panic!("Ledger creation failed");
+    };
+
+    let index = get_json(&factory, "/tokens/index.json");
+    assert_eq!(index["tokenCount"], 1);
+    assert_eq!(index["pages"], serde_json::json!(["/tokens.json"]));
+}
+
+#[test]
+fn test_invalid_logos_are_refused() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+
+    for logo in [
+        format!("data:image/png;base64,{}", "A".repeat(8 * 1024)),
+        "javascript:alert(1)".to_string(),
+        "https://example.com/logo with spaces.png".to_string(),
+    ] {
+        let result = create_paid_ledger_with_args(
+            &factory,
+            payment_ledger,
+            caller(),
+            CreateIcrcLedgerArgs {
+                logo: Some(logo),
+                ..ledger_args()
+            },
+        );
+
+        assert!(
+            matches!(
+                result,
+                CreateCanisterResult::Err(CreateCanisterError::InvalidLogo(_))
+            ),
+            "{result:?}"
+        );
+    }
+}
+
+#[test]
+fn test_token_list_follows_symbol_changes() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+
+    let CreateCanisterResult::Ok(ledger_id) =
+        create_paid_ledger_with_args(&factory, payment_ledger, caller(), ledger_args())
+    else {
+        panic!("Ledger creation failed");
+    };
+
+    let result: SetCanisterResult = factory
+        .update(
+            caller(),
+            "set_symbol",
+            SetSymbolArgs {
+                ledger_id,
+                symbol: "NEW".to_string(),
+            },
+        )
+        .expect("Failed to call set_symbol");
+    assert_eq!(result, SetCanisterResult::Ok());
+
+    let list = token_list(&factory, "/tokens.json");
+
+    assert_eq!(list["tokens"][0]["symbol"], "NEW");
+}
+
+#[test]
+fn test_token_list_is_certified() {
+    let (factory, _) = setup_paid_factory(None);
+
+    let response = get(&factory, "/tokens/index.json");
+
+    assert_eq!(response.status_code, 200);
+    assert!(response
+        .headers
+        .iter()
+        .any(|(name, value)| name == "IC-Certificate" && value.starts_with("certificate=:")));
+    // No page is served until a token is listed.
+    assert_eq!(get(&factory, "/tokens.json").status_code, 404);
+}
@@ -0,0 +1,146 @@
+use candid::Principal;
+use ic_papi_api::{caller::CallerPaysIcrc2Tokens, PaymentType};
+use icrc_factory::types::{
+    args::top_up::TopUpCanisterArgs,
+    pricing::Pricing,
+    results::create_canister::{CreateCanisterError, SetCanisterResult},
+};
+
+use crate::utils::{
+    ledger::{approve, create_paid_ledger, setup_paid_factory},
+    pocketic::{caller, user_1, PicBackend, PicCanisterTrait},
+};
+
+const TOP_UP_CYCLES: u64 = 1_000_000_000_000;
+
+/// Covers the top-up, the service fee and the ledger fee.
+const TOP_UP_ALLOWANCE: u64 = 2_000_000_000_000;
+
+fn top_up(
+    factory: &PicBackend,
+    payment_ledger: Principal,
+    payer: Principal,
+    args: TopUpCanisterArgs,
+) -> SetCanisterResult {
+    approve(
+        &factory.pic,
+        payment_ledger,
+        payer,
+        factory.canister_id,
+        TOP_UP_ALLOWANCE,
+    );
+
+    let payment = PaymentType::CallerPaysIcrc2Tokens(CallerPaysIcrc2Tokens {
+        ledger: payment_ledger,
+    });
+
+    factory
+        .update_with_args(payer, "top_up_canister", (args, Some(payment)))
+        .expect("Failed to call top_up_canister")
+}
+
+#[test]
+fn test_anyone_can_top_up_a_registered_canister() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+    let ledger_id = create_paid_ledger(&factory, payment_ledger, caller());
+
+    let balance_before = factory.pic.cycle_balance(ledger_id);
+
+    let result = top_up(
+        &factory,
+        payment_ledger,
+        user_1(),
+        TopUpCanisterArgs {
+            canister_id: ledger_id,
+            cycles: TOP_UP_CYCLES,
+        },
+    );
+
+    assert_eq!(result, SetCanisterResult::Ok());
+
+    // The ledger keeps burning cycles while the top-up is processed.
+    let balance_after = factory.pic.cycle_balance(ledger_id);
+    assert!(balance_after > balance_before + u128::from(TOP_UP_CYCLES) * 99 / 100);
+}
+
+#[test]
+fn test_top_up_unregistered_canister_fails() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+
+    let result = top_up(
+        &factory,
+        payment_ledger,
+        caller(),
+        TopUpCanisterArgs {
+            canister_id: payment_ledger,
+            cycles: TOP_UP_CYCLES,
+        },
+    );
+
+    assert_eq!(
+        result,
+        SetCanisterResult::Err(CreateCanisterError::CanisterNotFound)
+    );
+}
+
+#[test]
+fn test_top_up_below_minimum_fails() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+    let ledger_id = create_paid_ledger(&factory, payment_ledger, caller());
+
+    let result = top_up(
+        &factory,
+        payment_ledger,
+        caller(),
+        TopUpCanisterArgs {
+            canister_id: ledger_id,
+            cycles: 1_000,
+        },
+    );
+
+    assert_eq!(
+        result,
+        SetCanisterResult::Err(CreateCanisterError::InvalidTopUpAmount)
+    );
+}
+
+#[test]
+fn test_failed_deposit_is_credited_to_prepaid_balance() {
+    let (factory, payment_ledger) = setup_paid_factory(None);
+    let ledger_id = create_paid_ledger(&factory, payment_ledger, caller());
+
+    // Deleted behind the factory's back, the ledger is still registered but cannot take cycles.
+    factory
+        .pic
+        .stop_canister(ledger_id, Some(caller()))
+        .expect("The owner should be able to stop the ledger");
+    factory
+        .pic
+        .delete_canister(ledger_id, Some(caller()))
+        .expect("The owner should be able to delete the ledger");
+
+    let result = top_up(
+        &factory,
+        payment_ledger,
+        caller(),
+        TopUpCanisterArgs {
+            canister_id: ledger_id,
+            cycles: TOP_UP_CYCLES,
+        },
+    );
+
+    assert!(matches!(
+        result,
+        SetCanisterResult::Err(CreateCanisterError::TopUpFailed(_))
+    ));
+
+    let balance: u64 = factory
+        .query(caller(), "prepaid_balance", ())
+        .expect("Failed to query prepaid_balance");
+    let pricing: Pricing = factory
+        .query(caller(), "get_pricing", ())
+        .expect("Failed to query get_pricing");
+    let fee = pricing.with_margin(pricing.top_up_canister);
+
+    assert_eq!(balance, TOP_UP_CYCLES + fee);
+}
@@ -0,0 +1,28 @@
+//! Utilities for checking certified responses in `PocketIC` tests.
+use ic_certification::{Certificate, HashTree, LookupResult};
+use serde_bytes::ByteBuf;
+
+use crate::utils::pocketic::PicBackend;
+
+/// Checks that `witness` is certified by `certificate`, and returns it.
+pub fn verified_witness(
+    factory: &PicBackend,
+    certificate: &ByteBuf,
+    witness: &ByteBuf,
+) -> HashTree {
+    let certificate: Certificate =
+        serde_cbor::from_slice(certificate).expect("The certificate is CBOR");
+    let witness: HashTree = serde_cbor::from_slice(witness).expect("The witness is CBOR");
+
+    let certified_data = certificate.tree.lookup_path([
+        b"canister".as_slice(),
+        factory.canister_id.as_slice(),
+        b"certified_data".as_slice(),
+    ]);
+    assert_eq!(
+        certified_data,
+        LookupResult::Found(witness.digest().as_slice())
+    );
+
+    witness
+}
@@ -0,0 +1,106 @@
+//! Utilities for paying the factory with ICP in `PocketIC` tests, against stand-in ICP ledger and
+//! CMC canisters.
+use std::{env, fs::read};
+
+use candid::{encode_one, CandidType, Principal};
+use icrc_factory::types::config::{Config, InitArgs};
+use pocket_ic::PocketIcBuilder;
+
+use crate::utils::{
+    ledger::{deploy_payment_ledger, ledger_wasm},
+    pocketic::{caller, controller, user_1, BackendBuilder, PicBackend, PicCanisterTrait},
+};
+
+const CMC_STUB_WASM: &str = "../../target/wasm32-unknown-unknown/release/cmc_stub.wasm";
+
+/// Conversion rate reported by the stand-in CMC: 4 XDR, i.e. 4T cycles, per ICP.
+pub const XDR_PERMYRIAD_PER_ICP: u64 = 40_000;
+
+/// Transfer fee of the stand-in ICP ledger, in e8s; unlike the mainnet fee, so that quotes show the
+/// factory read it from the ledger.
+pub const ICP_LEDGER_FEE: u64 = 20_000;
+
+/// Cycles the stand-in CMC holds to "mint" cycles with.
+const CMC_CYCLES: u128 = 100_000_000_000_000;
+
+/// Cycles given to a factory deployed by [`setup_icp_factory`].
+const FACTORY_CYCLES: u128 = 10_000_000_000_000;
+
+#[derive(CandidType)]
+struct CmcStubInitArgs {
+    ledger: Principal,
+    xdr_permyriad_per_icp: u64,
+}
+
+fn cmc_stub_wasm() -> Vec<u8> {
+    let path = env::var("CMC_STUB_WASM_PATH").unwrap_or_else(|_| CMC_STUB_WASM.to_string());
+    read(&path).unwrap_or_else(|_| panic!("Could not find the CMC stub wasm: {path}"))
+}
+
+/// Deploys the factory on an application subnet, alongside a stand-in ICP ledger that funds
+/// [`caller`] and [`user_1`] and a stand-in CMC converting it to cycles, and stores the ledger wasm
+/// in the factory.
+///
+/// Returns the factory and the ICP ledger.
+pub fn setup_icp_factory() -> (PicBackend, Principal) {
+    let pic = PocketIcBuilder::new().with_application_subnet().build();
+    let subnet = pic.topology().get_app_subnets()[0];
+
+    let icp_ledger = deploy_payment_ledger(&pic, subnet, &[caller(), user_1()], ICP_LEDGER_FEE);
+
+    let cmc = pic.create_canister_on_subnet(None, None, subnet);
+    pic.add_cycles(cmc, CMC_CYCLES);
+    pic.install_canister(
+        cmc,
+        cmc_stub_wasm(),
+        encode_one(CmcStubInitArgs {
+            ledger: icp_ledger,
+            xdr_permyriad_per_icp: XDR_PERMYRIAD_PER_ICP,
+        })
+        .expect("encode CMC stub init args"),
+        None,
+    );
+
+    let factory_id = pic.create_canister_on_subnet(None, None, subnet);
+    let factory = BackendBuilder::default()
+        .with_canister_id(factory_id)
+        .with_cycles(FACTORY_CYCLES)
+        .with_init_args(InitArgs {
+            cycles_ledger: None,
+            blackhole_canister: None,
+            icp_ledger: Some(icp_ledger),
+            cmc: Some(cmc),
+        })
+        .deploy_on(pic);
+
+    factory
+        .update::<()>(controller(), "set_ledger_wasm", ledger_wasm())
+        .expect("Test setup error: failed to store the ledger wasm");
+
+    (factory, icp_ledger)
+}
+
+/// The e8s of ICP the factory charges for `cycles`.
+pub fn cycles_to_e8s(cycles: u64) -> u64 {
+    cycles.div_ceil(XDR_PERMYRIAD_PER_ICP)
+}
+
+/// Makes the stand-in CMC of `factory` refuse, or accept again, every `notify_top_up`.
+pub fn set_cmc_notify_rejected(factory: &PicBackend, rejected: bool) {
+    let config: Config = factory
+        .query(caller(), "config", ())
+        .expect("Failed to query config");
+    let cmc = config
+        .cmc
+        .expect("Test setup error: the factory has no CMC");
+
+    factory
+        .pic
+        .update_call(
+            cmc,
+            controller(),
+            "set_notify_rejected",
+            encode_one(rejected).expect("encode set_notify_rejected args"),
+        )
+        .expect("Test setup error: set_notify_rejected call failed");
+}
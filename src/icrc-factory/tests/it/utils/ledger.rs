@@ -0,0 +1,344 @@
+//! Utilities for deploying and using ICRC ledgers in `PocketIC` tests.
+use std::{env, fs::read};
+
+use candid::{encode_one, CandidType, Nat, Principal};
+use ic_papi_api::{caller::CallerPaysIcrc2Tokens, PaymentType};
+use icrc_factory::types::{
+    args::create_canister::{CreateIcrcIndexArgs, CreateIcrcLedgerArgs},
+    config::InitArgs,
+    ledger_suite::{
+        common::FeatureFlags,
+        ledger::{
+            archive_info::ArchiveInfo,
+            init_args::{ArchiveOptions, InitArgs as LedgerInitArgs},
+        },
+    },
+    results::create_canister::CreateCanisterResult,
+};
+use icrc_ledger_types::{
+    icrc1::{account::Account, transfer::TransferArg},
+    icrc2::approve::{ApproveArgs, ApproveError},
+};
+use pocket_ic::{PocketIc, PocketIcBuilder};
+use serde::Deserialize;
+
+use crate::utils::pocketic::{
+    caller, controller, user_1, BackendBuilder, PicBackend, PicCanisterTrait,
+};
+
+const LEDGER_WASM: &str = "../../target/ic-icrc1-ledger.wasm.gz";
+
+/// Balance given to every holder of a payment ledger deployed by [`deploy_payment_ledger`].
+pub const PAYMENT_LEDGER_INITIAL_BALANCE: u64 = 100_000_000_000_000;
+
+/// Transfer fee of the payment ledger deployed by [`setup_paid_factory`].
+const PAYMENT_LEDGER_FEE: u64 = 10_000;
+
+/// Allowance granted to the factory before each paid ledger creation; covers the fee and the
+/// ledger transfer fee.
+const CREATION_ALLOWANCE: u64 = 1_000_000_000_000;
+
+/// Cycles given to a factory deployed by [`setup_paid_factory`].
+const FACTORY_CYCLES: u128 = 10_000_000_000_000;
+
+/// Number of blocks past which a ledger created by the factory spawns an archive.
+const ARCHIVE_TRIGGER_THRESHOLD: u64 = 2_000;
+
+/// Cycles a ledger created by the factory needs to spawn an archive, with some margin.
+const ARCHIVE_CREATION_CYCLES: u128 = 15_000_000_000_000;
+
+#[derive(CandidType, Deserialize)]
+enum LedgerArgs {
+    Init(LedgerInitArgs),
+}
+
+pub fn ledger_wasm_path() -> String {
+    env::var("LEDGER_WASM_PATH").unwrap_or_else(|_| LEDGER_WASM.to_string())
+}
+
+pub fn ledger_wasm() -> Vec<u8> {
+    let path = ledger_wasm_path();
+    read(&path).unwrap_or_else(|_| panic!("Could not find the ledger wasm: {path}"))
+}
+
+/// Deploys an ICRC-2 ledger on `subnet` that funds each of `holders` and charges `transfer_fee`, to
+/// be used as the payment ledger of the factory.
+pub fn deploy_payment_ledger(
+    pic: &PocketIc,
+    subnet: Principal,
+    holders: &[Principal],
+    transfer_fee: u64,
+) -> Principal {
+    let init_args = LedgerInitArgs {
+        minting_account: Account {
+            owner: controller(),
+            subaccount: None,
+        },
+        fee_collector_account: None,
+        initial_balances: holders
+            .iter()
+            .map(|holder| {
+                (
+                    Account {
+                        owner: *holder,
+                        subaccount: None,
+                    },
+                    Nat::from(PAYMENT_LEDGER_INITIAL_BALANCE),
+                )
+            })
+            .collect(),
+        transfer_fee: Nat::from(transfer_fee),
+        decimals: Some(12),
+        token_name: "Test Cycles".to_string(),
+        token_symbol: "TCYCLES".to_string(),
+        metadata: vec![],
+        archive_options: ArchiveOptions {
+            trigger_threshold: 2_000,
+            num_blocks_to_archive: 1_000,
+            node_max_memory_size_bytes: None,
+            max_message_size_bytes: None,
+            controller_id: controller(),
+            more_controller_ids: None,
+            cycles_for_archive_creation: None,
+            max_transactions_per_response: None,
+        },
+        max_memo_length: None,
+        feature_flags: Some(FeatureFlags { icrc2: true }),
+        index_principal: None,
+    };
+
+    let ledger_id = pic.create_canister_on_subnet(None, None, subnet);
+    pic.add_cycles(ledger_id, 10_000_000_000_000);
+    pic.install_canister(
+        ledger_id,
+        ledger_wasm(),
+        encode_one(LedgerArgs::Init(init_args)).expect("encode ledger init args"),
+        None,
+    );
+    ledger_id
+}
+
+/// Approves `spender` to pull `amount` tokens from the default account of `owner`.
+pub fn approve(
+    pic: &PocketIc,
+    ledger_id: Principal,
+    owner: Principal,
+    spender: Principal,
+    amount: u64,
+) {
+    let args = ApproveArgs {
+        from_subaccount: None,
+        spender: Account {
+            owner: spender,
+            subaccount: None,
+        },
+        amount: Nat::from(amount),
+        expected_allowance: None,
+        expires_at: None,
+        fee: None,
+        memo: None,
+        created_at_time: None,
+    };
+
+    let reply = pic
+        .update_call(
+            ledger_id,
+            owner,
+            "icrc2_approve",
+            encode_one(args).expect("encode approve args"),
+        )
+        .expect("Test setup error: icrc2_approve call failed");
+    let result: Result<Nat, ApproveError> =
+        candid::decode_one(&reply).expect("Test setup error: failed to decode approve result");
+    result.expect("Test setup error: approval was rejected");
+}
+
+/// Deploys the factory on an application subnet, alongside a payment ledger that funds
+/// [`caller`] and [`user_1`], and stores the ledger wasm in the factory.
+///
+/// Fiduciary subnets scale creation costs beyond the factory fee, hence the application subnet.
+///
+/// Returns the factory and the payment ledger.
+pub fn setup_paid_factory(blackhole_canister: Option<Principal>) -> (PicBackend, Principal) {
+    let pic = PocketIcBuilder::new().with_application_subnet().build();
+    let subnet = pic.topology().get_app_subnets()[0];
+
+    let payment_ledger =
+        deploy_payment_ledger(&pic, subnet, &[caller(), user_1()], PAYMENT_LEDGER_FEE);
+
+    let factory_id = pic.create_canister_on_subnet(None, None, subnet);
+    let factory = BackendBuilder::default()
+        .with_canister_id(factory_id)
+        .with_cycles(FACTORY_CYCLES)
+        .with_init_args(InitArgs {
+            cycles_ledger: Some(payment_ledger),
+            blackhole_canister,
+            icp_ledger: None,
+            cmc: None,
+        })
+        .deploy_on(pic);
+
+    factory
+        .update::<()>(controller(), "set_ledger_wasm", ledger_wasm())
+        .expect("Test setup error: failed to store the ledger wasm");
+
+    (factory, payment_ledger)
+}
+
+/// Creates a ledger through the factory on behalf of `owner`, paying with the payment ledger.
+pub fn create_paid_ledger(
+    factory: &PicBackend,
+    payment_ledger: Principal,
+    owner: Principal,
+) -> Principal {
+    let args = CreateIcrcLedgerArgs {
+        symbol: Some("TKN".to_string()),
+        name: Some("Token".to_string()),
+        transfer_fee: None,
+        decimals: None,
+        minting_account: None,
+        settings: None,
+        logo: None,
+    };
+
+    match create_paid_ledger_with_args(factory, payment_ledger, owner, args) {
+        CreateCanisterResult::Ok(ledger_id) => ledger_id,
+        CreateCanisterResult::Err(err) => {
+            panic!("Test setup error: ledger creation failed: {err:?}")
+        }
+    }
+}
+
+/// Calls `create_icrc_ledger` with `args` on behalf of `owner`, paying with the payment ledger.
+pub fn create_paid_ledger_with_args(
+    factory: &PicBackend,
+    payment_ledger: Principal,
+    owner: Principal,
+    args: CreateIcrcLedgerArgs,
+) -> CreateCanisterResult {
+    approve(
+        &factory.pic,
+        payment_ledger,
+        owner,
+        factory.canister_id,
+        CREATION_ALLOWANCE,
+    );
+
+    let payment = PaymentType::CallerPaysIcrc2Tokens(CallerPaysIcrc2Tokens {
+        ledger: payment_ledger,
+    });
+
+    factory
+        .update_with_args(owner, "create_icrc_ledger", (args, Some(payment)))
+        .expect("Test setup error: create_icrc_ledger call failed")
+}
+
+/// Creates an index for `ledger_id` through the factory on behalf of `owner`, paying with the
+/// payment ledger. The index WASM must have been stored beforehand.
+pub fn create_paid_index(
+    factory: &PicBackend,
+    payment_ledger: Principal,
+    owner: Principal,
+    ledger_id: Principal,
+) -> Principal {
+    approve(
+        &factory.pic,
+        payment_ledger,
+        owner,
+        factory.canister_id,
+        CREATION_ALLOWANCE,
+    );
+
+    let args = CreateIcrcIndexArgs {
+        ledger_id,
+        settings: None,
+    };
+    let payment = PaymentType::CallerPaysIcrc2Tokens(CallerPaysIcrc2Tokens {
+        ledger: payment_ledger,
+    });
+
+    let result: CreateCanisterResult = factory
+        .update_with_args(owner, "create_icrc_index", (args, Some(payment)))
+        .expect("Test setup error: create_icrc_index call failed");
+
+    match result {
+        CreateCanisterResult::Ok(index_id) => index_id,
+        CreateCanisterResult::Err(err) => {
+            panic!("Test setup error: index creation failed: {err:?}")
+        }
+    }
+}
+
+/// Returns the balance of the default account of `owner`.
+pub fn balance_of(pic: &PocketIc, ledger_id: Principal, owner: Principal) -> Nat {
+    let account = Account {
+        owner,
+        subaccount: None,
+    };
+
+    let reply = pic
+        .query_call(
+            ledger_id,
+            owner,
+            "icrc1_balance_of",
+            encode_one(account).expect("encode account"),
+        )
+        .expect("Test setup error: icrc1_balance_of call failed");
+
+    candid::decode_one(&reply).expect("Test setup error: failed to decode balance")
+}
+
+/// Returns the archives a ledger has spawned so far.
+pub fn archives(pic: &PocketIc, ledger_id: Principal) -> Vec<Principal> {
+    let reply = pic
+        .query_call(
+            ledger_id,
+            Principal::anonymous(),
+            "archives",
+            candid::encode_args(()).expect("encode empty args"),
+        )
+        .expect("Test setup error: archives call failed");
+    let archives: Vec<ArchiveInfo> =
+        candid::decode_one(&reply).expect("Test setup error: failed to decode archives");
+
+    archives.into_iter().map(|a| a.canister_id).collect()
+}
+
+/// Mints enough blocks on a ledger created by the factory for it to spawn an archive, and returns
+/// the archive.
+pub fn spawn_archive(pic: &PocketIc, ledger_id: Principal, minter: Principal) -> Principal {
+    pic.add_cycles(ledger_id, ARCHIVE_CREATION_CYCLES);
+
+    let mint = TransferArg {
+        from_subaccount: None,
+        to: Account {
+            owner: user_1(),
+            subaccount: None,
+        },
+        fee: None,
+        created_at_time: None,
+        memo: None,
+        amount: Nat::from(1u64),
+    };
+    let payload = encode_one(mint).expect("encode transfer args");
+
+    let messages: Vec<_> = (0..=ARCHIVE_TRIGGER_THRESHOLD)
+        .map(|_| {
+            pic.submit_call(ledger_id, minter, "icrc1_transfer", payload.clone())
+                .expect("Test setup error: failed to submit a mint")
+        })
+        .collect();
+    for message in messages {
+        pic.await_call(message)
+            .expect("Test setup error: a mint was rejected");
+    }
+
+    for _ in 0..10 {
+        if let Some(archive_id) = archives(pic, ledger_id).first() {
+            return *archive_id;
+        }
+        pic.tick();
+    }
+
+    panic!("Test setup error: the ledger did not spawn an archive");
+}
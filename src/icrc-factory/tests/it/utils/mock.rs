@@ -11,3 +11,7 @@ pub const USER_1: &str = "7blps-itamd-lzszp-7lbda-4nngn-fev5u-2jvpn-6y3ap-eunp7-
 
 /// The Cycles Ledger Canister ID.
 pub const CYCLES_LEDGER_CANISTER_ID: &str = "um5iw-rqaaa-aaaaq-qaaba-cai";
+
+/// The blackhole canister used by the IC community to make canisters immutable.
+#[allow(dead_code)]
+pub const BLACKHOLE_CANISTER_ID: &str = "e3mmv-5qaaa-aaaah-aadma-cai";
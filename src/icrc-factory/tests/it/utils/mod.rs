@@ -1,2 +1,6 @@
+pub mod certification;
+pub mod icp;
+pub mod ledger;
 pub mod mock;
 pub mod pocketic;
+pub mod prepaid;
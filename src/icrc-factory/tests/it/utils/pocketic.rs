@@ -7,7 +7,7 @@ use icrc_factory::types::config::{Args::Init, InitArgs};
 use pocket_ic::{PocketIc, PocketIcBuilder};
 
 use super::mock::{CONTROLLER, CYCLES_LEDGER_CANISTER_ID};
-use crate::utils::mock::CALLER;
+use crate::utils::mock::{CALLER, USER_1};
 pub use crate::utils::pocketic::pic_canister::PicCanisterTrait;
 
 const BACKEND_WASM: &str = "../../target/wasm32-unknown-unknown/release/icrc_factory.wasm";
@@ -15,6 +15,9 @@ const BACKEND_WASM: &str = "../../target/wasm32-unknown-unknown/release/icrc_fac
 fn default_init_args() -> InitArgs {
     InitArgs {
         cycles_ledger: Some(cycles_ledger_canister_id()),
+        blackhole_canister: None,
+        icp_ledger: None,
+        cmc: None,
     }
 }
 
@@ -80,6 +83,20 @@ impl BackendBuilder {
         self
     }
 
+    #[allow(dead_code)]
+    pub fn with_cycles(mut self, cycles: u128) -> Self {
+        self.cycles = cycles;
+        self
+    }
+
+    /// Installs the backend into an existing canister instead of creating one on the fiduciary
+    /// subnet.
+    #[allow(dead_code)]
+    pub fn with_canister_id(mut self, canister_id: Principal) -> Self {
+        self.canister_id = Some(canister_id);
+        self
+    }
+
     #[allow(dead_code)]
     pub fn with_wasm(mut self, wasm_path: &str) -> Self {
         self.wasm_path = wasm_path.to_string();
@@ -142,6 +159,10 @@ impl BackendBuilder {
             .with_ii_subnet()
             .with_fiduciary_subnet()
             .build();
+        self.deploy_on(pic)
+    }
+
+    pub fn deploy_on(&mut self, pic: PocketIc) -> PicBackend {
         if self.auto_progress_enabled {
             pic.auto_progress();
         }
@@ -164,6 +185,11 @@ pub fn caller() -> Principal {
     Principal::from_text(CALLER).expect("Test setup error: Failed to parse caller principal")
 }
 
+#[allow(dead_code)]
+pub fn user_1() -> Principal {
+    Principal::from_text(USER_1).expect("Test setup error: Failed to parse user principal")
+}
+
 pub fn cycles_ledger_canister_id() -> Principal {
     Principal::from_text(CYCLES_LEDGER_CANISTER_ID)
         .expect("Test setup error: Failed to parse cycles ledger canister principal")
@@ -1,7 +1,7 @@
 //! Common methods for interacting with a canister using `PocketIc`.
 use std::sync::Arc;
 
-use candid::{decode_one, encode_one, CandidType, Principal};
+use candid::{decode_one, encode_args, encode_one, utils::ArgumentEncoder, CandidType, Principal};
 use pocket_ic::PocketIc;
 use serde::Deserialize;
 
@@ -33,6 +33,33 @@ pub trait PicCanisterTrait {
             .and_then(|reply| decode_one(&reply).map_err(|e| format!("Decoding failed: {e}")))
     }
 
+    /// Makes an update call to the canister with several arguments.
+    #[allow(dead_code)]
+    fn update_with_args<T>(
+        &self,
+        caller: Principal,
+        method: &str,
+        args: impl ArgumentEncoder,
+    ) -> Result<T, String>
+    where
+        T: for<'a> Deserialize<'a> + CandidType,
+    {
+        self.pic()
+            .update_call(
+                self.canister_id(),
+                caller,
+                method,
+                encode_args(args).unwrap(),
+            )
+            .map_err(|e| {
+                format!(
+                    "Update call error. RejectionCode: {:?}, Error: {}",
+                    e.reject_code, e.reject_message
+                )
+            })
+            .and_then(|reply| decode_one(&reply).map_err(|e| format!("Decoding failed: {e}")))
+    }
+
     /// Makes a query call to the canister.
     fn query<T>(&self, caller: Principal, method: &str, arg: impl CandidType) -> Result<T, String>
     where
@@ -48,4 +75,31 @@ pub trait PicCanisterTrait {
             })
             .and_then(|reply| decode_one(&reply).map_err(|_| "Decoding failed".to_string()))
     }
+
+    /// Makes a query call to the canister with several arguments.
+    #[allow(dead_code)]
+    fn query_with_args<T>(
+        &self,
+        caller: Principal,
+        method: &str,
+        args: impl ArgumentEncoder,
+    ) -> Result<T, String>
+    where
+        T: for<'a> Deserialize<'a> + CandidType,
+    {
+        self.pic()
+            .query_call(
+                self.canister_id(),
+                caller,
+                method,
+                encode_args(args).unwrap(),
+            )
+            .map_err(|e| {
+                format!(
+                    "Query call error. RejectionCode: {:?}, Error: {}",
+                    e.reject_code, e.reject_message
+                )
+            })
+            .and_then(|reply| decode_one(&reply).map_err(|_| "Decoding failed".to_string()))
+    }
 }
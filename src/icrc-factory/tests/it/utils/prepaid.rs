@@ -0,0 +1,51 @@
+//! Utilities for funding and reading prepaid balances in `PocketIC` tests.
+use candid::Principal;
+use ic_papi_api::{caller::CallerPaysIcrc2Tokens, PaymentType};
+use icrc_factory::types::{
+    args::auto_top_up::FundPrepaidBalanceArgs, prepaid::PrepaidJournalEntry,
+    results::create_canister::SetCanisterResult,
+};
+
+use crate::utils::{
+    ledger::approve,
+    pocketic::{caller, PicBackend, PicCanisterTrait},
+};
+
+/// Funds the prepaid balance of [`caller`] with `cycles`, paid with the payment ledger.
+pub fn fund_prepaid_balance(factory: &PicBackend, payment_ledger: Principal, cycles: u64) {
+    approve(
+        &factory.pic,
+        payment_ledger,
+        caller(),
+        factory.canister_id,
+        cycles + 10_000,
+    );
+
+    let payment = PaymentType::CallerPaysIcrc2Tokens(CallerPaysIcrc2Tokens {
+        ledger: payment_ledger,
+    });
+
+    let result: SetCanisterResult = factory
+        .update_with_args(
+            caller(),
+            "fund_prepaid_balance",
+            (FundPrepaidBalanceArgs { cycles }, Some(payment)),
+        )
+        .expect("Failed to call fund_prepaid_balance");
+
+    assert_eq!(result, SetCanisterResult::Ok());
+}
+
+/// Returns the prepaid balance of [`caller`].
+pub fn prepaid_balance(factory: &PicBackend) -> u64 {
+    factory
+        .query(caller(), "prepaid_balance", ())
+        .expect("Failed to query prepaid_balance")
+}
+
+/// Returns the first page of the prepaid journal of `owner`.
+pub fn prepaid_journal(factory: &PicBackend, owner: Principal) -> Vec<PrepaidJournalEntry> {
+    factory
+        .query_with_args(owner, "prepaid_journal", (None::<u64>, None::<u64>))
+        .expect("Failed to query prepaid_journal")
+}
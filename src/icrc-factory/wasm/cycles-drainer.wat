@@ -0,0 +1,89 @@
+;; Cycles drainer.
+;;
+;; The factory reinstalls a canister with this module before deleting it, so that the cycles left on
+;; the canister are not burnt with it.
+;;
+;; `drain` may only be called by a controller. Its argument is forwarded as-is to the management
+;; canister's `deposit_cycles`, i.e. it must be the Candid encoding of `record { canister_id }`,
+;; together with the whole balance but a small reserve that pays for the call itself. It replies
+;; with the deposited amount as a 16-byte little-endian integer.
+;;
+;; Regenerate the binary with: wat2wasm cycles-drainer.wat -o cycles-drainer.wasm
+(module
+  (import "ic0" "msg_caller_size" (func $msg_caller_size (result i32)))
+  (import "ic0" "msg_caller_copy" (func $msg_caller_copy (param i32 i32 i32)))
+  (import "ic0" "is_controller" (func $is_controller (param i32 i32) (result i32)))
+  (import "ic0" "msg_arg_data_size" (func $msg_arg_data_size (result i32)))
+  (import "ic0" "msg_arg_data_copy" (func $msg_arg_data_copy (param i32 i32 i32)))
+  (import "ic0" "canister_cycle_balance128" (func $canister_cycle_balance128 (param i32)))
+  (import "ic0" "call_new" (func $call_new (param i32 i32 i32 i32 i32 i32 i32 i32)))
+  (import "ic0" "call_data_append" (func $call_data_append (param i32 i32)))
+  (import "ic0" "call_cycles_add128" (func $call_cycles_add128 (param i64 i64)))
+  (import "ic0" "call_perform" (func $call_perform (result i32)))
+  (import "ic0" "msg_reply_data_append" (func $msg_reply_data_append (param i32 i32)))
+  (import "ic0" "msg_reply" (func $msg_reply))
+  (import "ic0" "msg_reject" (func $msg_reject (param i32 i32)))
+  (import "ic0" "trap" (func $trap (param i32 i32)))
+
+  ;; Memory layout:
+  ;;   0..128   constant strings
+  ;;   128..144 deposited amount, as a little-endian u128
+  ;;   160..256 caller
+  ;;   256..    argument of `drain`
+  (memory 1)
+  (data (i32.const 0) "deposit_cycles")
+  (data (i32.const 16) "caller is not a controller")
+  (data (i32.const 48) "deposit_cycles was rejected")
+  (data (i32.const 80) "call_perform failed")
+  (data (i32.const 100) "not enough cycles")
+
+  (table 2 funcref)
+  (elem (i32.const 0) $on_reply $on_reject)
+
+  (func $on_reply (param $env i32)
+    (call $msg_reply_data_append (i32.const 128) (i32.const 16))
+    (call $msg_reply))
+
+  (func $on_reject (param $env i32)
+    (call $msg_reject (i32.const 48) (i32.const 27)))
+
+  (func $drain
+    (local $caller_size i32)
+    (local $arg_size i32)
+    (local $low i64)
+    (local $high i64)
+
+    (local.set $caller_size (call $msg_caller_size))
+    (call $msg_caller_copy (i32.const 160) (i32.const 0) (local.get $caller_size))
+    (if (i32.eqz (call $is_controller (i32.const 160) (local.get $caller_size)))
+      (then (call $trap (i32.const 16) (i32.const 26))))
+
+    (call $canister_cycle_balance128 (i32.const 128))
+    (local.set $low (i64.load (i32.const 128)))
+    (local.set $high (i64.load (i32.const 136)))
+
+    ;; Keep 10B cycles to pay for the call and its callback.
+    (if (i32.and
+          (i64.eqz (local.get $high))
+          (i64.lt_u (local.get $low) (i64.const 10000000000)))
+      (then (call $trap (i32.const 100) (i32.const 17))))
+    (if (i64.lt_u (local.get $low) (i64.const 10000000000))
+      (then (local.set $high (i64.sub (local.get $high) (i64.const 1)))))
+    (local.set $low (i64.sub (local.get $low) (i64.const 10000000000)))
+    (i64.store (i32.const 128) (local.get $low))
+    (i64.store (i32.const 136) (local.get $high))
+
+    ;; The management canister has an empty principal.
+    (call $call_new
+      (i32.const 0) (i32.const 0)
+      (i32.const 0) (i32.const 14)
+      (i32.const 0) (i32.const 0)
+      (i32.const 1) (i32.const 0))
+    (local.set $arg_size (call $msg_arg_data_size))
+    (call $msg_arg_data_copy (i32.const 256) (i32.const 0) (local.get $arg_size))
+    (call $call_data_append (i32.const 256) (local.get $arg_size))
+    (call $call_cycles_add128 (local.get $high) (local.get $low))
+    (if (call $call_perform)
+      (then (call $trap (i32.const 80) (i32.const 19)))))
+
+  (export "canister_update drain" (func $drain)))